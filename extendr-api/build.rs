@@ -1,4 +1,5 @@
 use std::env;
+use std::process::Command;
 
 fn main() {
     println!("cargo:rustc-check-cfg=cfg(use_objsxp)");
@@ -53,4 +54,16 @@ fn main() {
     if &*major >= "4" && &*minor >= "5" {
         println!("cargo:rustc-cfg=r_4_5");
     }
+
+    // The rustc version is part of the ABI of tagged external pointers,
+    // see `wrapper/type_tag.rs`.
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown rustc".to_string());
+    println!("cargo:rustc-env=EXTENDR_RUSTC_VERSION={rustc_version}");
 }
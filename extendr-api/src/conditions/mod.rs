@@ -407,6 +407,20 @@ pub fn format_warn_message(header: &str, body: &[&str]) -> String {
     msg
 }
 
+/// Signal a warning from Rust code that R must not unwind through.
+///
+/// The warning is raised under `R_UnwindProtect`, so when it is turned into
+/// an error, e.g. by `options(warn = 2)`, the error is returned instead.
+pub(crate) fn signal_warning(msg: &str) -> crate::Result<()> {
+    let c_msg = std::ffi::CString::new(msg).unwrap();
+    let msg_ptr = c_msg.as_ptr();
+    crate::catch_r_error(move || unsafe {
+        crate::Rf_warningcall(crate::R_NilValue, c"%s".as_ptr(), msg_ptr);
+        crate::R_NilValue
+    })
+    .map(|_| ())
+}
+
 /// Signal a warning with an rlang-style formatted message.
 #[macro_export]
 macro_rules! warn {
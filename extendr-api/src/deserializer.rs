@@ -2,24 +2,357 @@
 //!
 use crate::error::{Error, Result};
 use crate::na::CanBeNA;
-use crate::robj::{Attributes, Length, Robj, Types};
+use crate::robj::{Attributes, GetSexp, Length, Rinternals, Robj, Types};
 use crate::scalar::{Rbool, Rfloat, Rint};
-use crate::wrapper::{Doubles, Integers, List, Logicals, Rstr, Strings};
+use crate::serializer::{Serde, Variants, WithAttributes, ATTRIBUTES_TOKEN};
+use crate::wrapper::symbol::row_names_symbol;
+use crate::wrapper::{Doubles, Integers, List, Logicals, PairlistIter, Rstr, Strings};
 use crate::Rany;
+use extendr_ffi::ATTRIB;
+use serde::de::value::MapDeserializer;
+use serde::de::IntoDeserializer;
 use serde::de::{
-    Deserialize, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess,
-    Visitor,
+    Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
 };
 use serde::forward_to_deserialize_any;
+use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Options for converting R objects with [`from_robj_with`].
+///
+/// Both `NULL` and `NA` are read as `None`, both lists and atomic vectors as
+/// sequences, and 64-bit integers from doubles, strings or `integer64`, so
+/// only the representation of enums and of [`Robj`]s can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializerConfig {
+    variants: Variants,
+    keep_attributes: bool,
+}
+
+impl Default for DeserializerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeserializerConfig {
+    /// The options used by [`from_robj`].
+    pub const fn new() -> Self {
+        Self {
+            variants: Variants::External,
+            keep_attributes: false,
+        }
+    }
+
+    /// Set how enum variants are represented.
+    pub fn variants(mut self, variants: Variants) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Read [`Robj`] values as they are, with their attributes and class,
+    /// rather than converting them through serde.
+    pub fn keep_attributes(mut self, keep_attributes: bool) -> Self {
+        self.keep_attributes = keep_attributes;
+        self
+    }
+}
+
+// An R object with the options of the current conversion.
+// `&Robj` deserializes with the default options.
+#[derive(Clone, Copy)]
+struct RobjDeserializer<'de> {
+    robj: &'de Robj,
+    config: DeserializerConfig,
+}
+
+impl<'de> RobjDeserializer<'de> {
+    // Another object, with the same options.
+    fn with(self, robj: &'de Robj) -> Self {
+        Self {
+            robj,
+            config: self.config,
+        }
+    }
+}
 
 /// Convert any R object to a Deserialize object.
 pub fn from_robj<'de, T>(robj: &'de Robj) -> Result<T>
 where
     T: Deserialize<'de>,
 {
-    let t = T::deserialize(robj)?;
-    Ok(t)
+    from_robj_with(robj, DeserializerConfig::new())
+}
+
+/// Convert any R object to a Deserialize object, with the given options.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::deserializer::{from_robj_with, DeserializerConfig};
+/// use extendr_api::serializer::Variants;
+/// use serde::Deserialize;
+/// test! {
+///     #[derive(Deserialize, PartialEq, Debug)]
+///     enum Shape {
+///         Circle { r: f64 },
+///     }
+///
+///     let config = DeserializerConfig::new().variants(Variants::Adjacent {
+///         tag: "type",
+///         content: "value",
+///     });
+///     let robj: Robj = list!(type = "Circle", value = list!(r = 1.0)).into();
+///     assert_eq!(from_robj_with::<Shape>(&robj, config)?, Shape::Circle { r: 1.0 });
+/// }
+/// ```
+pub fn from_robj_with<'de, T>(robj: &'de Robj, config: DeserializerConfig) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(RobjDeserializer { robj, config })
+}
+
+impl<T: DeserializeOwned> Serde<T> {
+    /// Convert the argument `name` of a function, naming it in errors,
+    /// e.g. `model$weights[[2]]: Expected Numeric, got Strings`.
+    pub fn from_arg(robj: &Robj, name: &str) -> Result<Self> {
+        from_robj(robj).map(Serde).map_err(|err| err.at_path(name))
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<&Robj> for Serde<T> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        from_robj(robj).map(Serde)
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<Robj> for Serde<T> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+/// Convert the rows of a `data.frame` to records, i.e. structs or maps.
+///
+/// Each row is deserialized from a named list of its elements, where
+/// factors are strings and `NA` is `None`.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::deserializer::from_dataframe;
+/// use serde::Deserialize;
+/// test! {
+///     #[derive(Deserialize, PartialEq, Debug)]
+///     struct Pet {
+///         name: String,
+///         age: Option<i32>,
+///     }
+///
+///     let df = R!("data.frame(name = c('Tom', 'Rex'), age = c(3L, NA))")?;
+///     let pets: Vec<Pet> = from_dataframe(&df)?;
+///     assert_eq!(pets, vec![
+///         Pet { name: "Tom".into(), age: Some(3) },
+///         Pet { name: "Rex".into(), age: None },
+///     ]);
+/// }
+/// ```
+pub fn from_dataframe<T>(robj: &Robj) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    if !robj.is_frame() {
+        return Err(Error::ExpectedDataframe(robj.clone()));
+    }
+    let list = List::try_from(robj)?;
+    let names: Vec<&str> = list
+        .names()
+        .map(|names| names.collect())
+        .unwrap_or_default();
+    let columns: Vec<Robj> = list
+        .values()
+        .map(|column| {
+            if column.is_factor() {
+                column.as_character_factor()
+            } else {
+                column
+            }
+        })
+        .collect();
+    let nrow = robj
+        .get_attrib(row_names_symbol())
+        .map_or(0, |row_names| row_names.len());
+
+    (0..nrow)
+        .map(|row| {
+            let values = columns
+                .iter()
+                .map(|column| row_element(column, row))
+                .collect::<Result<Vec<_>>>()?;
+            let record: Robj = List::from_names_and_values(&names, values)?.into();
+            from_robj(&record)
+        })
+        .collect()
+}
+
+// Get one element of a data frame column.
+fn row_element(column: &Robj, row: usize) -> Result<Robj> {
+    match column.as_any() {
+        Rany::Logicals(v) => Ok(v.elt(row).into()),
+        Rany::Integers(v) => Ok(v.elt(row).into()),
+        Rany::Doubles(v) => Ok(v.elt(row).into()),
+        Rany::Strings(v) => Ok(v.elt(row).into()),
+        Rany::List(v) => v.elt(row),
+        _ => Err(Error::Other(format!(
+            "unsupported data frame column of type {:?}",
+            column.rtype()
+        ))),
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Element types of the R vectors that [`borrow_slice`] can borrow.
+pub trait SliceElement: Copy + private::Sealed + 'static {
+    #[doc(hidden)]
+    const TOKEN: &'static str;
+}
+
+impl private::Sealed for f64 {}
+impl private::Sealed for i32 {}
+impl private::Sealed for u8 {}
+
+impl SliceElement for f64 {
+    const TOKEN: &'static str = "$extendr::private::[f64]";
+}
+
+impl SliceElement for i32 {
+    const TOKEN: &'static str = "$extendr::private::[i32]";
+}
+
+impl SliceElement for u8 {
+    const TOKEN: &'static str = "$extendr::private::[u8]";
+}
+
+/// Borrow the data of a double, integer or raw vector as a slice, without copying it.
+///
+/// Use this with `#[serde(borrow, deserialize_with = "borrow_slice")]` on
+/// `&[f64]`, `&[i32]` and `&[u8]` fields. `NA`s are their raw values,
+/// e.g. `i32::MIN` in integer vectors. Strings are borrowed without this,
+/// in `&str` and `Cow<str>` fields. This only works with R objects.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::deserializer::{borrow_slice, from_robj};
+/// use serde::Deserialize;
+/// test! {
+///     #[derive(Deserialize)]
+///     struct Series<'a> {
+///         name: &'a str,
+///         #[serde(borrow, deserialize_with = "borrow_slice")]
+///         values: &'a [f64],
+///     }
+///
+///     let robj: Robj = list!(name = "x", values = [1.0, 2.0, 3.0]).into();
+///     let series: Series = from_robj(&robj)?;
+///     assert_eq!(series.name, "x");
+///     assert_eq!(series.values, [1.0, 2.0, 3.0]);
+///     assert_eq!(series.values.as_ptr(), robj.dollar("values")?.as_real_slice().unwrap().as_ptr());
+/// }
+/// ```
+pub fn borrow_slice<'de, D, T>(deserializer: D) -> std::result::Result<&'de [T], D::Error>
+where
+    D: Deserializer<'de>,
+    T: SliceElement,
+{
+    deserializer.deserialize_newtype_struct(T::TOKEN, SliceVisitor(std::marker::PhantomData))
+}
+
+struct SliceVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: SliceElement> Visitor<'de> for SliceVisitor<T> {
+    type Value = &'de [T];
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "an R vector of {}", std::any::type_name::<T>())
+    }
+
+    fn visit_borrowed_bytes<E>(self, bytes: &'de [u8]) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        // Empty vectors may have a dangling data pointer.
+        if bytes.is_empty() {
+            return Ok(&[]);
+        }
+        let size = std::mem::size_of::<T>();
+        if bytes.len() % size != 0 || bytes.as_ptr().align_offset(std::mem::align_of::<T>()) != 0 {
+            return Err(E::custom("misaligned vector data"));
+        }
+        // Every bit pattern is a valid f64, i32 or u8.
+        Ok(unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const T, bytes.len() / size) })
+    }
+}
+
+// The data of a vector, if `borrow_slice` asks for it.
+fn slice_bytes<'de>(robj: &'de Robj, name: &str) -> Result<Option<&'de [u8]>> {
+    fn as_bytes<T>(slice: &[T]) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(slice.as_ptr() as *const u8, std::mem::size_of_val(slice))
+        }
+    }
+
+    let bytes = if name == f64::TOKEN {
+        match robj.as_real_slice() {
+            Some(slice) if !robj.is_integer64() => as_bytes(slice),
+            _ => return Err(Error::ExpectedReal(robj.clone())),
+        }
+    } else if name == i32::TOKEN {
+        match robj.as_integer_slice() {
+            Some(slice) => as_bytes(slice),
+            _ => return Err(Error::ExpectedInteger(robj.clone())),
+        }
+    } else if name == u8::TOKEN {
+        match robj.as_raw_slice() {
+            Some(slice) => slice,
+            _ => return Err(Error::ExpectedRaw(robj.clone())),
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(bytes))
+}
+
+// All attributes of an object, read straight from its attribute pairlist.
+fn attributes(robj: &Robj) -> Vec<(String, Robj)> {
+    let iter = PairlistIter {
+        robj: robj.clone(),
+        list_elem: unsafe { ATTRIB(robj.get()) },
+    };
+    iter.map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+// 64-bit integers may also be stored as strings.
+fn to_integer<T>(robj: &Robj) -> Result<T>
+where
+    T: TryFrom<Robj, Error = Error> + FromStr,
+{
+    if robj.is_string() && robj.len() == 1 {
+        let value = <&str>::try_from(robj)?;
+        value
+            .parse()
+            .map_err(|_| Error::ExpectedNumeric(robj.clone()))
+    } else {
+        T::try_from(robj.clone())
+    }
 }
 
 // Allow errors to propagate to extendr errors.
@@ -35,6 +368,8 @@ impl serde::de::Error for Error {
 // Convert unnamed lists to sequences.
 struct ListGetter<'a> {
     list: &'a [Robj],
+    index: usize,
+    config: DeserializerConfig,
 }
 
 impl<'de> SeqAccess<'de> for ListGetter<'de> {
@@ -44,12 +379,17 @@ impl<'de> SeqAccess<'de> for ListGetter<'de> {
     where
         T: DeserializeSeed<'de>,
     {
-        if self.list.is_empty() {
+        if self.index == self.list.len() {
             Ok(None)
         } else {
-            let e = &self.list[0];
-            self.list = &self.list[1..];
-            seed.deserialize(e).map(Some)
+            let e = RobjDeserializer {
+                robj: &self.list[self.index],
+                config: self.config,
+            };
+            self.index += 1;
+            seed.deserialize(e)
+                .map(Some)
+                .map_err(|err| err.at_path(&format!("[[{}]]", self.index)))
         }
     }
 }
@@ -58,6 +398,8 @@ impl<'de> SeqAccess<'de> for ListGetter<'de> {
 struct NamedListGetter<'a> {
     keys: &'a [Rstr],
     values: &'a [Robj],
+    index: usize,
+    config: DeserializerConfig,
 }
 
 impl<'de> MapAccess<'de> for NamedListGetter<'de> {
@@ -67,12 +409,10 @@ impl<'de> MapAccess<'de> for NamedListGetter<'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        if self.keys.is_empty() {
+        if self.index == self.keys.len() {
             Ok(None)
         } else {
-            let e = &self.keys[0];
-            self.keys = &self.keys[1..];
-            seed.deserialize(e).map(Some)
+            seed.deserialize(&self.keys[self.index]).map(Some)
         }
     }
 
@@ -80,15 +420,46 @@ impl<'de> MapAccess<'de> for NamedListGetter<'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        let e = &self.values[0];
-        self.values = &self.values[1..];
+        let key = &self.keys[self.index];
+        let e = RobjDeserializer {
+            robj: &self.values[self.index],
+            config: self.config,
+        };
+        self.index += 1;
         seed.deserialize(e)
+            .map_err(|err| err.at_path(&format!("${}", key)))
+    }
+}
+
+// Convert character vectors to sequences.
+struct StringsGetter<'a> {
+    list: &'a [Rstr],
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for StringsGetter<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.index == self.list.len() {
+            Ok(None)
+        } else {
+            let e = &self.list[self.index];
+            self.index += 1;
+            seed.deserialize(e)
+                .map(Some)
+                .map_err(|err| err.at_path(&format!("[{}]", self.index)))
+        }
     }
 }
 
 // Allow us to use Integers, Doubles and Logicals.
 struct SliceGetter<'a, E> {
     list: &'a [E],
+    index: usize,
 }
 
 // Allow us to use Integers and Rint.
@@ -179,15 +550,26 @@ impl<'de> Deserializer<'de> for &'de Rstr {
         visitor.visit_borrowed_str(self)
     }
 
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_na() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char string
-        str bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        str bytes byte_buf unit unit_struct newtype_struct seq tuple
         tuple_struct map struct enum ignored_any
     }
 }
 
 // Get the variant name and content of an enum.
-impl<'de> EnumAccess<'de> for &'de Robj {
+impl<'de> EnumAccess<'de> for RobjDeserializer<'de> {
     type Error = Error;
     type Variant = Self;
 
@@ -195,18 +577,39 @@ impl<'de> EnumAccess<'de> for &'de Robj {
     where
         V: DeserializeSeed<'de>,
     {
-        match self.as_any() {
+        if let Variants::Adjacent { tag, content } = self.config.variants {
+            return match self.robj.as_any() {
+                Rany::List(list) => {
+                    let keys = self
+                        .robj
+                        .get_attrib(crate::wrapper::symbol::names_symbol())
+                        .and_then(|keys| Strings::try_from(keys).ok())
+                        .ok_or_else(|| Error::Other("Expected named List for enum".into()))?;
+                    let keys = keys.as_slice();
+                    let values = list.as_slice();
+                    let position = |name: &str| keys.iter().position(|key| key == name);
+                    let tag = position(tag)
+                        .ok_or_else(|| Error::Other(format!("Expected `{tag}` for enum")))?;
+                    let variant = seed.deserialize(self.with(&values[tag]))?;
+                    // Unit variants have no content.
+                    let content = position(content).map_or(self, |i| self.with(&values[i]));
+                    Ok((variant, content))
+                }
+                _ => Err(Error::Other("Expected List for enum".into())),
+            };
+        }
+        match self.robj.as_any() {
             Rany::Strings(s) if s.len() == 1 => {
                 let variant = seed.deserialize(self)?;
                 Ok((variant, self))
             }
             Rany::List(list) if list.len() == 1 => {
-                if let Some(keys) = self.get_attrib(crate::wrapper::symbol::names_symbol()) {
+                if let Some(keys) = self.robj.get_attrib(crate::wrapper::symbol::names_symbol()) {
                     if let Ok(keys) = Strings::try_from(keys) {
                         let keys = keys.as_slice();
                         let values = &list.as_slice()[0];
                         let variant = seed.deserialize(&keys[0])?;
-                        return Ok((variant, values));
+                        return Ok((variant, self.with(values)));
                     }
                 }
                 Err(Error::Other("Expected named List for enum".into()))
@@ -217,7 +620,7 @@ impl<'de> EnumAccess<'de> for &'de Robj {
 }
 
 // Decode enum variants of various kinds.
-impl<'de> VariantAccess<'de> for &'de Robj {
+impl<'de> VariantAccess<'de> for RobjDeserializer<'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -258,26 +661,28 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        if self.list.is_empty() {
+        if self.index == self.list.len() {
             Ok(None)
         } else {
-            let res = seed.deserialize(self.list[0])?;
-            self.list = &self.list[1..];
-            Ok(Some(res))
+            let e = self.list[self.index];
+            self.index += 1;
+            seed.deserialize(e)
+                .map(Some)
+                .map_err(|err| Error::from(err).at_path(&format!("[{}]", self.index)))
         }
     }
 }
 
 // Given an Robj, generate a value of many kinds.
-impl<'de> Deserializer<'de> for &'de Robj {
+impl<'de> Deserializer<'de> for RobjDeserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let len = self.len();
-        match self.as_any() {
+        let len = self.robj.len();
+        match self.robj.as_any() {
             Rany::Null(_) => self.deserialize_unit(visitor),
             Rany::Integers(_v) => {
                 if len == 1 {
@@ -310,7 +715,7 @@ impl<'de> Deserializer<'de> for &'de Robj {
             }
             _ => Err(Error::Other(format!(
                 "deserialize_any: unexpected {:?}",
-                self.rtype()
+                self.robj.rtype()
             ))),
         }
     }
@@ -319,10 +724,10 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        if let Rany::Null(_) = self.as_any() {
+        if let Rany::Null(_) = self.robj.as_any() {
             visitor.visit_unit()
         } else {
-            Err(Error::ExpectedNull(self.clone()))
+            Err(Error::ExpectedNull(self.robj.clone()))
         }
     }
 
@@ -330,129 +735,129 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_bool(bool::try_from(self.clone())?)
+        visitor.visit_bool(bool::try_from(self.robj.clone())?)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(i8::try_from(self.clone())?)
+        visitor.visit_i8(i8::try_from(self.robj.clone())?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(i16::try_from(self.clone())?)
+        visitor.visit_i16(i16::try_from(self.robj.clone())?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(i32::try_from(self.clone())?)
+        visitor.visit_i32(i32::try_from(self.robj.clone())?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(i64::try_from(self.clone())?)
+        visitor.visit_i64(to_integer(self.robj)?)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i128(i64::try_from(self.clone())? as i128)
+        visitor.visit_i128(to_integer::<i64>(self.robj)? as i128)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(u8::try_from(self.clone())?)
+        visitor.visit_u8(u8::try_from(self.robj.clone())?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(u16::try_from(self.clone())?)
+        visitor.visit_u16(u16::try_from(self.robj.clone())?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(u32::try_from(self.clone())?)
+        visitor.visit_u32(u32::try_from(self.robj.clone())?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(u64::try_from(self.clone())?)
+        visitor.visit_u64(to_integer(self.robj)?)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u128(u64::try_from(self.clone())? as u128)
+        visitor.visit_u128(to_integer::<u64>(self.robj)? as u128)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(f32::try_from(self.clone())?)
+        visitor.visit_f32(f32::try_from(self.robj.clone())?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(f64::try_from(self.clone())?)
+        visitor.visit_f64(f64::try_from(self.robj.clone())?)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let s = <&str>::try_from(self)?;
+        let s = <&str>::try_from(self.robj)?;
         let mut c = s.chars();
         if let Some(ch) = c.next() {
             if c.next().is_none() {
                 return visitor.visit_char(ch);
             }
         }
-        Err(Error::ExpectedString(self.clone()))
+        Err(Error::ExpectedString(self.robj.clone()))
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_borrowed_str(<&str>::try_from(self)?)
+        visitor.visit_borrowed_str(<&str>::try_from(self.robj)?)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_string(<&str>::try_from(self)?.into())
+        visitor.visit_string(<&str>::try_from(self.robj)?.into())
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        if let Rany::Raw(val) = self.as_any() {
-            visitor.visit_bytes(val.as_slice())
+        if let Some(bytes) = self.robj.as_raw_slice() {
+            visitor.visit_borrowed_bytes(bytes)
         } else {
-            Err(Error::ExpectedRaw(self.clone()))
+            Err(Error::ExpectedRaw(self.robj.clone()))
         }
     }
 
@@ -460,10 +865,10 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        if let Rany::Raw(val) = self.as_any() {
+        if let Rany::Raw(val) = self.robj.as_any() {
             visitor.visit_byte_buf(val.as_slice().to_owned())
         } else {
-            Err(Error::ExpectedRaw(self.clone()))
+            Err(Error::ExpectedRaw(self.robj.clone()))
         }
     }
 
@@ -471,9 +876,9 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        if let Rany::Null(_) = self.as_any() {
+        if let Rany::Null(_) = self.robj.as_any() {
             visitor.visit_none()
-        } else if self.is_na() {
+        } else if self.robj.is_na() {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -487,10 +892,16 @@ impl<'de> Deserializer<'de> for &'de Robj {
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.config.keep_attributes && is_kept_robj_visitor::<V>() {
+            return visit_kept_robj(self.robj, visitor, self);
+        }
+        if let Some(bytes) = slice_bytes(self.robj, name)? {
+            return visitor.visit_borrowed_bytes(bytes);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -517,34 +928,44 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        match self.as_any() {
+        match self.robj.as_any() {
             Rany::List(val) => {
                 let lg = ListGetter {
                     list: val.as_slice(),
+                    index: 0,
+                    config: self.config,
                 };
                 Ok(visitor.visit_seq(lg)?)
             }
             Rany::Integers(val) => {
-                let lg = SliceGetter { list: val };
+                let lg = SliceGetter {
+                    list: val,
+                    index: 0,
+                };
                 Ok(visitor.visit_seq(lg)?)
             }
             Rany::Doubles(val) => {
-                let lg = SliceGetter { list: val };
+                let lg = SliceGetter {
+                    list: val,
+                    index: 0,
+                };
                 Ok(visitor.visit_seq(lg)?)
             }
             Rany::Logicals(val) => {
-                let lg = SliceGetter { list: val };
+                let lg = SliceGetter {
+                    list: val,
+                    index: 0,
+                };
                 Ok(visitor.visit_seq(lg)?)
             }
-            Rany::Strings(_val) => {
-                // Grubby hack that will go away once PRs are merged.
-                // use std::convert::TryInto;
-                // let val : Strings = val.clone().try_into().unwrap();
-                // let lg = StringGetter { list: &*val };
-                // Ok(visitor.visit_seq(lg)?)
-                unimplemented!("Deserialize shortcut for Strings");
+            Rany::Strings(val) => {
+                let lg = StringsGetter {
+                    list: val.as_slice(),
+                    index: 0,
+                };
+                Ok(visitor.visit_seq(lg)?)
             }
-            _ => Err(Error::ExpectedList(self.clone())),
+            _ => Err(Error::ExpectedList(self.robj.clone())),
         }
     }
 
@@ -552,33 +973,41 @@ impl<'de> Deserializer<'de> for &'de Robj {
     where
         V: Visitor<'de>,
     {
-        match self.as_any() {
+        match self.robj.as_any() {
             Rany::List(val) => {
-                if let Some(keys) = self.get_attrib(crate::wrapper::symbol::names_symbol()) {
+                if let Some(keys) = self.robj.get_attrib(crate::wrapper::symbol::names_symbol()) {
                     if let Ok(keys) = Strings::try_from(keys) {
                         let keys = keys.as_slice();
                         let lg = NamedListGetter {
                             keys,
                             values: val.as_slice(),
+                            index: 0,
+                            config: self.config,
                         };
                         return visitor.visit_map(lg);
                     }
                 }
-                Err(Error::ExpectedList(self.clone()))
+                Err(Error::ExpectedList(self.robj.clone()))
             }
-            _ => Err(Error::ExpectedList(self.clone())),
+            _ => Err(Error::ExpectedList(self.robj.clone())),
         }
     }
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         _fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == ATTRIBUTES_TOKEN {
+            return visitor.visit_map(WithAttributesAccess {
+                value: Some(self),
+                attributes: Some(attributes(self.robj)),
+            });
+        }
         self.deserialize_map(visitor)
     }
 
@@ -609,6 +1038,43 @@ impl<'de> Deserializer<'de> for &'de Robj {
     }
 }
 
+// `&Robj` deserializes with the default options.
+macro_rules! forward_to_robj_deserializer {
+    ($($method: ident $(($($arg: ident: $type: ty),*))?)*) => {
+        $(
+            fn $method<V>(self, $($($arg: $type,)*)? visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                RobjDeserializer {
+                    robj: self,
+                    config: DeserializerConfig::new(),
+                }
+                .$method($($($arg,)*)? visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for &'de Robj {
+    type Error = Error;
+
+    forward_to_robj_deserializer! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier deserialize_ignored_any
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
+
 struct RintVisitor;
 
 impl<'de> Visitor<'de> for RintVisitor {
@@ -840,7 +1306,138 @@ impl<'de> Deserialize<'de> for Robj {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(RobjVisitor)
+        // Deserializers that borrow have no `TypeId`, so extendr's own ones are
+        // recognised by name. They hand over the object itself, for
+        // `keep_attributes`, see `visit_kept_robj`.
+        let name = std::any::type_name::<D>();
+        if name == std::any::type_name::<RobjDeserializer>()
+            || name == std::any::type_name::<KeptRobj>()
+        {
+            deserializer.deserialize_newtype_struct("Robj", KeptRobjVisitor)
+        } else {
+            deserializer.deserialize_any(RobjVisitor)
+        }
+    }
+}
+
+struct KeptRobjVisitor;
+
+impl<'de> Visitor<'de> for KeptRobjVisitor {
+    type Value = Robj;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a value convertable to a Robj")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match DESERIALIZED_ROBJ.with(|stash| stash.take()) {
+            Some(robj) => Ok(robj),
+            None => deserializer.deserialize_any(RobjVisitor),
+        }
+    }
+}
+
+thread_local! {
+    // The object handed to `KeptRobjVisitor::visit_newtype_struct`.
+    static DESERIALIZED_ROBJ: RefCell<Option<Robj>> = const { RefCell::new(None) };
+}
+
+fn is_kept_robj_visitor<V>() -> bool {
+    std::any::type_name::<V>() == std::any::type_name::<KeptRobjVisitor>()
+}
+
+// Hand `robj` itself to a `KeptRobjVisitor`. It is stashed right before the
+// visitor takes it, so no other code can see it.
+fn visit_kept_robj<'de, V, D>(robj: &Robj, visitor: V, deserializer: D) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+    D: Deserializer<'de, Error = Error>,
+{
+    DESERIALIZED_ROBJ.with(|stash| *stash.borrow_mut() = Some(robj.clone()));
+    let result = visitor.visit_newtype_struct(deserializer);
+    DESERIALIZED_ROBJ.with(|stash| *stash.borrow_mut() = None);
+    result
+}
+
+// An attribute of an object, which is always kept as it is.
+struct KeptRobj(Robj);
+
+impl<'de> Deserializer<'de> for KeptRobj {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Other("attributes can only be read as Robj".into()))
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if is_kept_robj_visitor::<V>() {
+            let robj = self.0.clone();
+            visit_kept_robj(&robj, visitor, self)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+// The `value` and `attributes` of a `WithAttributes`.
+struct WithAttributesAccess<'de> {
+    value: Option<RobjDeserializer<'de>>,
+    attributes: Option<Vec<(String, Robj)>>,
+}
+
+impl<'de> MapAccess<'de> for WithAttributesAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let key = if self.value.is_some() {
+            "value"
+        } else if self.attributes.is_some() {
+            "attributes"
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if let Some(value) = self.value.take() {
+            return seed.deserialize(value);
+        }
+        let attributes = self.attributes.take().unwrap_or_default();
+        seed.deserialize(MapDeserializer::new(
+            attributes
+                .into_iter()
+                .map(|(name, value)| (name, KeptRobj(value))),
+        ))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for KeptRobj {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
     }
 }
 
@@ -1151,3 +1748,77 @@ impl<'de> Deserialize<'de> for Rstr {
         deserializer.deserialize_any(RstrVisitor)
     }
 }
+
+struct WithAttributesVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for WithAttributesVisitor<T> {
+    type Value = WithAttributes<T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a value with attributes")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut value = None;
+        let mut attributes = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "value" => value = Some(map.next_value()?),
+                "attributes" => attributes = map.next_value::<AttributeMap>()?.0,
+                _ => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
+            }
+        }
+        let value = value.ok_or_else(|| serde::de::Error::missing_field("value"))?;
+        Ok(WithAttributes { value, attributes })
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for WithAttributes<T> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            ATTRIBUTES_TOKEN,
+            &["value", "attributes"],
+            WithAttributesVisitor(std::marker::PhantomData),
+        )
+    }
+}
+
+struct AttributeMap(Vec<(String, Robj)>);
+
+struct AttributeMapVisitor;
+
+impl<'de> Visitor<'de> for AttributeMapVisitor {
+    type Value = AttributeMap;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of attributes")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut attributes = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            attributes.push(entry);
+        }
+        Ok(AttributeMap(attributes))
+    }
+}
+
+impl<'de> Deserialize<'de> for AttributeMap {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(AttributeMapVisitor)
+    }
+}
@@ -51,6 +51,7 @@ pub enum Error {
     ExpectedRstr(Robj),
     ExpectedLogical(Robj),
     ExpectedInteger(Robj),
+    ExpectedInteger64(Robj),
     ExpectedReal(Robj),
     ExpectedComplex(Robj),
     ExpectedString(Robj),
@@ -74,9 +75,14 @@ pub enum Error {
     ExpectedNumeric(Robj),
     ExpectedAltrep(Robj),
     ExpectedDataframe(Robj),
+    ExpectedDate(Robj),
+    ExpectedDateTime(Robj),
+    ExpectedDifftime(Robj),
 
     OutOfRange(Robj),
     MustNotBeNA(Robj),
+    /// A string that is marked as `"bytes"`, or is not valid in its encoding.
+    ExpectedUtf8(Robj),
     ExpectedWholeNumber(Robj, ConversionError),
     ExpectedNonZeroLength(Robj),
     ExpectedLength(usize),
@@ -86,9 +92,13 @@ pub enum Error {
     NoGraphicsDevices(Robj),
 
     ExpectedExternalPtrType(Robj, String),
+    IncompatibleExternalPtrType(Robj, String),
     ExpectedExternalNonNullPtr(Robj),
     ExpectedExternalPtrReference,
     Other(String),
+    /// An error converting an element of a nested object, at an R path
+    /// such as `x$points[[2]]`.
+    AtPath(String, Box<Error>),
 
     #[cfg(feature = "ndarray")]
     NDArrayShapeError(ndarray::ShapeError),
@@ -99,6 +109,17 @@ pub enum Error {
     TryFromSliceError(String),
 }
 
+impl Error {
+    // Prefix the path of an error in an element with the element's `segment`,
+    // e.g. `[[3]]` turns `$points[[2]]` into `[[3]]$points[[2]]`.
+    pub(crate) fn at_path(self, segment: &str) -> Error {
+        match self {
+            Error::AtPath(path, err) => Error::AtPath(format!("{}{}", segment, path), err),
+            err => Error::AtPath(segment.to_string(), Box::new(err)),
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -135,6 +156,9 @@ impl std::fmt::Display for Error {
             }
             Error::ExpectedLogical(robj) => write!(f, "Expected Logicals got {:?}", robj.rtype()),
             Error::ExpectedInteger(robj) => write!(f, "Expected Integers got {:?}", robj.rtype()),
+            Error::ExpectedInteger64(robj) => {
+                write!(f, "Expected Integers64 got {:?}", robj.rtype())
+            }
             Error::ExpectedReal(robj) => write!(f, "Expected Doubles got {:?}", robj.rtype()),
             Error::ExpectedComplex(robj) => write!(f, "Expected Complexes got {:?}", robj.rtype()),
             Error::ExpectedString(robj) => write!(f, "Expected Strings got {:?}", robj.rtype()),
@@ -166,9 +190,21 @@ impl std::fmt::Display for Error {
             Error::ExpectedDataframe(robj) => {
                 write!(f, "Expected Dataframe, got {:?}", robj.rtype())
             }
+            Error::ExpectedDate(robj) => write!(f, "Expected Date, got {:?}", robj.rtype()),
+            Error::ExpectedDateTime(robj) => {
+                write!(f, "Expected POSIXct, got {:?}", robj.rtype())
+            }
+            Error::ExpectedDifftime(robj) => {
+                write!(f, "Expected difftime, got {:?}", robj.rtype())
+            }
 
             Error::OutOfRange(_robj) => write!(f, "Out of range."),
             Error::MustNotBeNA(_robj) => write!(f, "Must not be NA."),
+            Error::ExpectedUtf8(robj) => write!(
+                f,
+                "Expected a string that can be translated to UTF-8, got {:?}",
+                robj
+            ),
             Error::ExpectedNonZeroLength(_robj) => write!(f, "Expected non zero length"),
             Error::ExpectedLength(len) => write!(f, "Expected length: {len}"),
             Error::OutOfLimits(robj) => write!(f, "The value is too big: {:?}", robj),
@@ -178,6 +214,9 @@ impl std::fmt::Display for Error {
             Error::ExpectedExternalPtrType(_robj, type_name) => {
                 write!(f, "Incorrect external pointer type {}", type_name)
             }
+            Error::IncompatibleExternalPtrType(_robj, reason) => {
+                write!(f, "Incompatible external pointer type: {}", reason)
+            }
             Error::ExpectedExternalNonNullPtr(robj) => {
                 write!(
                     f,
@@ -192,6 +231,7 @@ impl std::fmt::Display for Error {
             // this is very unlikely to occur, and it would just say: Rust error: could not convert slice to array
             Error::TryFromSliceError(std_error) => write!(f, "Rust error: {}", std_error),
             Error::Other(str) => write!(f, "{}", str),
+            Error::AtPath(path, err) => write!(f, "{}: {}", path, err),
 
             Error::ExpectedWholeNumber(robj, conversion_error) => {
                 write!(
@@ -237,6 +277,12 @@ impl From<Box<dyn std::error::Error>> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        Error::Other(err.to_string())
+    }
+}
+
 impl From<&str> for Error {
     fn from(err: &str) -> Error {
         Error::Other(err.to_string())
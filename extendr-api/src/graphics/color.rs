@@ -1,3 +1,24 @@
+//! Colors as used by R's graphics engine.
+//!
+//! R represents a color as a packed 32 bit integer (`rcolor`) with the red,
+//! green, blue and alpha components in the lowest to the highest byte.
+//! [Color] wraps this representation and adds parsing, formatting,
+//! conversions between color spaces, blending and palettes.
+//!
+//! ```
+//! use extendr_api::graphics::color::Color;
+//! use std::str::FromStr;
+//!
+//! let red = Color::from_str("#FF000080").unwrap();
+//! assert_eq!(red.alpha(), 0x80);
+//! assert_eq!(red.to_string(), "#FF000080");
+//! ```
+
+use crate as extendr_api;
+use crate::*;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Color(i32);
 
 impl Color {
@@ -6,6 +27,11 @@ impl Color {
         self.0
     }
 
+    /// Wrap an R color (`rcolor`) as used by the graphics engine.
+    pub fn from_i32(rcolor: i32) -> Color {
+        Color(rcolor)
+    }
+
     /// Generate a color from a CSS-like hex number.
     /// eg. `Color::hex(0xF0F8FF)`
     pub fn hex(hex: u32) -> Color {
@@ -33,6 +59,525 @@ impl Color {
     pub fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
         Color(red as i32 | (green as i32) << 8 | (blue as i32) << 16 | (alpha as i32) << 24)
     }
+
+    /// Fully transparent white, which R uses for `NA` and `"transparent"`.
+    pub fn transparent() -> Color {
+        Color::rgba(0xff, 0xff, 0xff, 0)
+    }
+
+    /// The red component (0-255).
+    pub fn red(&self) -> u8 {
+        (self.0 & 0xff) as u8
+    }
+
+    /// The green component (0-255).
+    pub fn green(&self) -> u8 {
+        ((self.0 >> 8) & 0xff) as u8
+    }
+
+    /// The blue component (0-255).
+    pub fn blue(&self) -> u8 {
+        ((self.0 >> 16) & 0xff) as u8
+    }
+
+    /// The alpha component (0-255), 0 being fully transparent.
+    pub fn alpha(&self) -> u8 {
+        ((self.0 >> 24) & 0xff) as u8
+    }
+
+    /// A copy of this color with a different alpha component.
+    pub fn with_alpha(&self, alpha: u8) -> Color {
+        Color::rgba(self.red(), self.green(), self.blue(), alpha)
+    }
+
+    /// True if the color is fully opaque.
+    pub fn is_opaque(&self) -> bool {
+        self.alpha() == 0xff
+    }
+
+    /// True if the color is fully transparent.
+    pub fn is_transparent(&self) -> bool {
+        self.alpha() == 0
+    }
+
+    /// Parse a hex color string as accepted by R: `#RGB`, `#RGBA`,
+    /// `#RRGGBB` or `#RRGGBBAA`.
+    ///
+    /// Returns `None` if the string is not a hex color.
+    pub fn from_hex_str(s: &str) -> Option<Color> {
+        let digits = s.strip_prefix('#')?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u32::from_str_radix(digits, 16).ok()?;
+        let nibble = |shift: u32| (((value >> shift) & 0xf) * 0x11) as u8;
+        let byte = |shift: u32| ((value >> shift) & 0xff) as u8;
+        match digits.len() {
+            3 => Some(Color::rgb(nibble(8), nibble(4), nibble(0))),
+            4 => Some(Color::rgba(nibble(12), nibble(8), nibble(4), nibble(0))),
+            6 => Some(Color::rgb(byte(16), byte(8), byte(0))),
+            8 => Some(Color::rgba(byte(24), byte(16), byte(8), byte(0))),
+            _ => None,
+        }
+    }
+
+    /// Format as `#RRGGBB`, or `#RRGGBBAA` if the color is not opaque.
+    ///
+    /// This is the same format as R's `rgb()` and the inverse of [Color::from_hex_str].
+    pub fn to_hex_string(&self) -> String {
+        if self.is_opaque() {
+            format!("#{:02X}{:02X}{:02X}", self.red(), self.green(), self.blue())
+        } else {
+            format!(
+                "#{:02X}{:02X}{:02X}{:02X}",
+                self.red(),
+                self.green(),
+                self.blue(),
+                self.alpha()
+            )
+        }
+    }
+
+    /// Convert R colors to [Color]s with the same rules as `col2rgb(x, alpha = TRUE)`.
+    ///
+    /// `x` may be a character vector of hex strings, color names (see `colors()`)
+    /// and palette indices, or a numeric vector of palette indices.
+    /// `NA` is transparent white.
+    /// Hex strings are parsed in Rust, anything else is resolved by R.
+    pub fn from_robj(x: &Robj) -> Result<Vec<Color>> {
+        if let Some(strings) = x.as_str_iter() {
+            let parsed = strings
+                .map(|s| {
+                    if s.is_na() {
+                        Some(Color::transparent())
+                    } else {
+                        Color::from_hex_str(s)
+                    }
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(colors) = parsed {
+                return Ok(colors);
+            }
+        }
+        col2rgb(x)
+    }
+
+    /// The red, green and blue components scaled to `[0, 1]`.
+    pub fn to_rgb_f64(&self) -> (f64, f64, f64) {
+        (
+            self.red() as f64 / 255.,
+            self.green() as f64 / 255.,
+            self.blue() as f64 / 255.,
+        )
+    }
+
+    /// Generate an opaque color from red, green and blue components in `[0, 1]`.
+    /// Out of range values are clamped.
+    pub fn from_rgb_f64(red: f64, green: f64, blue: f64) -> Color {
+        Color::rgb(scale_to_u8(red), scale_to_u8(green), scale_to_u8(blue))
+    }
+
+    /// Convert to hue, saturation and value, as `rgb2hsv()` does.
+    pub fn to_hsv(&self) -> Hsv {
+        let (r, g, b) = self.to_rgb_f64();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let s = if max > 0. { delta / max } else { 0. };
+        let h = if delta == 0. {
+            0.
+        } else if max == r {
+            ((g - b) / delta).rem_euclid(6.) / 6.
+        } else if max == g {
+            ((b - r) / delta + 2.) / 6.
+        } else {
+            ((r - g) / delta + 4.) / 6.
+        };
+        Hsv { h, s, v: max }
+    }
+
+    /// Generate an opaque color from hue, saturation and value, as `hsv()` does.
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let Hsv { h, s, v } = hsv;
+        let h6 = h.rem_euclid(1.) * 6.;
+        let i = h6.floor();
+        let f = h6 - i;
+        let p = v * (1. - s);
+        let q = v * (1. - s * f);
+        let t = v * (1. - s * (1. - f));
+        let (r, g, b) = match i as i32 {
+            0 => (v, t, p),
+            1 => (q, v, p),
+            2 => (p, v, t),
+            3 => (p, q, v),
+            4 => (t, p, v),
+            _ => (v, p, q),
+        };
+        Color::from_rgb_f64(r, g, b)
+    }
+
+    /// Convert to linear (gamma expanded) sRGB.
+    pub fn to_linear_rgb(&self) -> LinearRgb {
+        let (r, g, b) = self.to_rgb_f64();
+        LinearRgb {
+            r: srgb_to_linear(r),
+            g: srgb_to_linear(g),
+            b: srgb_to_linear(b),
+        }
+    }
+
+    /// Generate an opaque color from linear sRGB. Out of gamut values are clamped.
+    pub fn from_linear_rgb(rgb: LinearRgb) -> Color {
+        Color::from_rgb_f64(
+            linear_to_srgb(rgb.r),
+            linear_to_srgb(rgb.g),
+            linear_to_srgb(rgb.b),
+        )
+    }
+
+    /// Convert to CIE L*a*b* with a D65 white point.
+    pub fn to_lab(&self) -> Lab {
+        let (x, y, z) = self.to_linear_rgb().to_xyz();
+        let f = |t: f64| {
+            if t > LAB_EPSILON {
+                t.cbrt()
+            } else {
+                (LAB_KAPPA * t + 16.) / 116.
+            }
+        };
+        let fx = f(x / WHITE_X);
+        let fy = f(y / WHITE_Y);
+        let fz = f(z / WHITE_Z);
+        Lab {
+            l: 116. * fy - 16.,
+            a: 500. * (fx - fy),
+            b: 200. * (fy - fz),
+        }
+    }
+
+    /// Generate an opaque color from CIE L*a*b*. Out of gamut values are clamped.
+    pub fn from_lab(lab: Lab) -> Color {
+        let fy = (lab.l + 16.) / 116.;
+        let fx = fy + lab.a / 500.;
+        let fz = fy - lab.b / 200.;
+        let finv = |t: f64| {
+            if t.powi(3) > LAB_EPSILON {
+                t.powi(3)
+            } else {
+                (116. * t - 16.) / LAB_KAPPA
+            }
+        };
+        let x = WHITE_X * finv(fx);
+        let y = WHITE_Y * finv(fy);
+        let z = WHITE_Z * finv(fz);
+        Color::from_linear_rgb(LinearRgb::from_xyz(x, y, z))
+    }
+
+    /// Convert to polar CIE-LUV (hue, chroma, luminance) as used by `hcl()`.
+    pub fn to_hcl(&self) -> Hcl {
+        let (x, y, z) = self.to_linear_rgb().to_xyz();
+        let (un, vn) = white_uv();
+        let t = y / WHITE_Y;
+        let l = if t > LAB_EPSILON {
+            116. * t.cbrt() - 16.
+        } else {
+            LAB_KAPPA * t
+        };
+        let denom = x + 15. * y + 3. * z;
+        let (u, v) = if denom > 0. {
+            (
+                13. * l * (4. * x / denom - un),
+                13. * l * (9. * y / denom - vn),
+            )
+        } else {
+            (0., 0.)
+        };
+        let c = u.hypot(v);
+        let h = if c > 0. {
+            v.atan2(u).to_degrees().rem_euclid(360.)
+        } else {
+            0.
+        };
+        Hcl { h, c, l }
+    }
+
+    /// Generate an opaque color from polar CIE-LUV coordinates, as `hcl()` does.
+    /// Out of gamut values are clamped, like `hcl(fixup = TRUE)`.
+    pub fn from_hcl(hcl: Hcl) -> Color {
+        let Hcl { h, c, l } = hcl;
+        if l <= 0. {
+            return Color::rgb(0, 0, 0);
+        }
+        let (un, vn) = white_uv();
+        let u = c * h.to_radians().cos();
+        let v = c * h.to_radians().sin();
+        let y = WHITE_Y
+            * if l > LAB_KAPPA * LAB_EPSILON {
+                ((l + 16.) / 116.).powi(3)
+            } else {
+                l / LAB_KAPPA
+            };
+        let up = u / (13. * l) + un;
+        let vp = v / (13. * l) + vn;
+        let x = 9. * y * up / (4. * vp);
+        let z = -x / 3. - 5. * y + 3. * y / vp;
+        Color::from_linear_rgb(LinearRgb::from_xyz(x, y, z))
+    }
+
+    /// Composite this color over `background` (Porter-Duff "source over").
+    ///
+    /// This is what a device does when drawing a semi-transparent color.
+    pub fn over(&self, background: Color) -> Color {
+        let src_a = self.alpha() as f64 / 255.;
+        let dst_a = background.alpha() as f64 / 255.;
+        let out_a = src_a + dst_a * (1. - src_a);
+        if out_a <= 0. {
+            return Color::rgba(0, 0, 0, 0);
+        }
+        let channel = |src: u8, dst: u8| {
+            let value = (src as f64 * src_a + dst as f64 * dst_a * (1. - src_a)) / out_a / 255.;
+            scale_to_u8(value)
+        };
+        Color::rgba(
+            channel(self.red(), background.red()),
+            channel(self.green(), background.green()),
+            channel(self.blue(), background.blue()),
+            scale_to_u8(out_a),
+        )
+    }
+
+    /// Linearly interpolate between two colors in sRGB, including alpha.
+    /// `t = 0` is `self`, `t = 1` is `other`.
+    pub fn mix(&self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0., 1.);
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color::rgba(
+            lerp(self.red(), other.red()),
+            lerp(self.green(), other.green()),
+            lerp(self.blue(), other.blue()),
+            lerp(self.alpha(), other.alpha()),
+        )
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parse a color with the same rules as R's `col2rgb()`.
+    ///
+    /// Hex strings and `"transparent"` are handled in Rust. Color names,
+    /// such as `"steelblue"`, and palette indices, such as `"2"`, are looked up
+    /// by R and so require R to be running.
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(color) = Color::from_hex_str(s) {
+            return Ok(color);
+        }
+        if s.eq_ignore_ascii_case("transparent") || s == "NA" {
+            return Ok(Color::transparent());
+        }
+        let colors = col2rgb(&Robj::from(s))?;
+        colors
+            .first()
+            .copied()
+            .ok_or_else(|| Error::Other(format!("invalid color specification '{s}'")))
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex_string())
+    }
+}
+
+// A color becomes an element of a character vector, eg. `r!(Color::rgb(1, 2, 3))`.
+impl ToVectorValue for Color {
+    fn sexptype() -> SEXPTYPE {
+        SEXPTYPE::STRSXP
+    }
+
+    fn to_sexp(&self) -> SEXP {
+        str_to_character(&self.to_hex_string())
+    }
+}
+
+impl ToVectorValue for &Color {
+    fn sexptype() -> SEXPTYPE {
+        SEXPTYPE::STRSXP
+    }
+
+    fn to_sexp(&self) -> SEXP {
+        str_to_character(&self.to_hex_string())
+    }
+}
+
+impl TryFrom<&Robj> for Vec<Color> {
+    type Error = Error;
+
+    /// See [Color::from_robj].
+    fn try_from(robj: &Robj) -> Result<Self> {
+        Color::from_robj(robj)
+    }
+}
+
+impl TryFrom<Robj> for Vec<Color> {
+    type Error = Error;
+
+    /// See [Color::from_robj].
+    fn try_from(robj: Robj) -> Result<Self> {
+        Color::from_robj(&robj)
+    }
+}
+
+/// Hue, saturation and value, all in `[0, 1]` as in R's `hsv()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+/// Hue (degrees), chroma and luminance as in R's `hcl()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hcl {
+    pub h: f64,
+    pub c: f64,
+    pub l: f64,
+}
+
+/// CIE L*a*b* coordinates (D65 white point).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// Linear light sRGB components, nominally in `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearRgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl LinearRgb {
+    fn to_xyz(self) -> (f64, f64, f64) {
+        let LinearRgb { r, g, b } = self;
+        (
+            WHITE_Y * (0.412453 * r + 0.357580 * g + 0.180423 * b),
+            WHITE_Y * (0.212671 * r + 0.715160 * g + 0.072169 * b),
+            WHITE_Y * (0.019334 * r + 0.119193 * g + 0.950227 * b),
+        )
+    }
+
+    fn from_xyz(x: f64, y: f64, z: f64) -> LinearRgb {
+        LinearRgb {
+            r: (3.240479 * x - 1.537150 * y - 0.498535 * z) / WHITE_Y,
+            g: (-0.969256 * x + 1.875992 * y + 0.041556 * z) / WHITE_Y,
+            b: (0.055648 * x - 0.204043 * y + 1.057311 * z) / WHITE_Y,
+        }
+    }
+}
+
+// D65 white point and CIE constants, as used by R's hcl() (src/library/grDevices/src/colors.c).
+const WHITE_X: f64 = 95.047;
+const WHITE_Y: f64 = 100.000;
+const WHITE_Z: f64 = 108.883;
+const LAB_EPSILON: f64 = 216. / 24389.;
+const LAB_KAPPA: f64 = 24389. / 27.;
+
+fn white_uv() -> (f64, f64) {
+    let denom = WHITE_X + 15. * WHITE_Y + 3. * WHITE_Z;
+    (4. * WHITE_X / denom, 9. * WHITE_Y / denom)
+}
+
+fn srgb_to_linear(u: f64) -> f64 {
+    if u > 0.04045 {
+        ((u + 0.055) / 1.055).powf(2.4)
+    } else {
+        u / 12.92
+    }
+}
+
+fn linear_to_srgb(u: f64) -> f64 {
+    if u > 0.0031308 {
+        1.055 * u.powf(1. / 2.4) - 0.055
+    } else {
+        12.92 * u
+    }
+}
+
+fn scale_to_u8(value: f64) -> u8 {
+    (value.clamp(0., 1.) * 255.).round() as u8
+}
+
+// Resolve colors through R's `col2rgb(x, alpha = TRUE)`, a 4 x n integer matrix.
+fn col2rgb(x: &Robj) -> Result<Vec<Color>> {
+    let rgba = call!("grDevices::col2rgb", x, alpha = true)?;
+    let values = rgba
+        .as_integer_slice()
+        .ok_or_else(|| Error::ExpectedInteger(rgba.clone()))?;
+    Ok(values
+        .chunks_exact(4)
+        .map(|c| Color::rgba(c[0] as u8, c[1] as u8, c[2] as u8, c[3] as u8))
+        .collect())
+}
+
+/// Palettes of `n` colors, computed in Rust.
+pub mod palette {
+    use super::{Color, Hcl};
+
+    // Equally spaced values from `from` to `to`, like `seq(from, to, length.out = n)`.
+    fn seq(from: f64, to: f64, n: usize) -> impl Iterator<Item = f64> {
+        let step = if n > 1 {
+            (to - from) / (n - 1) as f64
+        } else {
+            0.
+        };
+        (0..n).map(move |i| from + step * i as f64)
+    }
+
+    /// Qualitative palette with hues equally spaced around the circle at fixed
+    /// chroma and luminance, as in `hcl(h = seq(h.start, h.start + 360, length = n + 1)[-(n + 1)], c, l)`.
+    /// `qualitative(n, 15., 100., 65.)` is the default ggplot2 hue palette.
+    pub fn qualitative(n: usize, h_start: f64, c: f64, l: f64) -> Vec<Color> {
+        seq(h_start, h_start + 360., n + 1)
+            .take(n)
+            .map(|h| Color::from_hcl(Hcl { h, c, l }))
+            .collect()
+    }
+
+    /// Sequential palette going from `(h.0, c.0, l.0)` to `(h.1, c.1, l.1)`, like
+    /// `hcl.colors()` with a sequential multi-hue palette.
+    ///
+    /// `power.0` controls the chroma and `power.1` the luminance trajectory.
+    pub fn sequential(
+        n: usize,
+        h: (f64, f64),
+        c: (f64, f64),
+        l: (f64, f64),
+        power: (f64, f64),
+    ) -> Vec<Color> {
+        seq(1., 0., n)
+            .map(|i| {
+                Color::from_hcl(Hcl {
+                    h: h.1 - (h.1 - h.0) * i,
+                    c: c.1 - (c.1 - c.0) * i.powf(power.0),
+                    l: l.1 - (l.1 - l.0) * i.powf(power.1),
+                })
+            })
+            .collect()
+    }
+
+    /// HCL approximation of the viridis palette, as in `hcl.colors(n, "viridis")`.
+    pub fn viridis(n: usize) -> Vec<Color> {
+        sequential(n, (300., 75.), (40., 95.), (15., 90.), (1., 1.1))
+    }
+
+    /// Gradient between two colors, interpolated in sRGB like `colorRampPalette(c(from, to))(n)`.
+    pub fn gradient(from: Color, to: Color, n: usize) -> Vec<Color> {
+        seq(0., 1., n).map(|t| from.mix(to, t)).collect()
+    }
 }
 
 pub mod predefined {
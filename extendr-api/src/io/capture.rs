@@ -0,0 +1,149 @@
+//! Capture R console output from Rust.
+
+use crate as extendr_api;
+use crate::*;
+
+/// Text written to the R console while running [with_captured_output].
+///
+/// Every line is terminated by `\n`, as with R's `capture.output()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Captured {
+    /// Standard output, e.g. `print()` and `cat()`.
+    pub output: String,
+    /// The message stream, e.g. `message()` and immediate warnings.
+    pub messages: String,
+}
+
+/// Run `f` and capture everything R writes to the console meanwhile.
+///
+/// This diverts R's output and message streams with `sink()`, so it works
+/// both inside an R package and in an embedded R session. Deferred warnings
+/// are printed by R after the top level call returns and are not captured.
+/// Output written directly by Rust, e.g. with `println!`, is not captured,
+/// but [`rprintln!`] is.
+///
+/// The sinks are removed even if `f` panics.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let (value, captured) = with_captured_output(|| {
+///         R!(r#"print(1:3); message("careful"); 42L"#)
+///     })?;
+///     assert_eq!(value?, r!(42));
+///     assert_eq!(captured.output, "[1] 1 2 3\n");
+///     assert_eq!(captured.messages, "careful\n");
+/// }
+/// ```
+pub fn with_captured_output<T>(f: impl FnOnce() -> T) -> Result<(T, Captured)> {
+    let mut capture = Capture::start()?;
+    let value = f();
+    let captured = capture.finish()?;
+    Ok((value, captured))
+}
+
+// Diverts output and messages into text connections, writing to
+// variables `output` and `messages` in a private environment.
+struct Capture {
+    env: Environment,
+    output: Robj,
+    messages: Robj,
+    previous_message_sink: i32,
+    active: bool,
+}
+
+impl Capture {
+    fn start() -> Result<Self> {
+        let env = Environment::new_with_parent(Environment::base());
+        let output = text_connection(&env, "output")?;
+        let messages = text_connection(&env, "messages")?;
+        let previous_message_sink = sink_number_messages()?;
+
+        call!("sink", &output)?;
+        if let Err(e) = sink_messages(Some(&messages)) {
+            let _ = call!("sink");
+            return Err(e);
+        }
+
+        Ok(Self {
+            env,
+            output,
+            messages,
+            previous_message_sink,
+            active: true,
+        })
+    }
+
+    fn restore(&mut self) -> Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+
+        // Connection 2 is stderr, i.e. no message sink.
+        let message_result = if self.previous_message_sink == 2 {
+            sink_messages(None)
+        } else {
+            call!("getConnection", self.previous_message_sink)
+                .and_then(|con| sink_messages(Some(&con)))
+        };
+        let output_result = call!("sink");
+        let _ = call!("close", &self.output);
+        let _ = call!("close", &self.messages);
+        message_result?;
+        output_result?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Captured> {
+        self.restore()?;
+        Ok(Captured {
+            output: self.lines("output")?,
+            messages: self.lines("messages")?,
+        })
+    }
+
+    fn lines(&self, name: &str) -> Result<String> {
+        let lines = self.env.local(Symbol::from_string(name))?;
+        let lines = lines
+            .as_str_iter()
+            .ok_or_else(|| Error::ExpectedString(lines.clone()))?;
+        Ok(lines.map(|line| format!("{line}\n")).collect())
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+// A text connection writing to the variable `name` in `env`.
+fn text_connection(env: &Environment, name: &str) -> Result<Robj> {
+    let code = format!(r#"textConnection("{name}", open = "w", local = TRUE)"#);
+    let expr = parse(&code)?;
+    let mut res = Robj::from(());
+    for lang in expr.values() {
+        res = lang.eval_with_env(env)?;
+    }
+    Ok(res)
+}
+
+// `sink(con, type = "message")`, or `sink(type = "message")` to reset to stderr.
+fn sink_messages(con: Option<&Robj>) -> Result<Robj> {
+    let sink = eval_string("sink")?;
+    match con {
+        Some(con) => sink.call(Pairlist::from_pairs([
+            ("", con.clone()),
+            ("type", r!("message")),
+        ])),
+        None => sink.call(Pairlist::from_pairs([("type", r!("message"))])),
+    }
+}
+
+fn sink_number_messages() -> Result<i32> {
+    let number = eval_string(r#"sink.number(type = "message")"#)?;
+    number
+        .as_integer()
+        .ok_or_else(|| Error::ExpectedInteger(number.clone()))
+}
@@ -1,16 +1,37 @@
-use super::PstreamFormat;
+use super::{raise_stream_error, PstreamFormat};
 use crate::{catch_r_error, error::Error, error::Result, robj::Robj};
 use extendr_ffi::{R_NilValue, R_Unserialize, R_inpstream_st, R_inpstream_t, SEXP};
 use std::io::Read;
 
+/// The hook called for references (e.g. environments) when reading R data.
 pub struct ReadHook {
-    func: Option<unsafe extern "C" fn(arg1: SEXP, arg2: SEXP) -> SEXP>,
-    data: SEXP,
+    pub func: Option<unsafe extern "C" fn(arg1: SEXP, arg2: SEXP) -> SEXP>,
+    pub data: SEXP,
+}
+
+// The `data` of the input stream: the reader and the first I/O error it returned.
+struct InStream<'a, R: Read> {
+    reader: &'a mut R,
+    error: Option<std::io::Error>,
+}
+
+impl<R: Read> InStream<'_, R> {
+    // Fill `buf`, or record the error and return `false`.
+    fn read_exact(&mut self, buf: &mut [u8]) -> bool {
+        let result =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.reader.read_exact(buf)));
+        let error = match result {
+            Ok(Ok(())) => return true,
+            Ok(Err(e)) => e,
+            Err(_) => std::io::Error::other("the reader panicked"),
+        };
+        self.error = Some(error);
+        false
+    }
 }
 
 pub trait Load {
-    /// Save an object in the R data format.
-    /// `version` should probably be 3.
+    /// Load an object in the R data format.
     fn load<P: AsRef<std::path::Path>>(
         path: &P,
         format: PstreamFormat,
@@ -21,17 +42,22 @@ pub trait Load {
         Self::from_reader(&mut reader, format, hook)
     }
 
-    /// Save an object in the R data format to a `Write` trait.
-    /// `version` should probably be 3.
+    /// Load an object in the R data format from a `Read` trait.
+    ///
+    /// Errors returned by `reader`, including an unexpected end of the input,
+    /// are returned as an [`Error`].
     fn from_reader<R: Read>(
         reader: &mut R,
         format: PstreamFormat,
         hook: Option<ReadHook>,
     ) -> Result<Robj> {
         unsafe extern "C" fn inchar<R: Read>(arg1: R_inpstream_t) -> ::std::os::raw::c_int {
-            let reader = &mut *((*arg1).data as *mut R);
-            let buf: &mut [u8] = &mut [0_u8];
-            reader.read_exact(buf).map(|_| buf[0].into()).unwrap_or(-1)
+            let stream = &mut *((*arg1).data as *mut InStream<R>);
+            let mut buf = [0_u8];
+            if !stream.read_exact(&mut buf) {
+                raise_stream_error();
+            }
+            buf[0].into()
         }
 
         unsafe extern "C" fn inbytes<R: Read>(
@@ -39,13 +65,18 @@ pub trait Load {
             arg2: *mut ::std::os::raw::c_void,
             arg3: ::std::os::raw::c_int,
         ) {
-            let reader = &mut *((*arg1).data as *mut R);
+            let stream = &mut *((*arg1).data as *mut InStream<R>);
             let buf = std::slice::from_raw_parts_mut(arg2 as *mut u8, arg3 as usize);
-            reader.read_exact(buf).unwrap();
+            if !stream.read_exact(buf) {
+                raise_stream_error();
+            }
         }
 
-        let read_ptr: *mut R = reader as &mut R;
-        let data = read_ptr.cast::<std::ffi::c_void>();
+        let mut stream = InStream {
+            reader,
+            error: None,
+        };
+        let data = (&mut stream as *mut InStream<R>).cast::<std::ffi::c_void>();
 
         let (hook_func, hook_data) = if let Some(hook) = hook {
             (hook.func, hook.data)
@@ -53,8 +84,6 @@ pub trait Load {
             (None, unsafe { R_NilValue })
         };
 
-        // let sexp = self.get();
-        // pub type R_inpstream_t = *mut R_inpstream_st;
         let mut state = R_inpstream_st {
             data,
             type_: format,
@@ -67,11 +96,11 @@ pub trait Load {
             nat2utf8_obj: std::ptr::null_mut(),
         };
 
-        Ok(unsafe {
-            Robj::from_sexp(catch_r_error(move || {
-                R_Unserialize(&mut state as R_inpstream_t)
-            })?)
-        })
+        let res = catch_r_error(move || unsafe { R_Unserialize(&mut state as R_inpstream_t) });
+        if let Some(e) = stream.error.take() {
+            return Err(Error::Other(format!("could not read R data: {e}")));
+        }
+        Ok(unsafe { Robj::from_sexp(res?) })
     }
 }
 
@@ -1,7 +1,22 @@
 pub type PstreamFormat = extendr_ffi::R_pstream_format_t;
 
+mod capture;
 mod load;
+mod rds;
 mod save;
 
-pub use load::Load;
-pub use save::Save;
+pub use capture::{with_captured_output, Captured};
+pub use load::{Load, ReadHook};
+pub use rds::{
+    load_rdata, read_rds, read_rds_with, write_rds, write_rds_with, Compression, RdsOptions,
+};
+pub use save::{OutStream, Save, WriteHook};
+
+// Abort the serialization in progress after a stream callback failed.
+// The callback stores the actual error for the caller of `catch_r_error`.
+//
+// Safety: must be called from a stream callback, with no values that need
+// dropping still alive in that callback.
+unsafe fn raise_stream_error() -> ! {
+    extendr_ffi::Rf_error(c"%s".as_ptr(), c"I/O error in R data stream".as_ptr());
+}
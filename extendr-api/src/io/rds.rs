@@ -0,0 +1,319 @@
+//! Read and write `.rds` and `.RData` files.
+//!
+//! Objects are (un)serialized in Rust and streamed to or from the file.
+//! Compressed files are read and written through R's `gzfile()`, `bzfile()`
+//! and `xzfile()` connections, so they are compatible with `readRDS()` and
+//! `saveRDS()`.
+
+use super::{Load, PstreamFormat, ReadHook, Save, WriteHook};
+use crate as extendr_api;
+use crate::*;
+use extendr_ffi::{
+    R_GlobalEnv, R_NilValue, R_tryEval, Rf_cons, Rf_lcons, Rf_protect, Rf_unprotect,
+};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The compression of an `.rds` or `.RData` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Compression {
+    None,
+    /// The default of `saveRDS()`.
+    #[default]
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Detect the compression from the first bytes of a file.
+    ///
+    /// ```
+    /// use extendr_api::io::Compression;
+    /// assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+    /// assert_eq!(Compression::detect(b"BZh9"), Compression::Bzip2);
+    /// assert_eq!(Compression::detect(b"\xfd7zXZ\x00"), Compression::Xz);
+    /// assert_eq!(Compression::detect(b"X\n"), Compression::None);
+    /// ```
+    pub fn detect(header: &[u8]) -> Compression {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if header.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if header.starts_with(b"\xfd7zXZ\x00") {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    // The R function opening a connection with this compression.
+    fn connection(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gzfile"),
+            Compression::Bzip2 => Some("bzfile"),
+            Compression::Xz => Some("xzfile"),
+        }
+    }
+}
+
+/// Options for [read_rds_with] and [write_rds_with].
+///
+/// The defaults match those of `saveRDS()`.
+#[derive(Debug, Clone)]
+pub struct RdsOptions {
+    /// The format used for writing. Reading detects the format.
+    pub format: PstreamFormat,
+    /// The serialization version used for writing, 2 or 3.
+    pub version: i32,
+    /// The compression used for writing. Reading detects the compression.
+    pub compression: Compression,
+    /// An R function called for references, such as environments,
+    /// as with the `refhook` argument of `saveRDS()` and `readRDS()`.
+    pub refhook: Option<Function>,
+}
+
+impl RdsOptions {
+    // Check the options for writing before any file is touched.
+    fn validate(&self) -> Result<()> {
+        if !(2..=3).contains(&self.version) {
+            return Err(Error::Other(format!(
+                "version must be 2 or 3, got {:?}",
+                self.version
+            )));
+        }
+        if self.format == PstreamFormat::R_pstream_any_format {
+            return Err(Error::Other(
+                "format must be a concrete format for writing".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for RdsOptions {
+    fn default() -> Self {
+        Self {
+            format: PstreamFormat::R_pstream_xdr_format,
+            version: 3,
+            compression: Compression::Gzip,
+            refhook: None,
+        }
+    }
+}
+
+/// Read a single R object from an `.rds` file, like `readRDS()`.
+///
+/// The compression is detected from the file contents.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::io::{read_rds, write_rds};
+/// test! {
+///     let path = std::env::temp_dir().join("extendr_read_rds_doc.rds");
+///     write_rds(&r!([1, 2, 3]), &path)?;
+///     assert_eq!(read_rds(&path)?, r!([1, 2, 3]));
+///     # std::fs::remove_file(&path).unwrap();
+/// }
+/// ```
+pub fn read_rds<P: AsRef<Path>>(path: P) -> Result<Robj> {
+    read_rds_with(path, &RdsOptions::default())
+}
+
+/// Read a single R object from an `.rds` file, using `options.refhook`.
+pub fn read_rds_with<P: AsRef<Path>>(path: P, options: &RdsOptions) -> Result<Robj> {
+    let path = path.as_ref();
+    let hook = options.refhook.as_ref().map(|refhook| ReadHook {
+        func: Some(call_refhook),
+        data: unsafe { refhook.get() },
+    });
+
+    let compression = detect_file(path)?;
+    let res = match compression.connection() {
+        None => {
+            let file = std::fs::File::open(path).map_err(|e| io_error(path, e))?;
+            Robj::from_reader(&mut BufReader::new(file), any_format(), hook)
+        }
+        Some(connection) => {
+            let con = Connection::open(connection, path, "rb")?;
+            Robj::from_reader(&mut BufReader::new(con), any_format(), hook)
+        }
+    };
+    res.map_err(|e| Error::Other(format!("could not read {path:?}: {e}")))
+}
+
+/// Write a single R object to an `.rds` file, like `saveRDS()`.
+pub fn write_rds<P: AsRef<Path>>(robj: &Robj, path: P) -> Result<()> {
+    write_rds_with(robj, path, &RdsOptions::default())
+}
+
+/// Write a single R object to an `.rds` file with the given options.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::io::{read_rds, write_rds_with, Compression, RdsOptions};
+/// test! {
+///     let path = std::env::temp_dir().join("extendr_write_rds_doc.rds");
+///     let options = RdsOptions {
+///         compression: Compression::Xz,
+///         version: 2,
+///         ..Default::default()
+///     };
+///     write_rds_with(&r!("hello"), &path, &options)?;
+///     assert_eq!(read_rds(&path)?, r!("hello"));
+///     # std::fs::remove_file(&path).unwrap();
+/// }
+/// ```
+pub fn write_rds_with<P: AsRef<Path>>(robj: &Robj, path: P, options: &RdsOptions) -> Result<()> {
+    let path = path.as_ref();
+    options.validate()?;
+
+    // Write next to the destination and rename, so that a failed write leaves
+    // an existing file untouched.
+    let tmp = temp_path(path);
+    let res = write_file(robj, &tmp, options)
+        .and_then(|_| std::fs::rename(&tmp, path).map_err(Error::from));
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    res.map_err(|e| Error::Other(format!("could not write {path:?}: {e}")))
+}
+
+fn write_file(robj: &Robj, path: &Path, options: &RdsOptions) -> Result<()> {
+    let hook = options.refhook.as_ref().map(|refhook| WriteHook {
+        func: call_refhook,
+        data: unsafe { refhook.get() },
+    });
+
+    match options.compression.connection() {
+        None => {
+            let file = std::fs::File::create(path).map_err(|e| io_error(path, e))?;
+            let mut writer = BufWriter::new(file);
+            robj.to_writer(&mut writer, options.format, options.version, hook)?;
+            writer.flush().map_err(Error::from)
+        }
+        Some(connection) => {
+            let con = Connection::open(connection, path, "wb")?;
+            let mut writer = BufWriter::new(con);
+            robj.to_writer(&mut writer, options.format, options.version, hook)?;
+            writer.flush().map_err(Error::from)
+        }
+    }
+}
+
+// A hidden file in the directory of `path`, so that renaming it is atomic.
+fn temp_path(path: &Path) -> std::path::PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
+
+/// Read the objects in an `.RData` file into a new environment, like `load()`.
+///
+/// The environment's parent is the global environment.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::io::load_rdata;
+/// test! {
+///     let path = std::env::temp_dir().join("extendr_load_rdata_doc.RData");
+///     let path_str = path.to_str().unwrap();
+///     R!("local({ a <- 1L; b <- 'two'; save(a, b, file = {{path_str}}) })")?;
+///
+///     let env = load_rdata(&path)?;
+///     assert_eq!(env.local(sym!(a))?, r!(1));
+///     assert_eq!(env.local(sym!(b))?, r!("two"));
+///     # std::fs::remove_file(&path).unwrap();
+/// }
+/// ```
+pub fn load_rdata<P: AsRef<Path>>(path: P) -> Result<Environment> {
+    let path = path.as_ref();
+    // R's `load()` gives a confusing error for missing files.
+    detect_file(path)?;
+    let env = Environment::new_with_parent(Environment::global());
+    call!("load", file = path_str(path)?, envir = &env)
+        .map_err(|e| Error::Other(format!("could not load {path:?}: {e}")))?;
+    Ok(env)
+}
+
+// An R connection read and written with `readBin()` and `writeBin()`.
+struct Connection {
+    con: Robj,
+}
+
+impl Connection {
+    fn open(function: &str, path: &Path, mode: &str) -> Result<Self> {
+        let con = eval_string(function)?
+            .call(pairlist!(path_str(path)?, mode))
+            .map_err(|e| Error::Other(format!("could not open {path:?}: {e}")))?;
+        Ok(Self { con })
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes = call!("readBin", &self.con, "raw", n = buf.len() as f64)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let bytes: &[u8] = bytes
+            .as_typed_slice()
+            .ok_or_else(|| std::io::Error::other("readBin() did not return a raw vector"))?;
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        call!("writeBin", Raw::from_bytes(buf), &self.con)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = call!("close", &self.con);
+    }
+}
+
+// `R_Unserialize` reads the format from the stream.
+fn any_format() -> PstreamFormat {
+    PstreamFormat::R_pstream_any_format
+}
+
+// Calls the R function `fun` on `x`, for `refhook`s.
+unsafe extern "C" fn call_refhook(x: SEXP, fun: SEXP) -> SEXP {
+    let args = Rf_protect(Rf_cons(x, R_NilValue));
+    let call = Rf_protect(Rf_lcons(fun, args));
+    let mut error = 0;
+    let res = R_tryEval(call, R_GlobalEnv, &mut error);
+    Rf_unprotect(2);
+    if error != 0 {
+        extendr_ffi::Rf_error(c"%s".as_ptr(), c"error in refhook".as_ptr());
+    }
+    res
+}
+
+// The compression of the file at `path`.
+fn detect_file(path: &Path) -> Result<Compression> {
+    let mut file = std::fs::File::open(path).map_err(|e| io_error(path, e))?;
+    let mut header = Vec::with_capacity(6);
+    (&mut file)
+        .take(6)
+        .read_to_end(&mut header)
+        .map_err(|e| io_error(path, e))?;
+    Ok(Compression::detect(&header))
+}
+
+fn path_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| Error::Other(format!("path {path:?} is not valid UTF-8")))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Other(format!("could not open {path:?}: {e}"))
+}
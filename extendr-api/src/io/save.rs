@@ -1,6 +1,6 @@
 //! Wrapper for R output streams.
 
-use super::PstreamFormat;
+use super::{raise_stream_error, PstreamFormat};
 use crate::{catch_r_error, error::Error, error::Result, robj::GetSexp};
 use extendr_ffi::{
     R_NilValue, R_Serialize, R_outpstream_st, R_outpstream_t, R_pstream_data_t, R_pstream_format_t,
@@ -17,6 +17,7 @@ pub struct WriteHook {
 pub struct OutStream<W: Write> {
     r_state: R_outpstream_st,
     writer: W,
+    error: Option<std::io::Error>,
 }
 
 impl<W: Write> OutStream<W> {
@@ -27,9 +28,10 @@ impl<W: Write> OutStream<W> {
         hook: Option<WriteHook>,
     ) -> Box<OutStream<W>> {
         unsafe extern "C" fn outchar<W: Write>(arg1: R_outpstream_t, arg2: ::std::os::raw::c_int) {
-            let writer = &mut *((*arg1).data as *mut W);
-            let b = [arg2 as u8];
-            writer.write_all(&b).unwrap();
+            let stream = &mut *((*arg1).data as *mut OutStream<W>);
+            if !stream.write_all(&[arg2 as u8]) {
+                raise_stream_error();
+            }
         }
 
         unsafe extern "C" fn outbytes<W: Write>(
@@ -37,9 +39,11 @@ impl<W: Write> OutStream<W> {
             arg2: *mut ::std::os::raw::c_void,
             arg3: ::std::os::raw::c_int,
         ) {
-            let writer = &mut *((*arg1).data as *mut W);
+            let stream = &mut *((*arg1).data as *mut OutStream<W>);
             let b = std::slice::from_raw_parts(arg2 as *mut u8, arg3 as usize);
-            writer.write_all(b).unwrap();
+            if !stream.write_all(b) {
+                raise_stream_error();
+            }
         }
 
         {
@@ -58,11 +62,34 @@ impl<W: Write> OutStream<W> {
                 OutPersistHookFunc: hook_fn,
                 OutPersistHookData: hook_data,
             };
-            let mut os = Box::new(OutStream { r_state, writer });
-            os.r_state.data = &mut os.writer as *mut W as R_pstream_data_t;
+            let mut os = Box::new(OutStream {
+                r_state,
+                writer,
+                error: None,
+            });
+            os.r_state.data = &mut *os as *mut OutStream<W> as R_pstream_data_t;
             os
         }
     }
+
+    /// The first error returned by the writer, if any.
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    // Write all of `buf`, or record the error and return `false`.
+    fn write_all(&mut self, buf: &[u8]) -> bool {
+        let writer = &mut self.writer;
+        let result =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| writer.write_all(buf)));
+        let error = match result {
+            Ok(Ok(())) => return true,
+            Ok(Err(e)) => e,
+            Err(_) => std::io::Error::other("the writer panicked"),
+        };
+        self.error = Some(error);
+        false
+    }
 }
 
 pub trait Save: GetSexp {
@@ -82,6 +109,8 @@ pub trait Save: GetSexp {
 
     /// Save an object in the R data format to a `Write` trait.
     /// `version` should probably be 3.
+    ///
+    /// Errors returned by `writer` are returned as an [`Error`].
     fn to_writer<W: Write>(
         &self,
         writer: &mut W,
@@ -89,10 +118,6 @@ pub trait Save: GetSexp {
         version: i32,
         hook: Option<WriteHook>,
     ) -> Result<()> {
-        let mut os = OutStream::from_writer(writer, format, version, hook);
-
-        let stream = &mut os.r_state as R_outpstream_t;
-        let sexp = unsafe { self.get() };
         if !(2..=3).contains(&version) {
             return Err(Error::Other(format!(
                 "version must be 2 or 3, got {:?}",
@@ -100,10 +125,18 @@ pub trait Save: GetSexp {
             )));
         }
 
-        catch_r_error(move || unsafe {
+        let mut os = OutStream::from_writer(writer, format, version, hook);
+        let stream = &mut os.r_state as R_outpstream_t;
+        let sexp = unsafe { self.get() };
+
+        let res = catch_r_error(move || unsafe {
             R_Serialize(sexp, stream);
             R_NilValue
-        })?;
+        });
+        if let Some(e) = os.take_error() {
+            return Err(Error::Other(format!("could not write R data: {e}")));
+        }
+        res?;
         Ok(())
     }
 }
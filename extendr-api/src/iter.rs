@@ -1,6 +1,6 @@
 use crate::*;
 
-use extendr_ffi::{R_NaString, R_NilValue, Rf_isFactor, INTEGER, STRING_ELT, TYPEOF};
+use extendr_ffi::{R_NaString, R_NilValue, Rf_isFactor, Rf_xlength, INTEGER, STRING_ELT, TYPEOF};
 use wrapper::symbol::levels_symbol;
 /// Iterator over name-value pairs in lists.
 pub type NamedListIter = std::iter::Zip<StrIter, ListIter>;
@@ -64,6 +64,11 @@ pub(crate) fn str_from_strsxp<'a>(sexp: SEXP, index: usize) -> Option<&'a str> {
     })
 }
 
+// Whether every string of a `STRSXP` can be borrowed as a `&str`.
+unsafe fn strsxp_is_utf8(sexp: SEXP) -> bool {
+    (0..Rf_xlength(sexp)).all(|i| rstr::try_charsxp_to_str(STRING_ELT(sexp, i)).is_ok())
+}
+
 impl Iterator for StrIter {
     type Item = &'static str;
 
@@ -155,6 +160,8 @@ pub trait AsStrIter: GetSexp + Types + Length + Attributes + Rinternals {
     /// Get an iterator over a string vector.
     /// Returns None if the object is not a string vector
     /// but works for factors.
+    /// Also returns None if a string is not valid UTF-8; use
+    /// [`Strings`] and [`Rstr::to_utf8`] to translate such strings.
     ///
     /// ```
     /// use extendr_api::prelude::*;
@@ -184,6 +191,9 @@ pub trait AsStrIter: GetSexp + Types + Length + Attributes + Rinternals {
         let len = self.len();
         if self.sexptype() == SEXPTYPE::STRSXP {
             unsafe {
+                if !strsxp_is_utf8(self.get()) {
+                    return None;
+                }
                 Some(StrIter {
                     vector: self.as_robj().clone(),
                     i,
@@ -194,6 +204,7 @@ pub trait AsStrIter: GetSexp + Types + Length + Attributes + Rinternals {
         } else if self.sexptype() == SEXPTYPE::CHARSXP {
             let len = 1;
             unsafe {
+                rstr::try_charsxp_to_str(self.get()).ok()?;
                 Some(StrIter {
                     vector: self.as_robj().clone(),
                     i,
@@ -204,6 +215,9 @@ pub trait AsStrIter: GetSexp + Types + Length + Attributes + Rinternals {
         } else if self.is_factor() {
             let levels = self.get_attrib(levels_symbol()).unwrap();
             unsafe {
+                if levels.sexptype() != SEXPTYPE::STRSXP || !strsxp_is_utf8(levels.get()) {
+                    return None;
+                }
                 Some(StrIter {
                     vector: self.as_robj().clone(),
                     i,
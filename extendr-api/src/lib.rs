@@ -294,6 +294,9 @@
 //! - `graphics`: provides the functionality to control or implement graphics devices.
 //! - `either`: provides implementation of type conversion traits for `Either<L, R>` from [`either`](https://docs.rs/either/latest/either/) if `L` and `R` both implement those traits.
 //! - `faer`: provides conversion between R's matrices and [`faer`](https://docs.rs/faer/latest/faer/).
+//! - `nalgebra`: provides conversion between R's vectors and matrices and [`nalgebra`](https://docs.rs/nalgebra/latest/nalgebra/).
+//! - `indexmap`: provides conversion between named vectors and lists and the `IndexMap` of [`indexmap`](https://docs.rs/indexmap/latest/indexmap/), which keeps the order of the names.
+//! - `chrono`, `time` and `jiff`: provide conversions between R's `Date`, `POSIXct` and `difftime` classes and the date and time types of [`chrono`](https://docs.rs/chrono/latest/chrono/), [`time`](https://docs.rs/time/latest/time/) and [`jiff`](https://docs.rs/jiff/latest/jiff/).
 //!
 //! extendr-api supports three ways of returning a Result<T,E> to R.
 //! Only one behavior feature can be enabled at a time.
@@ -303,6 +306,12 @@
 //! These features are experimental and are subject to change.
 //! - `result_list`: return `Ok` as `list(ok=?, err=NULL)` or `Err` `list(ok=NULL, err=?)`
 //! - `result_condition`: return `Ok` as is or `Err` as $value in an R error condition.
+//!
+//! By default `i64` values are returned to R as doubles, which loses precision above 2^53.
+//! - `integer64`: return `i64` as `bit64::integer64` vectors instead. See also [`Integers64`](wrapper::Integers64).
+//!
+//! To find leaked R objects, see the diagnostics in [`ownership`].
+//! - `debug-ownership`: record a backtrace for every protected object. See [`ownership::ProtectedObject`].
 #![doc(
     html_logo_url = "https://raw.githubusercontent.com/extendr/extendr/main/extendr-logo-256.png"
 )]
@@ -335,6 +344,9 @@ pub mod serializer;
 #[cfg(feature = "serde")]
 pub mod deserializer;
 
+#[cfg(feature = "serde")]
+pub use serializer::Serde;
+
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
@@ -351,6 +363,7 @@ pub use error::*;
 pub use functions::*;
 pub use lang_macros::*;
 pub use na::*;
+pub use ownership::{protect_scope, Local, ProtectScope};
 pub use robj::*;
 pub use thread_safety::{catch_r_error, single_threaded, throw_r_error};
 pub use wrapper::*;
@@ -467,7 +480,7 @@ pub unsafe fn register_call_methods(info: *mut extendr_ffi::DllInfo, metadata: M
 }
 
 /// Type of R objects used by [Robj::rtype].
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Rtype {
     Null,        // NILSXP
     Symbol,      // SYMSXP
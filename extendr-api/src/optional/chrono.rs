@@ -0,0 +1,132 @@
+/*!
+Conversions between R's date and time classes and [`chrono`](https://docs.rs/chrono/latest/chrono/).
+
+| R          | chrono                                     |
+|------------|--------------------------------------------|
+| `Date`     | [`NaiveDate`]                              |
+| `POSIXct`  | [`DateTime<Utc>`], [`DateTime<FixedOffset>`] |
+| `difftime` | [`TimeDelta`] (also known as `Duration`)   |
+
+`Option<T>`, `Vec<T>` and `Vec<Option<T>>` are supported too, with `None` for `NA`.
+
+Any `DateTime<Tz>` can be returned to R. As `chrono` has no time zone names,
+the result has time zone `"UTC"`; the instant in time is unchanged.
+Date-times from R are read in UTC, whatever their `tzone` attribute.
+
+```rust
+use extendr_api::prelude::*;
+use chrono::{NaiveDate, TimeDelta};
+
+#[extendr]
+fn next_week(date: NaiveDate) -> NaiveDate {
+    date + TimeDelta::try_weeks(1).unwrap()
+}
+```
+*/
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeDelta, TimeZone, Utc};
+
+use crate::wrapper::datetime::{
+    impl_time_conversions, time_values_into_robj, TimeClass, TimeValue,
+};
+use crate::*;
+
+// Days from 0001-01-01 to 1970-01-01.
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+// Split seconds into whole seconds and nanoseconds.
+fn split_seconds(value: f64) -> (i64, u32) {
+    let seconds = value.floor();
+    let nanos = ((value - seconds) * 1e9).round().min(999_999_999.0);
+    (seconds as i64, nanos as u32)
+}
+
+fn out_of_range(value: f64) -> Error {
+    Error::OutOfRange(value.into())
+}
+
+impl TimeValue for NaiveDate {
+    const CLASS: TimeClass = TimeClass::Date;
+
+    fn to_r(&self) -> f64 {
+        (self.num_days_from_ce() as i64 - UNIX_EPOCH_DAYS_FROM_CE) as f64
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        i32::try_from(value.floor() as i64 + UNIX_EPOCH_DAYS_FROM_CE)
+            .ok()
+            .and_then(NaiveDate::from_num_days_from_ce_opt)
+            .ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for DateTime<Utc> {
+    const CLASS: TimeClass = TimeClass::DateTime;
+
+    fn to_r(&self) -> f64 {
+        self.timestamp() as f64 + self.timestamp_subsec_nanos() as f64 * 1e-9
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        let (seconds, nanos) = split_seconds(value);
+        DateTime::from_timestamp(seconds, nanos).ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for DateTime<FixedOffset> {
+    const CLASS: TimeClass = TimeClass::DateTime;
+
+    fn to_r(&self) -> f64 {
+        self.to_utc().to_r()
+    }
+
+    fn from_r(value: f64, tzone: &str) -> Result<Self> {
+        DateTime::<Utc>::from_r(value, tzone).map(|time| time.fixed_offset())
+    }
+}
+
+impl TimeValue for TimeDelta {
+    const CLASS: TimeClass = TimeClass::Difftime;
+
+    fn to_r(&self) -> f64 {
+        self.num_seconds() as f64 + self.subsec_nanos() as f64 * 1e-9
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        let (seconds, nanos) = split_seconds(value);
+        TimeDelta::new(seconds, nanos).ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl_time_conversions!(NaiveDate);
+impl_time_conversions!(TimeDelta);
+impl_time_conversions!(@try_from DateTime<Utc>);
+impl_time_conversions!(@try_from DateTime<FixedOffset>);
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for Robj {
+    fn from(value: DateTime<Tz>) -> Self {
+        time_values_into_robj([Some(&value.to_utc())])
+    }
+}
+
+impl<Tz: TimeZone> From<Option<DateTime<Tz>>> for Robj {
+    fn from(value: Option<DateTime<Tz>>) -> Self {
+        time_values_into_robj([value.map(|value| value.to_utc()).as_ref()])
+    }
+}
+
+impl<Tz: TimeZone> From<Vec<DateTime<Tz>>> for Robj {
+    fn from(values: Vec<DateTime<Tz>>) -> Self {
+        let values: Vec<_> = values.iter().map(DateTime::to_utc).collect();
+        time_values_into_robj(values.iter().map(Some))
+    }
+}
+
+impl<Tz: TimeZone> From<Vec<Option<DateTime<Tz>>>> for Robj {
+    fn from(values: Vec<Option<DateTime<Tz>>>) -> Self {
+        let values: Vec<_> = values
+            .iter()
+            .map(|value| value.as_ref().map(DateTime::to_utc))
+            .collect();
+        time_values_into_robj(values.iter().map(Option::as_ref))
+    }
+}
@@ -1,6 +1,8 @@
-use faer::{mat, Mat, MatRef};
+use faer::complex_native::c64;
+use faer::{col, mat, row, Col, ColMut, ColRef, Mat, MatMut, MatRef, Row, RowRef};
 
-use crate::scalar::Rfloat;
+use super::real_slice;
+use crate::scalar::{Rcplx, Rfloat};
 use crate::*;
 
 /// Convert a `faer::Mat<f64>` into an `RMatrix<f64>` which is not `NA` aware.
@@ -72,7 +74,7 @@ impl TryFrom<&Robj> for Mat<f64> {
         let nrow = rmat.nrows();
         let ncol = rmat.ncols();
 
-        if let Some(slice) = robj.as_real_slice() {
+        if let Some(slice) = real_slice(robj) {
             let fmat = Mat::from_fn(nrow, ncol, |i, j| slice[i + j * nrow]);
             Ok(fmat)
         } else {
@@ -89,7 +91,8 @@ impl TryFrom<&'_ Robj> for MatRef<'_, f64> {
         let nrows = rmat.nrows();
         let ncols = rmat.ncols();
 
-        if let Some(slice) = robj.as_typed_slice() {
+        if real_slice(robj).is_some() {
+            let slice = robj.as_typed_slice().unwrap();
             let fmat = mat::from_column_major_slice(slice, nrows, ncols);
             Ok(fmat)
         } else {
@@ -125,11 +128,242 @@ impl From<RMatrix<i32>> for Mat<f64> {
     }
 }
 
+/// Mutably borrow the data of an `RMatrix<f64>`, to modify it in place.
+impl<'a> TryFrom<&'a mut RMatrix<f64>> for MatMut<'a, f64> {
+    type Error = Error;
+
+    fn try_from(value: &'a mut RMatrix<f64>) -> Result<Self> {
+        let nrow = value.nrows();
+        let ncol = value.ncols();
+        if real_slice(value).is_none() {
+            return Err(Error::ExpectedReal(value.as_robj().clone()));
+        }
+        Ok(mat::from_column_major_slice_mut(
+            value.data_mut(),
+            nrow,
+            ncol,
+        ))
+    }
+}
+
+impl<'a> TryFrom<&'a mut Robj> for MatMut<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a mut Robj) -> Result<Self> {
+        let rmat = RMatrix::<f64>::try_from(&*robj)?;
+        let nrows = rmat.nrows();
+        let ncols = rmat.ncols();
+
+        if real_slice(robj).is_some() {
+            let slice = robj.as_typed_slice_mut().unwrap();
+            Ok(mat::from_column_major_slice_mut(slice, nrows, ncols))
+        } else {
+            Err(Error::ExpectedReal(robj.clone()))
+        }
+    }
+}
+
+fn c64_from_rcplx(value: Rcplx) -> c64 {
+    c64::new(value.re().into(), value.im().into())
+}
+
+fn rcplx_from_c64(value: c64) -> Rcplx {
+    Rcplx::new(value.re, value.im)
+}
+
+/// Convert a `faer::Mat<c64>` into a complex `RMatrix`.
+impl From<Mat<c64>> for RMatrix<Rcplx> {
+    fn from(value: Mat<c64>) -> Self {
+        RMatrix::from(value.as_ref())
+    }
+}
+
+impl From<MatRef<'_, c64>> for RMatrix<Rcplx> {
+    fn from(value: MatRef<'_, c64>) -> Self {
+        RMatrix::new_matrix(value.nrows(), value.ncols(), |i, j| {
+            rcplx_from_c64(value.read(i, j))
+        })
+    }
+}
+
+impl From<Mat<c64>> for Robj {
+    fn from(value: Mat<c64>) -> Self {
+        RMatrix::<Rcplx>::from(value).into()
+    }
+}
+
+impl From<MatRef<'_, c64>> for Robj {
+    fn from(value: MatRef<'_, c64>) -> Self {
+        RMatrix::<Rcplx>::from(value).into()
+    }
+}
+
+impl From<RMatrix<Rcplx>> for Mat<c64> {
+    fn from(value: RMatrix<Rcplx>) -> Self {
+        let nrow = value.nrows();
+        let slice = value.data();
+        Mat::from_fn(nrow, value.ncols(), |i, j| {
+            c64_from_rcplx(slice[i + j * nrow])
+        })
+    }
+}
+
+impl TryFrom<&Robj> for Mat<c64> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        Ok(RMatrix::<Rcplx>::try_from(robj)?.into())
+    }
+}
+
+impl TryFrom<Robj> for Mat<c64> {
+    type Error = crate::Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+/// Convert a `faer::Mat<f32>` into a double `RMatrix`.
+impl From<Mat<f32>> for RMatrix<f64> {
+    fn from(value: Mat<f32>) -> Self {
+        RMatrix::new_matrix(value.nrows(), value.ncols(), |i, j| value.read(i, j) as f64)
+    }
+}
+
+impl From<Mat<f32>> for Robj {
+    fn from(value: Mat<f32>) -> Self {
+        RMatrix::<f64>::from(value).into()
+    }
+}
+
+/// Copy a double matrix, rounding to `f32`.
+impl TryFrom<&Robj> for Mat<f32> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let rmat = RMatrix::<f64>::try_from(robj)?;
+        let nrow = rmat.nrows();
+        let slice = real_slice(robj).ok_or_else(|| Error::ExpectedReal(robj.clone()))?;
+        Ok(Mat::from_fn(nrow, rmat.ncols(), |i, j| {
+            slice[i + j * nrow] as f32
+        }))
+    }
+}
+
+impl TryFrom<Robj> for Mat<f32> {
+    type Error = crate::Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+/// Convert a `faer::Col<f64>` into a double vector without dimensions.
+impl From<Col<f64>> for Robj {
+    fn from(value: Col<f64>) -> Self {
+        Robj::from(value.as_ref())
+    }
+}
+
+impl From<ColRef<'_, f64>> for Robj {
+    fn from(value: ColRef<'_, f64>) -> Self {
+        (0..value.nrows()).map(|i| value.read(i)).collect_robj()
+    }
+}
+
+/// Convert a `faer::Row<f64>` into a double vector without dimensions.
+impl From<Row<f64>> for Robj {
+    fn from(value: Row<f64>) -> Self {
+        Robj::from(value.as_ref())
+    }
+}
+
+impl From<RowRef<'_, f64>> for Robj {
+    fn from(value: RowRef<'_, f64>) -> Self {
+        (0..value.ncols()).map(|j| value.read(j)).collect_robj()
+    }
+}
+
+impl TryFrom<&Robj> for Col<f64> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let col = ColRef::<f64>::try_from(robj)?;
+        Ok(col.to_owned())
+    }
+}
+
+impl TryFrom<&Robj> for Row<f64> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let row = RowRef::<f64>::try_from(robj)?;
+        Ok(row.to_owned())
+    }
+}
+
+/// Borrow the data of a double vector. The dimensions of matrices are ignored.
+impl<'a> TryFrom<&'a Robj> for ColRef<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a Robj) -> Result<Self> {
+        if let Some(slice) = real_slice(robj) {
+            Ok(col::from_slice(slice))
+        } else {
+            Err(Error::ExpectedReal(robj.clone()))
+        }
+    }
+}
+
+/// Borrow the data of a double vector. The dimensions of matrices are ignored.
+impl<'a> TryFrom<&'a Robj> for RowRef<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a Robj) -> Result<Self> {
+        if let Some(slice) = real_slice(robj) {
+            Ok(row::from_slice(slice))
+        } else {
+            Err(Error::ExpectedReal(robj.clone()))
+        }
+    }
+}
+
+/// Mutably borrow the data of a double vector, to modify it in place.
+impl<'a> TryFrom<&'a mut Robj> for ColMut<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a mut Robj) -> Result<Self> {
+        if real_slice(robj).is_some() {
+            Ok(col::from_slice_mut(robj.as_typed_slice_mut().unwrap()))
+        } else {
+            Err(Error::ExpectedReal(robj.clone()))
+        }
+    }
+}
+
+impl TryFrom<Robj> for Col<f64> {
+    type Error = crate::Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl TryFrom<Robj> for Row<f64> {
+    type Error = crate::Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate as extendr_api;
     use crate::*;
-    use faer::{mat, Mat, MatRef};
+    use faer::complex_native::c64;
+    use faer::{col, mat, row, Col, ColMut, Mat, MatMut, MatRef, Row};
 
     #[test]
     fn test_rmatrix_to_faer_mat() {
@@ -264,4 +498,75 @@ mod test {
             assert_eq!(a, b);
         }
     }
+
+    #[test]
+    fn test_faer_mat_mut() {
+        test! {
+            let mut rmatrix = RMatrix::new_matrix(2, 2, |i, j| (i + 2 * j) as f64);
+            let mut view = MatMut::<f64>::try_from(&mut rmatrix)?;
+            view.write(1, 1, 10.0);
+            assert_eq!(rmatrix.data(), &[0.0, 1.0, 2.0, 10.0]);
+
+            let mut robj = R!("matrix(c(1, 2, 3, 4), 2)")?;
+            let mut view = MatMut::<f64>::try_from(&mut robj)?;
+            view.write(0, 0, 0.0);
+            assert_eq!(robj, R!("matrix(c(0, 2, 3, 4), 2)")?);
+
+            let mut robj = R!("matrix(1:4, 2)")?;
+            assert!(MatMut::<f64>::try_from(&mut robj).is_err());
+
+            // `integer64` stores the bits of `i64` values in doubles.
+            let mut robj = R!("matrix(c(1, 2, 3, 4), 2)")?;
+            robj.set_class(["integer64"])?;
+            assert!(MatMut::<f64>::try_from(&mut robj).is_err());
+            assert!(Mat::<f32>::try_from(&robj).is_err());
+            assert!(MatRef::<f64>::try_from(&robj).is_err());
+            let mut rmatrix = RMatrix::<f64>::try_from(robj.clone())?;
+            assert!(MatMut::<f64>::try_from(&mut rmatrix).is_err());
+            let mut robj = robj;
+            assert!(ColMut::<f64>::try_from(&mut robj).is_err());
+        }
+    }
+
+    #[test]
+    fn test_faer_complex() {
+        test! {
+            let robj = R!("matrix(complex(real = 1:4, imaginary = -1), 2)")?;
+            let a = Mat::<c64>::try_from(&robj)?;
+            assert_eq!(a.read(1, 0), c64::new(2.0, -1.0));
+            assert_eq!(Robj::from(a), robj);
+
+            let b = mat![[c64::new(0.0, 1.0)]];
+            assert_eq!(Robj::from(b.as_ref()), R!("matrix(1i, 1)")?);
+        }
+    }
+
+    #[test]
+    fn test_faer_f32() {
+        test! {
+            let a = Mat::<f32>::try_from(R!("matrix(c(0.5, 1.5), 1)")?)?;
+            assert_eq!(a, mat![[0.5f32, 1.5]]);
+            assert_eq!(Robj::from(a), R!("matrix(c(0.5, 1.5), 1)")?);
+        }
+    }
+
+    #[test]
+    fn test_faer_col_row() {
+        test! {
+            let robj = R!("c(1, 2, 3)")?;
+            let a = Col::<f64>::try_from(&robj)?;
+            assert_eq!(a, col![1.0, 2.0, 3.0]);
+            assert_eq!(Robj::from(a), robj);
+
+            let b = Row::<f64>::try_from(&robj)?;
+            assert_eq!(b, row![1.0, 2.0, 3.0]);
+            assert_eq!(Robj::from(b.as_ref()), robj);
+
+            let mut robj = R!("c(1, 2)")?;
+            let mut c = ColMut::<f64>::try_from(&mut robj)?;
+            c.write(1, 0.0);
+            assert_eq!(robj, R!("c(1, 0)")?);
+            assert!(Col::<f64>::try_from(R!("1:2")?).is_err());
+        }
+    }
 }
@@ -0,0 +1,110 @@
+/*!
+Conversions between R's date and time classes and [`jiff`](https://docs.rs/jiff/latest/jiff/).
+
+| R          | jiff                               |
+|------------|------------------------------------|
+| `Date`     | [`civil::Date`](jiff::civil::Date) |
+| `POSIXct`  | [`Zoned`], [`Timestamp`]           |
+| `difftime` | [`SignedDuration`]                 |
+
+`Option<T>`, `Vec<T>` and `Vec<Option<T>>` are supported too, with `None` for `NA`.
+
+Unlike the other time crates, `jiff` knows time zone names, so a `Zoned`
+keeps the `tzone` attribute of a `POSIXct`, and vice versa. An empty `tzone`
+is the system time zone. As R has one time zone per vector, a `Vec<Zoned>`
+is returned in the time zone of its first element.
+
+```rust
+use extendr_api::prelude::*;
+use jiff::{ToSpan, Zoned};
+
+#[extendr]
+fn tomorrow(time: Zoned) -> Zoned {
+    time.checked_add(1.day()).unwrap()
+}
+```
+*/
+use jiff::{civil::Date, tz::TimeZone, SignedDuration, Timestamp, Zoned};
+
+use crate::wrapper::datetime::{impl_time_conversions, TimeClass, TimeValue};
+use crate::*;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+const UNIX_EPOCH: Date = Date::constant(1970, 1, 1);
+
+fn out_of_range(value: f64) -> Error {
+    Error::OutOfRange(value.into())
+}
+
+impl TimeValue for Date {
+    const CLASS: TimeClass = TimeClass::Date;
+
+    fn to_r(&self) -> f64 {
+        (self.duration_since(UNIX_EPOCH).as_secs() / SECONDS_PER_DAY) as f64
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        let days = value.floor() as i64;
+        days.checked_mul(SECONDS_PER_DAY)
+            .and_then(|seconds| {
+                UNIX_EPOCH
+                    .checked_add(SignedDuration::from_secs(seconds))
+                    .ok()
+            })
+            .ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for Timestamp {
+    const CLASS: TimeClass = TimeClass::DateTime;
+
+    fn to_r(&self) -> f64 {
+        self.as_duration().as_secs_f64()
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        SignedDuration::try_from_secs_f64(value)
+            .ok()
+            .and_then(|duration| Timestamp::from_duration(duration).ok())
+            .ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for Zoned {
+    const CLASS: TimeClass = TimeClass::DateTime;
+
+    fn to_r(&self) -> f64 {
+        self.timestamp().to_r()
+    }
+
+    fn tzone(&self) -> &str {
+        self.time_zone().iana_name().unwrap_or("UTC")
+    }
+
+    fn from_r(value: f64, tzone: &str) -> Result<Self> {
+        let tz = if tzone.is_empty() {
+            TimeZone::system()
+        } else {
+            TimeZone::get(tzone).map_err(|e| Error::Other(e.to_string()))?
+        };
+        Ok(Timestamp::from_r(value, tzone)?.to_zoned(tz))
+    }
+}
+
+impl TimeValue for SignedDuration {
+    const CLASS: TimeClass = TimeClass::Difftime;
+
+    fn to_r(&self) -> f64 {
+        self.as_secs_f64()
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        SignedDuration::try_from_secs_f64(value).map_err(|_| out_of_range(value))
+    }
+}
+
+impl_time_conversions!(Date);
+impl_time_conversions!(Timestamp);
+impl_time_conversions!(Zoned);
+impl_time_conversions!(SignedDuration);
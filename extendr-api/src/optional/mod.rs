@@ -1,9 +1,25 @@
 /*!
 A set of optional features and third-party crate integrations, usually hidden behind feature gates.
 */
+#[cfg(feature = "chrono")]
+pub mod chrono;
 #[cfg(feature = "either")]
 pub mod either;
 #[cfg(feature = "faer")]
 pub mod faer;
+#[cfg(feature = "jiff")]
+pub mod jiff;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
 #[cfg(feature = "ndarray")]
 pub mod ndarray;
+#[cfg(feature = "time")]
+pub mod time;
+
+/// The data of a double vector. `integer64` vectors store the bits of `i64`
+/// values in doubles, so they have none.
+#[cfg(any(feature = "faer", feature = "nalgebra"))]
+pub(crate) fn real_slice(robj: &crate::Robj) -> Option<&[f64]> {
+    use crate::Rinternals;
+    robj.as_real_slice().filter(|_| !robj.is_integer64())
+}
@@ -0,0 +1,417 @@
+/*!
+Conversions between R's vectors and matrices and [`nalgebra`](https://docs.rs/nalgebra/latest/nalgebra/)'s
+dynamically sized `DMatrix` and `DVector`.
+
+`DMatrixView` and `DVectorView` borrow the data of double vectors and matrices,
+which are stored in column-major order, as `nalgebra` stores them. Integer and
+logical matrices are copied to `DMatrix<f64>`, with `NA` as R's `NA_real_`.
+
+```
+use extendr_api::prelude::*;
+use extendr_api::optional::nalgebra::NamedDMatrix;
+use nalgebra::{DMatrix, DMatrixView};
+
+test! {
+    let robj = R!("matrix(c(1, 2, 3, 4), 2)")?;
+    let view = DMatrixView::<f64>::try_from(&robj)?;
+    assert_eq!(view[(1, 0)], 2.0);
+
+    let product: Robj = (view * view.transpose()).into();
+    assert_eq!(product, R!("tcrossprod(matrix(c(1, 2, 3, 4), 2))")?);
+
+    // Keep the dimnames for the result.
+    let robj = R!("matrix(1:4, 2, dimnames = list(c('a', 'b'), c('x', 'y')))")?;
+    let mut named = NamedDMatrix::<f64>::try_from(&robj)?;
+    named.matrix *= 2.0;
+    assert_eq!(Robj::from(named), R!("matrix(c(2, 4, 6, 8), 2, dimnames = list(c('a', 'b'), c('x', 'y')))")?);
+}
+```
+*/
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView, Scalar};
+
+use super::real_slice;
+use crate::*;
+
+/// Convert a `nalgebra::DMatrix` into an `RMatrix`, which is not `NA` aware.
+impl<T> From<DMatrix<T>> for RMatrix<T>
+where
+    T: Scalar + ToVectorValue,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from(value: DMatrix<T>) -> Self {
+        RMatrix::from(value.as_view())
+    }
+}
+
+impl<T> From<DMatrixView<'_, T>> for RMatrix<T>
+where
+    T: Scalar + ToVectorValue,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from(value: DMatrixView<'_, T>) -> Self {
+        RMatrix::new_matrix(value.nrows(), value.ncols(), |i, j| value[(i, j)].clone())
+    }
+}
+
+impl<T> From<DMatrix<T>> for Robj
+where
+    T: Scalar + ToVectorValue,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from(value: DMatrix<T>) -> Self {
+        RMatrix::<T>::from(value).into()
+    }
+}
+
+impl<T> From<DMatrixView<'_, T>> for Robj
+where
+    T: Scalar + ToVectorValue,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from(value: DMatrixView<'_, T>) -> Self {
+        RMatrix::<T>::from(value).into()
+    }
+}
+
+/// Convert a `nalgebra::DVector` into an R vector without dimensions.
+impl<T> From<DVector<T>> for Robj
+where
+    T: Scalar + ToVectorValue,
+{
+    fn from(value: DVector<T>) -> Self {
+        value.iter().cloned().collect_robj()
+    }
+}
+
+impl<T> From<DVectorView<'_, T>> for Robj
+where
+    T: Scalar + ToVectorValue,
+{
+    fn from(value: DVectorView<'_, T>) -> Self {
+        value.iter().cloned().collect_robj()
+    }
+}
+
+impl From<RMatrix<f64>> for DMatrix<f64> {
+    fn from(value: RMatrix<f64>) -> Self {
+        DMatrix::from_column_slice(value.nrows(), value.ncols(), value.data())
+    }
+}
+
+impl From<RMatrix<i32>> for DMatrix<f64> {
+    fn from(value: RMatrix<i32>) -> Self {
+        DMatrix::from_iterator(
+            value.nrows(),
+            value.ncols(),
+            value.data().iter().map(|&x| integer_to_real(x)),
+        )
+    }
+}
+
+impl<'a> From<&'a RMatrix<f64>> for DMatrixView<'a, f64> {
+    fn from(value: &'a RMatrix<f64>) -> Self {
+        DMatrixView::from_slice(value.data(), value.nrows(), value.ncols())
+    }
+}
+
+fn integer_to_real(x: i32) -> f64 {
+    if x.is_na() {
+        f64::na()
+    } else {
+        x as f64
+    }
+}
+
+fn logical_to_integer(x: Rbool) -> i32 {
+    if x.is_na() {
+        i32::na()
+    } else {
+        x.to_bool() as i32
+    }
+}
+
+fn logical_to_real(x: Rbool) -> f64 {
+    if x.is_na() {
+        f64::na()
+    } else {
+        x.to_bool() as i32 as f64
+    }
+}
+
+/// The dimensions of a matrix.
+fn matrix_dim(robj: &Robj) -> Result<(usize, usize)> {
+    if robj.is_matrix() {
+        Ok((robj.nrows(), robj.ncols()))
+    } else {
+        Err(Error::ExpectedMatrix(robj.clone()))
+    }
+}
+
+/// Copy a double, integer or logical matrix.
+impl TryFrom<&Robj> for DMatrix<f64> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let (nrows, ncols) = matrix_dim(robj)?;
+        if let Some(slice) = real_slice(robj) {
+            Ok(DMatrix::from_column_slice(nrows, ncols, slice))
+        } else if let Some(slice) = robj.as_integer_slice() {
+            let values = slice.iter().map(|&x| integer_to_real(x));
+            Ok(DMatrix::from_iterator(nrows, ncols, values))
+        } else if let Some(slice) = robj.as_logical_slice() {
+            let values = slice.iter().map(|&x| logical_to_real(x));
+            Ok(DMatrix::from_iterator(nrows, ncols, values))
+        } else {
+            Err(Error::ExpectedNumeric(robj.clone()))
+        }
+    }
+}
+
+/// Copy an integer or logical matrix. `NA` is `i32::MIN`.
+impl TryFrom<&Robj> for DMatrix<i32> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let (nrows, ncols) = matrix_dim(robj)?;
+        if let Some(slice) = robj.as_integer_slice() {
+            Ok(DMatrix::from_column_slice(nrows, ncols, slice))
+        } else if let Some(slice) = robj.as_logical_slice() {
+            let values = slice.iter().map(|&x| logical_to_integer(x));
+            Ok(DMatrix::from_iterator(nrows, ncols, values))
+        } else {
+            Err(Error::ExpectedInteger(robj.clone()))
+        }
+    }
+}
+
+/// Borrow the data of a double matrix.
+impl<'a> TryFrom<&'a Robj> for DMatrixView<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a Robj) -> Result<Self> {
+        let (nrows, ncols) = matrix_dim(robj)?;
+        let slice = real_slice(robj).ok_or_else(|| Error::ExpectedReal(robj.clone()))?;
+        Ok(DMatrixView::from_slice(slice, nrows, ncols))
+    }
+}
+
+/// Copy a double, integer or logical vector. The dimensions of matrices are ignored.
+impl TryFrom<&Robj> for DVector<f64> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if let Some(slice) = real_slice(robj) {
+            Ok(DVector::from_column_slice(slice))
+        } else if let Some(slice) = robj.as_integer_slice() {
+            Ok(DVector::from_iterator(
+                slice.len(),
+                slice.iter().map(|&x| integer_to_real(x)),
+            ))
+        } else if let Some(slice) = robj.as_logical_slice() {
+            Ok(DVector::from_iterator(
+                slice.len(),
+                slice.iter().map(|&x| logical_to_real(x)),
+            ))
+        } else {
+            Err(Error::ExpectedNumeric(robj.clone()))
+        }
+    }
+}
+
+/// Borrow the data of a double vector. The dimensions of matrices are ignored.
+impl<'a> TryFrom<&'a Robj> for DVectorView<'a, f64> {
+    type Error = Error;
+
+    fn try_from(robj: &'a Robj) -> Result<Self> {
+        let slice = real_slice(robj).ok_or_else(|| Error::ExpectedReal(robj.clone()))?;
+        Ok(DVectorView::from_slice(slice, slice.len()))
+    }
+}
+
+impl TryFrom<Robj> for DMatrix<f64> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl TryFrom<Robj> for DMatrix<i32> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl TryFrom<Robj> for DVector<f64> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+/// A `DMatrix` with the dimnames of the R matrix that it was made from.
+///
+/// Converting it back to R keeps the row and column names whose length
+/// still matches the number of rows and columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedDMatrix<T: Scalar> {
+    pub matrix: DMatrix<T>,
+    pub dimnames: Option<List>,
+}
+
+impl<T: Scalar> From<DMatrix<T>> for NamedDMatrix<T> {
+    fn from(matrix: DMatrix<T>) -> Self {
+        Self {
+            matrix,
+            dimnames: None,
+        }
+    }
+}
+
+impl<T: Scalar> TryFrom<&Robj> for NamedDMatrix<T>
+where
+    for<'a> DMatrix<T>: TryFrom<&'a Robj, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let dimnames = robj
+            .get_attrib(wrapper::symbol::dimnames_symbol())
+            .map(List::try_from)
+            .transpose()?;
+        Ok(Self {
+            matrix: robj.try_into()?,
+            dimnames,
+        })
+    }
+}
+
+impl<T: Scalar> TryFrom<Robj> for NamedDMatrix<T>
+where
+    for<'a> DMatrix<T>: TryFrom<&'a Robj, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<T> From<NamedDMatrix<T>> for Robj
+where
+    T: Scalar + ToVectorValue,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from(value: NamedDMatrix<T>) -> Self {
+        let dim = [value.matrix.nrows(), value.matrix.ncols()];
+        let mut robj: Robj = value.matrix.into();
+        if let Some(dimnames) = value.dimnames.filter(|dimnames| dimnames.len() == 2) {
+            let dimnames =
+                dimnames.values().zip(dim).map(
+                    |(names, len)| {
+                        if names.len() == len {
+                            names
+                        } else {
+                            ().into()
+                        }
+                    },
+                );
+            let dimnames = List::from_values(dimnames);
+            if dimnames.values().any(|names| !names.is_null()) {
+                robj.set_attrib(wrapper::symbol::dimnames_symbol(), dimnames)
+                    .unwrap();
+            }
+        }
+        robj
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NamedDMatrix;
+    use crate as extendr_api;
+    use crate::*;
+    use nalgebra::{dmatrix, dvector, DMatrix, DMatrixView, DVector, DVectorView};
+
+    #[test]
+    fn test_rmatrix_to_dmatrix() {
+        test! {
+            let rmatrix = RMatrix::new_matrix(3, 2, |i, j| (i + 3 * j) as f64);
+            let a = DMatrix::<f64>::from(rmatrix);
+            assert_eq!(a, dmatrix![0.0, 3.0; 1.0, 4.0; 2.0, 5.0]);
+
+            let rmatrix = RMatrix::new_matrix(2, 2, |i, j| if i == j { i32::na() } else { 1 });
+            let a = DMatrix::<f64>::from(rmatrix);
+            assert!(a[(0, 0)].is_na());
+            assert_eq!(a[(0, 1)], 1.0);
+        }
+    }
+
+    #[test]
+    fn test_rmatrix_to_dmatrix_view() {
+        test! {
+            let rmatrix = RMatrix::new_matrix(3, 2, |i, j| (i + 3 * j) as f64);
+            let view = DMatrixView::<f64>::from(&rmatrix);
+            assert_eq!(view, dmatrix![0.0, 3.0; 1.0, 4.0; 2.0, 5.0]);
+            assert_eq!(view.as_ptr(), rmatrix.data().as_ptr());
+        }
+    }
+
+    #[test]
+    fn test_try_from_robj() {
+        test! {
+            let a = DMatrix::<f64>::try_from(R!("matrix(c(1, 2, 3, 4), 2)")?)?;
+            assert_eq!(a, dmatrix![1.0, 3.0; 2.0, 4.0]);
+
+            let a = DMatrix::<f64>::try_from(R!("matrix(c(TRUE, FALSE, NA, TRUE), 2)")?)?;
+            assert_eq!(a[(1, 0)], 0.0);
+            assert!(a[(0, 1)].is_na());
+
+            let a = DMatrix::<i32>::try_from(R!("matrix(1:4, 2)")?)?;
+            assert_eq!(a, dmatrix![1, 3; 2, 4]);
+
+            assert!(DMatrix::<f64>::try_from(R!("c(1, 2)")?).is_err());
+            assert!(DMatrix::<i32>::try_from(R!("matrix(c(1, 2), 1)")?).is_err());
+
+            let robj = R!("c(1, 2, 3)")?;
+            let v = DVector::<f64>::try_from(&robj)?;
+            assert_eq!(v, dvector![1.0, 2.0, 3.0]);
+            let v = DVectorView::<f64>::try_from(&robj)?;
+            assert_eq!(v, dvector![1.0, 2.0, 3.0]);
+            assert_eq!(DVector::<f64>::try_from(R!("1:2")?)?, dvector![1.0, 2.0]);
+
+            // `integer64` vectors hold the bits of `i64` values, not doubles.
+            let robj = R!("structure(c(1, 2, 3, 4), dim = c(2L, 2L), class = 'integer64')")?;
+            assert!(DMatrix::<f64>::try_from(&robj).is_err());
+            assert!(DMatrixView::<f64>::try_from(&robj).is_err());
+            assert!(DVector::<f64>::try_from(&robj).is_err());
+            assert!(DVectorView::<f64>::try_from(&robj).is_err());
+        }
+    }
+
+    #[test]
+    fn test_dmatrix_to_robj() {
+        test! {
+            let a = dmatrix![1.0, 3.0; 2.0, 4.0];
+            assert_eq!(Robj::from(a.clone()), R!("matrix(c(1, 2, 3, 4), 2)")?);
+            assert_eq!(Robj::from(a.view((0, 1), (2, 1))), R!("matrix(c(3, 4), 2)")?);
+            assert_eq!(Robj::from(dmatrix![1, 2]), R!("matrix(1:2, 1)")?);
+            assert_eq!(Robj::from(dvector![1.0, 2.0]), R!("c(1, 2)")?);
+        }
+    }
+
+    #[test]
+    fn test_named_dmatrix() {
+        test! {
+            let robj = R!("matrix(c(1, 2, 3, 4), 2, dimnames = list(NULL, c('x', 'y')))")?;
+            let named = NamedDMatrix::<f64>::try_from(&robj)?;
+            assert_eq!(Robj::from(named.clone()), robj);
+
+            let wider = NamedDMatrix { matrix: named.matrix.clone().insert_column(2, 0.0), ..named };
+            assert_eq!(Robj::from(wider), R!("matrix(c(1, 2, 3, 4, 0, 0), 2)")?);
+        }
+    }
+}
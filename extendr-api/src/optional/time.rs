@@ -0,0 +1,83 @@
+/*!
+Conversions between R's date and time classes and [`time`](https://docs.rs/time/latest/time/).
+
+| R          | time                 |
+|------------|----------------------|
+| `Date`     | [`Date`]             |
+| `POSIXct`  | [`OffsetDateTime`]   |
+| `difftime` | [`Duration`]         |
+
+`Option<T>`, `Vec<T>` and `Vec<Option<T>>` are supported too, with `None` for `NA`.
+
+Date-times are returned to R with time zone `"UTC"`; the instant in time is
+unchanged. Date-times from R are read in UTC, whatever their `tzone` attribute.
+
+```rust
+use extendr_api::prelude::*;
+use time::{Duration, OffsetDateTime};
+
+#[extendr]
+fn elapsed(start: OffsetDateTime, end: OffsetDateTime) -> Duration {
+    end - start
+}
+```
+*/
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::wrapper::datetime::{impl_time_conversions, TimeClass, TimeValue};
+use crate::*;
+
+// The Julian day of 1970-01-01.
+const UNIX_EPOCH_JULIAN_DAY: i64 = 2_440_588;
+
+fn out_of_range(value: f64) -> Error {
+    Error::OutOfRange(value.into())
+}
+
+impl TimeValue for Date {
+    const CLASS: TimeClass = TimeClass::Date;
+
+    fn to_r(&self) -> f64 {
+        (self.to_julian_day() as i64 - UNIX_EPOCH_JULIAN_DAY) as f64
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        i32::try_from(value.floor() as i64 + UNIX_EPOCH_JULIAN_DAY)
+            .ok()
+            .and_then(|day| Date::from_julian_day(day).ok())
+            .ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for OffsetDateTime {
+    const CLASS: TimeClass = TimeClass::DateTime;
+
+    fn to_r(&self) -> f64 {
+        self.unix_timestamp() as f64 + self.nanosecond() as f64 * 1e-9
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        let seconds = value.floor();
+        let nanos = ((value - seconds) * 1e9).round().min(999_999_999.0) as i64;
+        OffsetDateTime::from_unix_timestamp(seconds as i64)
+            .ok()
+            .and_then(|time| time.checked_add(Duration::nanoseconds(nanos)))
+            .ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl TimeValue for Duration {
+    const CLASS: TimeClass = TimeClass::Difftime;
+
+    fn to_r(&self) -> f64 {
+        self.as_seconds_f64()
+    }
+
+    fn from_r(value: f64, _tzone: &str) -> Result<Self> {
+        Duration::checked_seconds_f64(value).ok_or_else(|| out_of_range(value))
+    }
+}
+
+impl_time_conversions!(Date);
+impl_time_conversions!(OffsetDateTime);
+impl_time_conversions!(Duration);
@@ -7,16 +7,41 @@
 //! unlike `R_PreserveObject`.
 //!
 //! This module exports two functions, `protect(sexp)` and `unprotect(sexp)`.
+//!
+//! Short-lived objects can instead be protected for the duration of a
+//! [`protect_scope`], which does not use the global table.
+//!
+//! [`protection_stats`] and [`protection_snapshot`] show the objects in the
+//! table, so that tests can check that a function leaves nothing protected.
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::ownership::protection_snapshot;
+//! test! {
+//!     let before = protection_snapshot();
+//!     std::mem::forget(r!([1.0, 2.0]));
+//!     let leaked = before.diff(&protection_snapshot()).added;
+//!     assert_eq!(leaked.len(), 1);
+//!     assert_eq!(leaked[0].rtype, Rtype::Doubles);
+//! }
+//! ```
 
 use once_cell::sync::Lazy;
+use std::cell::Cell;
 use std::collections::hash_map::{Entry, HashMap};
+use std::marker::PhantomData;
 use std::sync::Mutex;
 
 use extendr_ffi::{
-    R_NilValue, R_PreserveObject, R_ReleaseObject, R_xlen_t, Rf_allocVector, Rf_protect,
-    Rf_unprotect, LENGTH, SET_VECTOR_ELT, SEXP, SEXPTYPE, VECTOR_ELT,
+    R_ClassSymbol, R_NilValue, R_PreserveObject, R_ProtectWithIndex, R_ReleaseObject, R_Reprotect,
+    R_xlen_t, Rf_allocVector, Rf_protect, Rf_setAttrib, Rf_unprotect, LENGTH, PROTECT_INDEX,
+    SET_VECTOR_ELT, SEXP, SEXPTYPE, TYPEOF, VECTOR_ELT,
 };
 
+use crate::{sxp_to_rtype, Rtype};
+
+use crate::robj::{fill_vector, Robj, ToVectorValue};
+use crate::thread_safety::single_threaded;
+
 mod send_sexp {
     //! Provide a wrapper around R's pointer type `SEXP` that is `Send`.
     //!
@@ -61,16 +86,159 @@ pub(crate) unsafe fn unprotect(sexp: SEXP) {
     own.unprotect(sexp);
 }
 
+/// Statistics of the objects protected by the global table, see [`protection_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProtectionStats {
+    /// The number of protected objects.
+    pub protected: usize,
+    /// The highest number of protected objects so far, see [`reset_high_water_mark`].
+    pub high_water_mark: usize,
+    /// The size of the preservation vector.
+    pub capacity: usize,
+    /// The number of times the table has been compacted.
+    pub compactions: usize,
+}
+
+/// Get statistics of the objects protected by the global table.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::ownership::protection_stats;
+/// test! {
+///     let before = protection_stats();
+///     let robj = r!(1);
+///     assert_eq!(protection_stats().protected, before.protected + 1);
+///     assert!(protection_stats().high_water_mark > before.protected);
+///     drop(robj);
+///     assert_eq!(protection_stats().protected, before.protected);
+/// }
+/// ```
+pub fn protection_stats() -> ProtectionStats {
+    let own = OWNERSHIP.lock().expect("protection_stats failed");
+    ProtectionStats {
+        protected: own.protected,
+        high_water_mark: own.high_water_mark,
+        capacity: own.max_index,
+        compactions: own.compactions,
+    }
+}
+
+/// Reset the high-water mark to the current number of protected objects.
+pub fn reset_high_water_mark() {
+    let mut own = OWNERSHIP.lock().expect("reset_high_water_mark failed");
+    own.high_water_mark = own.protected;
+}
+
+/// An object in a [`ProtectionSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedObject {
+    /// The address of the SEXP.
+    pub address: usize,
+    pub rtype: Rtype,
+    /// The number of `Robj`s that hold the object.
+    pub refcount: usize,
+    /// Where the object was protected, if the `debug-ownership` feature is enabled.
+    pub backtrace: Option<String>,
+}
+
+/// The objects protected by the global table at some point, see [`protection_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProtectionSnapshot {
+    objects: Vec<ProtectedObject>,
+}
+
+/// The changes between two [`ProtectionSnapshot`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProtectionDiff {
+    /// Objects that were protected, or gained references, since the first snapshot.
+    pub added: Vec<ProtectedObject>,
+    /// Objects that were released, or lost references, since the first snapshot.
+    pub removed: Vec<ProtectedObject>,
+}
+
+impl ProtectionDiff {
+    /// Returns `true` if no objects were protected or released.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl ProtectionSnapshot {
+    /// The protected objects, ordered by address.
+    pub fn objects(&self) -> &[ProtectedObject] {
+        &self.objects
+    }
+
+    /// The number of protected objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if no objects are protected.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Compare with a later snapshot.
+    pub fn diff(&self, later: &ProtectionSnapshot) -> ProtectionDiff {
+        fn gained(from: &ProtectionSnapshot, to: &ProtectionSnapshot) -> Vec<ProtectedObject> {
+            to.objects
+                .iter()
+                .filter(|object| {
+                    let before = from
+                        .objects
+                        .binary_search_by_key(&object.address, |o| o.address)
+                        .map_or(0, |i| from.objects[i].refcount);
+                    object.refcount > before
+                })
+                .cloned()
+                .collect()
+        }
+        ProtectionDiff {
+            added: gained(self, later),
+            removed: gained(later, self),
+        }
+    }
+}
+
+/// Take a snapshot of the objects protected by the global table.
+///
+/// Compare snapshots taken before and after some code with
+/// [`ProtectionSnapshot::diff`] to find the objects it leaked.
+pub fn protection_snapshot() -> ProtectionSnapshot {
+    let own = OWNERSHIP.lock().expect("protection_snapshot failed");
+    let mut objects: Vec<ProtectedObject> = own
+        .objects
+        .iter()
+        .filter(|(_, object)| object.refcount != 0)
+        .map(|(addr, object)| unsafe {
+            ProtectedObject {
+                address: addr.inner() as usize,
+                rtype: sxp_to_rtype(TYPEOF(addr.inner())),
+                refcount: object.refcount,
+                #[cfg(feature = "debug-ownership")]
+                backtrace: object.backtrace.as_ref().map(|bt| bt.to_string()),
+                #[cfg(not(feature = "debug-ownership"))]
+                backtrace: None,
+            }
+        })
+        .collect();
+    objects.sort_by_key(|object| object.address);
+    ProtectionSnapshot { objects }
+}
+
 pub const INITIAL_PRESERVATION_SIZE: usize = 100000;
 pub const EXTRA_PRESERVATION_SIZE: usize = 100000;
 
 // `Object` is a manual reference counting mechanism that is used for each SEXP.
 // `refcount` is the number of times the SEXP is accessed.
 // `index` is the index of the SEXP in the preservation vector.
+// `backtrace` is where the SEXP was protected, with the `debug-ownership` feature.
 #[derive(Debug)]
 struct Object {
     refcount: usize,
     index: usize,
+    #[cfg(feature = "debug-ownership")]
+    backtrace: Option<std::backtrace::Backtrace>,
 }
 
 // A reference counted object with an index in the preservation vector.
@@ -87,6 +255,13 @@ struct Ownership {
 
     // A hash map from SEXP address to object.
     objects: HashMap<SendSEXP, Object>,
+
+    // The number of objects with a non-zero refcount, and its maximum.
+    protected: usize,
+    high_water_mark: usize,
+
+    // The number of calls to `garbage_collect`.
+    compactions: usize,
 }
 
 impl Ownership {
@@ -100,6 +275,9 @@ impl Ownership {
                 cur_index: 0,
                 max_index: INITIAL_PRESERVATION_SIZE,
                 objects: HashMap::with_capacity(INITIAL_PRESERVATION_SIZE),
+                protected: 0,
+                high_water_mark: 0,
+                compactions: 0,
             }
         }
     }
@@ -115,16 +293,10 @@ impl Ownership {
 
         // copy non-null elements to new vector and hashmap.
         let mut j = 0;
-        for (addr, object) in self.objects.iter() {
+        for (addr, object) in self.objects.drain() {
             if object.refcount != 0 {
                 SET_VECTOR_ELT(new_sexp, j as R_xlen_t, addr.inner());
-                new_objects.insert(
-                    addr.clone(),
-                    Object {
-                        refcount: object.refcount,
-                        index: j,
-                    },
-                );
+                new_objects.insert(addr, Object { index: j, ..object });
                 j += 1;
             }
         }
@@ -134,6 +306,7 @@ impl Ownership {
         self.cur_index = j;
         self.max_index = new_size;
         self.objects = new_objects;
+        self.compactions += 1;
     }
 
     unsafe fn protect(&mut self, sexp: SEXP) {
@@ -157,17 +330,26 @@ impl Ownership {
             ref mut cur_index,
             ref mut max_index,
             ref mut objects,
+            ref mut protected,
+            ref mut high_water_mark,
+            ..
         } = *self;
 
         let mut entry = objects.entry(send_sexp);
         let preservation_sexp = preservation.inner();
         match entry {
             Entry::Occupied(ref mut occupied) => {
-                if occupied.get().refcount == 0 {
+                let object = occupied.get_mut();
+                if object.refcount == 0 {
                     // Address re-used - re-set the sexp.
-                    SET_VECTOR_ELT(preservation_sexp, occupied.get().index as R_xlen_t, sexp);
+                    SET_VECTOR_ELT(preservation_sexp, object.index as R_xlen_t, sexp);
+                    *protected += 1;
+                    #[cfg(feature = "debug-ownership")]
+                    {
+                        object.backtrace = Some(std::backtrace::Backtrace::force_capture());
+                    }
                 }
-                occupied.get_mut().refcount += 1;
+                object.refcount += 1;
             }
             Entry::Vacant(vacant) => {
                 let index = *cur_index;
@@ -175,9 +357,16 @@ impl Ownership {
                 *cur_index += 1;
                 assert!(index != *max_index);
                 let refcount = 1;
-                vacant.insert(Object { refcount, index });
+                *protected += 1;
+                vacant.insert(Object {
+                    refcount,
+                    index,
+                    #[cfg(feature = "debug-ownership")]
+                    backtrace: Some(std::backtrace::Backtrace::force_capture()),
+                });
             }
         }
+        *high_water_mark = (*high_water_mark).max(*protected);
 
         Rf_unprotect(1);
     }
@@ -186,9 +375,9 @@ impl Ownership {
         let send_sexp = sexp.into();
         let Ownership {
             preservation,
-            cur_index: _,
-            max_index: _,
             ref mut objects,
+            protected,
+            ..
         } = self;
 
         let mut entry = objects.entry(send_sexp);
@@ -205,6 +394,11 @@ impl Ownership {
                         // have a ref to objects anymore and it is faster to clear them up en-masse.
                         let preservation_sexp = preservation.inner();
                         SET_VECTOR_ELT(preservation_sexp, object.index as R_xlen_t, R_NilValue);
+                        *protected -= 1;
+                        #[cfg(feature = "debug-ownership")]
+                        {
+                            object.backtrace = None;
+                        }
                     }
                 }
             }
@@ -217,10 +411,7 @@ impl Ownership {
     #[allow(dead_code)]
     unsafe fn ref_count(&mut self, sexp: SEXP) -> usize {
         let Ownership {
-            preservation: _,
-            cur_index: _,
-            max_index: _,
-            ref mut objects,
+            ref mut objects, ..
         } = *self;
 
         let mut entry = objects.entry(sexp.into());
@@ -238,6 +429,10 @@ impl Ownership {
 
         // println!("\ncheck");
 
+        let protected = self.objects.values().filter(|o| o.refcount != 0).count();
+        assert_eq!(self.protected, protected);
+        assert!(self.high_water_mark >= protected);
+
         for (addr, object) in self.objects.iter() {
             assert!(object.index < self.max_index);
             let elt = VECTOR_ELT(preservation_sexp, object.index as R_xlen_t);
@@ -267,6 +462,176 @@ impl Ownership {
     }
 }
 
+const INITIAL_SCOPE_SIZE: usize = 64;
+
+/// Run `f` with a scope that protects R objects until `f` returns.
+///
+/// Every [`Robj`] is protected by a global table, which takes a lock and a
+/// hash table update whenever it is made or dropped. A [`Local`] is only
+/// stored in an arena of the scope, which is itself on R's protection stack,
+/// so it is cheap to make temporary objects in a loop. Locals cannot outlive
+/// the scope; use [`Local::escape`] to keep one as an `Robj`.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let total = protect_scope(|scope| {
+///         let mut total = 0.0;
+///         for i in 0..1000 {
+///             let x = scope.collect([i as f64, 1.0]);
+///             total += x.as_real_slice().unwrap().iter().sum::<f64>();
+///         }
+///         total
+///     });
+///     assert_eq!(total, 500500.0);
+///
+///     let kept = protect_scope(|scope| scope.collect(["a", "b"]).escape());
+///     assert_eq!(kept, r!(["a", "b"]));
+/// }
+/// ```
+pub fn protect_scope<F, R>(f: F) -> R
+where
+    F: FnOnce(&ProtectScope) -> R,
+{
+    single_threaded(|| {
+        let scope = unsafe { ProtectScope::new() };
+        f(&scope)
+    })
+}
+
+/// An arena of R objects protected by [`protect_scope`].
+///
+/// The objects are held by a list on R's protection stack. When the list is
+/// full, a list twice its size, which holds the old one, replaces it.
+pub struct ProtectScope {
+    arena: Cell<SEXP>,
+    len: Cell<usize>,
+    index: PROTECT_INDEX,
+}
+
+impl ProtectScope {
+    unsafe fn new() -> Self {
+        let arena = Rf_allocVector(SEXPTYPE::VECSXP, INITIAL_SCOPE_SIZE as R_xlen_t);
+        let mut index = 0;
+        R_ProtectWithIndex(arena, &mut index);
+        // The first element holds the previous arena.
+        ProtectScope {
+            arena: Cell::new(arena),
+            len: Cell::new(1),
+            index,
+        }
+    }
+
+    /// Protect `sexp` until the end of the scope.
+    ///
+    /// # Safety
+    ///
+    /// `sexp` must be a valid SEXP pointer.
+    pub unsafe fn protect(&self, sexp: SEXP) -> Local<'_> {
+        let mut arena = self.arena.get();
+        let len = self.len.get();
+        if len == LENGTH(arena) as usize {
+            Rf_protect(sexp);
+            let new_arena = Rf_allocVector(SEXPTYPE::VECSXP, (len * 2) as R_xlen_t);
+            SET_VECTOR_ELT(new_arena, 0, arena);
+            R_Reprotect(new_arena, self.index);
+            Rf_unprotect(1);
+            arena = new_arena;
+            self.arena.set(arena);
+            self.len.set(1);
+        }
+        let len = self.len.get();
+        SET_VECTOR_ELT(arena, len as R_xlen_t, sexp);
+        self.len.set(len + 1);
+        Local {
+            sexp,
+            scope: PhantomData,
+        }
+    }
+
+    /// Allocate a vector of `len` elements, as [`Rinternals::alloc_vector`](crate::Rinternals::alloc_vector).
+    pub fn alloc_vector(&self, sexptype: SEXPTYPE, len: usize) -> Local<'_> {
+        unsafe { self.protect(Rf_allocVector(sexptype, len as R_xlen_t)) }
+    }
+
+    /// Make a vector from values, as [`RobjItertools::collect_robj`](crate::RobjItertools::collect_robj).
+    pub fn collect<I>(&self, values: I) -> Local<'_>
+    where
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+        I::Item: ToVectorValue,
+    {
+        let values = values.into_iter();
+        let sexptype = I::Item::sexptype();
+        if sexptype == SEXPTYPE::NILSXP {
+            return unsafe { self.protect(R_NilValue) };
+        }
+        let local = self.alloc_vector(sexptype, values.len());
+        unsafe { fill_vector(local.sexp, values) };
+        if let Some(class) = I::Item::vector_class() {
+            // The class is scoped too, so neither goes through the global table.
+            let class = self.collect([class]);
+            unsafe { Rf_setAttrib(local.sexp, R_ClassSymbol, class.sexp) };
+        }
+        local
+    }
+}
+
+impl Drop for ProtectScope {
+    fn drop(&mut self) {
+        // Scopes are nested, so this is the top of the protection stack.
+        unsafe { Rf_unprotect(1) };
+    }
+}
+
+/// An R object protected by a [`ProtectScope`].
+///
+/// A `Local` is a copyable reference to the object, which can be used as
+/// a `&Robj`. Cloning the `Robj` or calling [`escape`](Local::escape)
+/// protects the object beyond the scope.
+#[derive(Clone, Copy)]
+pub struct Local<'scope> {
+    sexp: SEXP,
+    scope: PhantomData<&'scope ProtectScope>,
+}
+
+impl Local<'_> {
+    /// Get the object as an `Robj`, which is protected beyond the scope.
+    pub fn escape(&self) -> Robj {
+        unsafe { Robj::from_sexp(self.sexp) }
+    }
+
+    /// Get the underlying SEXP.
+    ///
+    /// # Safety
+    ///
+    /// Access to a raw SEXP pointer can cause undefined behaviour and is not thread safe.
+    pub unsafe fn get(&self) -> SEXP {
+        self.sexp
+    }
+}
+
+impl std::ops::Deref for Local<'_> {
+    type Target = Robj;
+
+    fn deref(&self) -> &Robj {
+        // `Robj` is a transparent wrapper of a SEXP, but this one is never dropped.
+        unsafe { &*(&self.sexp as *const SEXP as *const Robj) }
+    }
+}
+
+impl From<Local<'_>> for Robj {
+    fn from(local: Local<'_>) -> Self {
+        local.escape()
+    }
+}
+
+impl std::fmt::Debug for Local<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -377,4 +742,62 @@ mod test {
             });
         }
     }
+
+    #[test]
+    fn scope_test() {
+        test! {
+            let kept = protect_scope(|scope| {
+                // Enough objects to grow the arena twice.
+                let locals = (0..INITIAL_SCOPE_SIZE * 3)
+                    .map(|i| scope.collect([i as i32]))
+                    .collect::<Vec<_>>();
+                R!("gc()").unwrap();
+
+                for (i, local) in locals.iter().enumerate() {
+                    assert_eq!(local.as_integer(), Some(i as i32));
+                    let mut own = OWNERSHIP.lock().expect("lock failed");
+                    assert_eq!(unsafe { own.ref_count(local.get()) }, 0);
+                }
+                locals[1].escape()
+            });
+            assert_eq!(kept, r!(1));
+        }
+    }
+
+    #[test]
+    fn diagnostics_test() {
+        test! {
+            // Other tests may protect objects concurrently, so only look at ours.
+            let find = |objects: &[ProtectedObject], robj: SEXP| {
+                objects.iter().find(|o| o.address == robj as usize).cloned()
+            };
+
+            let before = protection_snapshot();
+            let kept = r!([1, 2]);
+            let shared = kept.clone();
+            let leaked = r!("leaked");
+            let leaked_sexp = unsafe { leaked.get() };
+            std::mem::forget(leaked);
+            let after = protection_snapshot();
+
+            let diff = before.diff(&after);
+            let kept_object = find(&diff.added, unsafe { kept.get() }).unwrap();
+            assert_eq!((kept_object.rtype, kept_object.refcount), (Rtype::Integers, 2));
+            let leaked_object = find(&diff.added, leaked_sexp).unwrap();
+            assert_eq!((leaked_object.rtype, leaked_object.refcount), (Rtype::Strings, 1));
+            #[cfg(feature = "debug-ownership")]
+            assert!(leaked_object.backtrace.is_some());
+
+            let kept_sexp = unsafe { kept.get() };
+            drop((kept, shared));
+            let diff = after.diff(&protection_snapshot());
+            assert!(find(&diff.added, kept_sexp).is_none());
+            assert_eq!(find(&diff.removed, kept_sexp).map(|o| o.refcount), Some(2));
+            assert!(find(&diff.removed, leaked_sexp).is_none());
+
+            let stats = protection_stats();
+            assert!(stats.high_water_mark >= stats.protected);
+            assert!(stats.capacity >= stats.protected);
+        }
+    }
 }
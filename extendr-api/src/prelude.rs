@@ -39,26 +39,35 @@ pub use super::wrapper::{
 #[cfg(use_r_altlist)]
 pub use super::wrapper::AltListImpl;
 
+pub use super::io::with_captured_output;
 pub use super::iter::StrIter;
+pub use super::ownership::{protect_scope, Local, ProtectScope};
 pub use super::robj::{
-    AsStrIter, Attributes, Eval, GetSexp, IntoRobj, Length, Operators, Rinternals, Robj,
-    RobjItertools, Slices, Types,
+    AsStrIter, Attributes, DuplicateNames, Eval, FromNested, GetSexp, IntoNested, IntoRobj, Length,
+    MapOptions, NamedMap, Nested, Operators, Rinternals, Robj, RobjItertools, Slices, Types,
 };
 pub use super::scalar::*;
 pub use super::thread_safety::{catch_r_error, single_threaded, throw_r_error};
 pub use super::wrapper::s4::S4;
 pub use super::wrapper::{
-    Complexes, Dataframe, Doubles, EnvIter, Environment, Expressions, ExternalPtr, FromList,
-    Function, Integers, IntoDataFrameRow, Language, List, ListIter, Logicals, Nullable, Pairlist,
-    Primitive, Promise, Raw, Rstr, Strings, Symbol,
+    register_type, registered_type, Conversions, MatrixConversions, NA_INTEGER64,
+};
+pub use super::wrapper::{
+    Comparisons, Complexes, Dataframe, DateTimes, Dates, Difftimes, Doubles, Encoding, EnvIter,
+    Environment, Expressions, ExternalPtr, FromList, Function, Integers, Integers64,
+    IntoDataFrameRow, Language, List, ListIter, Logicals, Nullable, Pairlist, Primitive, Promise,
+    Raw, Rstr, RstrCache, Shared, Strings, Subscript, Subset, Symbol, TimeUnits, TypeAbi, TypeTag,
+    WeakRef,
 };
-pub use super::wrapper::{Conversions, MatrixConversions};
 pub use super::Nullable::*;
 pub use extendr_macros::{
     call, extendr, extendr_module, pairlist, IntoDataFrameRow, IntoList, Rraw, TryFromList, R,
 };
 pub use std::convert::{TryFrom, TryInto};
 
+#[cfg(feature = "serde")]
+pub use super::serializer::Serde;
+
 #[cfg(feature = "ndarray")]
 pub use ::ndarray;
 
@@ -67,3 +76,6 @@ pub use ::either::*;
 
 #[cfg(feature = "faer")]
 pub use ::faer;
+
+#[cfg(feature = "nalgebra")]
+pub use ::nalgebra;
@@ -107,7 +107,7 @@ impl std::fmt::Debug for Robj {
                    BUILTINSXP => write!(f, "r!(Builtin())"),
                    CHARSXP => {
                        let c = Rstr::try_from(self.clone()).unwrap();
-                       write!(f, "r!(Rstr::from_string({:?}))", c.as_str())
+                       write!(f, "r!(Rstr::from_string({:?}))", c.to_string_lossy())
                    }
                    LGLSXP => {
                        let slice = self.as_logical_slice().unwrap();
@@ -219,6 +219,11 @@ pub trait ToVectorValue {
     {
         unsafe { R_NilValue }
     }
+
+    /// The class of vectors of this type, if any.
+    fn vector_class() -> Option<&'static str> {
+        None
+    }
 }
 
 macro_rules! impl_real_tvv {
@@ -264,11 +269,40 @@ impl_real_tvv!(f32);
 
 // Since these types might exceeds the max or min of R's 32bit integer, we need
 // to return as REALSXP
+#[cfg(not(feature = "integer64"))]
 impl_real_tvv!(i64);
 impl_real_tvv!(u32);
 impl_real_tvv!(u64);
 impl_real_tvv!(usize);
 
+// With the `integer64` feature, `i64` is returned losslessly as `bit64::integer64`.
+#[cfg(feature = "integer64")]
+macro_rules! impl_integer64_tvv {
+    ($t: ty, $to_i64: expr) => {
+        impl ToVectorValue for $t {
+            fn sexptype() -> SEXPTYPE {
+                SEXPTYPE::REALSXP
+            }
+
+            fn to_real(&self) -> f64 {
+                let value: Option<i64> = $to_i64(self);
+                f64::from_bits(value.unwrap_or(NA_INTEGER64) as u64)
+            }
+
+            fn vector_class() -> Option<&'static str> {
+                Some("integer64")
+            }
+        }
+    };
+}
+
+#[cfg(feature = "integer64")]
+impl_integer64_tvv!(i64, |x: &i64| Some(*x));
+#[cfg(feature = "integer64")]
+impl_integer64_tvv!(&i64, |x: &&i64| Some(**x));
+#[cfg(feature = "integer64")]
+impl_integer64_tvv!(Option<i64>, |x: &Option<i64>| *x);
+
 macro_rules! impl_complex_tvv {
     ($t: ty) => {
         impl ToVectorValue for $t {
@@ -593,47 +627,10 @@ where
         // Length of the vector is known in advance.
         let sexptype = I::Item::sexptype();
         if sexptype != SEXPTYPE::NILSXP {
-            let res = Robj::alloc_vector(sexptype, len);
-            let sexp = res.get();
-            match sexptype {
-                SEXPTYPE::REALSXP => {
-                    let ptr = REAL(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_real();
-                    }
-                }
-                SEXPTYPE::CPLXSXP => {
-                    let ptr = COMPLEX(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_complex();
-                    }
-                }
-                SEXPTYPE::INTSXP => {
-                    let ptr = INTEGER(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_integer();
-                    }
-                }
-                SEXPTYPE::LGLSXP => {
-                    let ptr = LOGICAL(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_logical();
-                    }
-                }
-                SEXPTYPE::STRSXP => {
-                    for (i, v) in iter.enumerate() {
-                        SET_STRING_ELT(sexp, i as isize, v.to_sexp());
-                    }
-                }
-                SEXPTYPE::RAWSXP => {
-                    let ptr = RAW(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_raw();
-                    }
-                }
-                _ => {
-                    panic!("unexpected SEXPTYPE in collect_robj");
-                }
+            let mut res = Robj::alloc_vector(sexptype, len);
+            fill_vector(res.get(), iter);
+            if let Some(class) = I::Item::vector_class() {
+                res.set_class([class]).unwrap();
             }
             res
         } else {
@@ -642,6 +639,54 @@ where
     })
 }
 
+// Fill a vector of type `I::Item::sexptype()` with the values of `iter`.
+pub(crate) unsafe fn fill_vector<I>(sexp: SEXP, iter: I)
+where
+    I: Iterator,
+    I::Item: ToVectorValue,
+{
+    match I::Item::sexptype() {
+        SEXPTYPE::REALSXP => {
+            let ptr = REAL(sexp);
+            for (i, v) in iter.enumerate() {
+                *ptr.add(i) = v.to_real();
+            }
+        }
+        SEXPTYPE::CPLXSXP => {
+            let ptr = COMPLEX(sexp);
+            for (i, v) in iter.enumerate() {
+                *ptr.add(i) = v.to_complex();
+            }
+        }
+        SEXPTYPE::INTSXP => {
+            let ptr = INTEGER(sexp);
+            for (i, v) in iter.enumerate() {
+                *ptr.add(i) = v.to_integer();
+            }
+        }
+        SEXPTYPE::LGLSXP => {
+            let ptr = LOGICAL(sexp);
+            for (i, v) in iter.enumerate() {
+                *ptr.add(i) = v.to_logical();
+            }
+        }
+        SEXPTYPE::STRSXP => {
+            for (i, v) in iter.enumerate() {
+                SET_STRING_ELT(sexp, i as isize, v.to_sexp());
+            }
+        }
+        SEXPTYPE::RAWSXP => {
+            let ptr = RAW(sexp);
+            for (i, v) in iter.enumerate() {
+                *ptr.add(i) = v.to_raw();
+            }
+        }
+        _ => {
+            panic!("unexpected SEXPTYPE in collect_robj");
+        }
+    }
+}
+
 /// Extensions to iterators for R objects including [RobjItertools::collect_robj()].
 pub trait RobjItertools: Iterator {
     /// Convert a wide range of iterators to Robj.
@@ -0,0 +1,375 @@
+//! Conversions between maps and named vectors and lists.
+//!
+//! `HashMap`, `BTreeMap` and, with the `indexmap` feature, `IndexMap` with `String`
+//! or `&str` keys can be made from named atomic vectors and named lists, with the
+//! names as keys. Maps of scalars become named atomic vectors, and maps of [`Robj`]
+//! become named lists. Use `List::try_from(map)` for maps of other values.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use std::collections::{BTreeMap, HashMap};
+//! test! {
+//!     let weights: HashMap<String, f64> = R!("c(a = 1, b = 2)")?.try_into()?;
+//!     assert_eq!(weights["b"], 2.0);
+//!
+//!     // BTreeMaps are sorted by name.
+//!     let counts = BTreeMap::from([("y", 2), ("x", 1)]);
+//!     assert_eq!(Robj::from(counts), R!("c(x = 1L, y = 2L)")?);
+//!
+//!     let options = MapOptions::new().duplicates(DuplicateNames::Error);
+//!     let map = HashMap::<String, f64>::from_robj_with(&R!("c(a = 1, a = 2)")?, options);
+//!     assert!(map.is_err());
+//! }
+//! ```
+use super::*;
+use std::collections::{BTreeMap, HashSet};
+
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+
+/// What to do with names that appear more than once, in [`MapOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateNames {
+    /// Keep the first element with the name, as R's `x[["a"]]` does.
+    First,
+    /// Keep the last element with the name.
+    #[default]
+    Last,
+    /// Return an error.
+    Error,
+}
+
+/// Options for converting named vectors and lists to maps, with [`NamedMap::from_robj_with`].
+///
+/// By default, an element replaces earlier elements with the same name,
+/// and `NA` names become the key `"NA"`. Elements without names have the name `""`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapOptions {
+    duplicates: DuplicateNames,
+    allow_na_names: bool,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            duplicates: DuplicateNames::default(),
+            allow_na_names: true,
+        }
+    }
+}
+
+impl MapOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set what to do with names that appear more than once.
+    pub fn duplicates(mut self, duplicates: DuplicateNames) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Allow `NA` names, which become the key `"NA"`, or make them an error.
+    pub fn allow_na_names(mut self, allow: bool) -> Self {
+        self.allow_na_names = allow;
+        self
+    }
+}
+
+/// Keys of maps made from names: `String` and `&str`.
+pub trait MapKey {
+    fn from_name(name: &'static str) -> Self;
+}
+
+impl MapKey for String {
+    fn from_name(name: &'static str) -> Self {
+        name.to_string()
+    }
+}
+
+impl MapKey for &str {
+    fn from_name(name: &'static str) -> Self {
+        name
+    }
+}
+
+/// Maps that can be made from named vectors and lists.
+pub trait NamedMap: Sized {
+    /// Make a map from a named vector or list. `TryFrom<Robj>` uses the default options.
+    fn from_robj_with(robj: &Robj, options: MapOptions) -> Result<Self>;
+}
+
+/// The names and elements of a vector or list, in order, without the duplicates that
+/// `options` leave out. The elements of atomic vectors are vectors of length one.
+fn named_elements(robj: &Robj, options: MapOptions) -> Result<Vec<(&'static str, Robj)>> {
+    let values = match robj.sexptype() {
+        SEXPTYPE::NILSXP => return Ok(Vec::new()),
+        SEXPTYPE::INTSXP if robj.is_factor() => nested::elements(&robj.as_character_factor())?,
+        SEXPTYPE::VECSXP
+        | SEXPTYPE::LGLSXP
+        | SEXPTYPE::INTSXP
+        | SEXPTYPE::REALSXP
+        | SEXPTYPE::CPLXSXP
+        | SEXPTYPE::STRSXP
+        | SEXPTYPE::RAWSXP => nested::elements(robj)?,
+        _ => return Err(Error::ExpectedVector(robj.clone())),
+    };
+    let names: Vec<&'static str> = match robj.get_attrib(wrapper::symbol::names_symbol()) {
+        Some(names) => names
+            .as_str_iter()
+            .ok_or_else(|| Error::ExpectedUtf8(names.clone()))?
+            .collect(),
+        None => vec![""; values.len()],
+    };
+
+    let mut seen = HashSet::new();
+    let mut elements = Vec::with_capacity(values.len());
+    for (i, (name, value)) in names.into_iter().zip(values).enumerate() {
+        if name.is_na() && !options.allow_na_names {
+            return Err(Error::Other(format!("element {} has an NA name", i + 1)));
+        }
+        if options.duplicates != DuplicateNames::Last && !seen.insert(name) {
+            match options.duplicates {
+                DuplicateNames::Error => {
+                    return Err(Error::Other(format!("duplicated name '{}'", name)))
+                }
+                _ => continue,
+            }
+        }
+        elements.push((name, value));
+    }
+    Ok(elements)
+}
+
+/// Make a named atomic vector.
+fn named_vector<K, V, I>(pairs: I) -> Robj
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: ToVectorValue + AsRef<str>,
+    V: ToVectorValue,
+{
+    let (names, values): (Vec<K>, Vec<V>) = pairs.into_iter().unzip();
+    let mut robj = Robj::from(values);
+    robj.set_names(names).unwrap();
+    robj
+}
+
+/// Make a named list.
+fn named_list<K, V, I>(pairs: I) -> Result<List>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: ToVectorValue + AsRef<str>,
+    V: IntoRobj,
+{
+    let (names, values): (Vec<K>, Vec<Robj>) =
+        pairs.into_iter().map(|(k, v)| (k, v.into_robj())).unzip();
+    List::from_names_and_values(names, values)
+}
+
+macro_rules! impl_map_conversions {
+    ($map: ident, $($key_bounds: tt)+) => {
+        impl<K, T> NamedMap for $map<K, T>
+        where
+            K: MapKey + $($key_bounds)+,
+            T: TryFrom<Robj, Error = Error>,
+        {
+            fn from_robj_with(robj: &Robj, options: MapOptions) -> Result<Self> {
+                named_elements(robj, options)?
+                    .into_iter()
+                    .map(|(name, value)| Ok((K::from_name(name), value.try_into()?)))
+                    .collect()
+            }
+        }
+
+        impl<K> NamedMap for $map<K, Robj>
+        where
+            K: MapKey + $($key_bounds)+,
+        {
+            fn from_robj_with(robj: &Robj, options: MapOptions) -> Result<Self> {
+                Ok(named_elements(robj, options)?
+                    .into_iter()
+                    .map(|(name, value)| (K::from_name(name), value))
+                    .collect())
+            }
+        }
+
+        impl<K, T> TryFrom<&Robj> for $map<K, T>
+        where
+            Self: NamedMap,
+        {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                Self::from_robj_with(robj, MapOptions::default())
+            }
+        }
+
+        impl<K, T> TryFrom<Robj> for $map<K, T>
+        where
+            Self: NamedMap,
+        {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        impl<K, T> TryFrom<&Robj> for Option<$map<K, T>>
+        where
+            $map<K, T>: NamedMap,
+        {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                if robj.is_null() || robj.is_na() {
+                    Ok(None)
+                } else {
+                    Ok(Some(robj.try_into()?))
+                }
+            }
+        }
+
+        impl<K, T> TryFrom<Robj> for Option<$map<K, T>>
+        where
+            $map<K, T>: NamedMap,
+        {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        /// Make a named atomic vector, with the order of the map.
+        impl<K, V> From<$map<K, V>> for Robj
+        where
+            K: ToVectorValue + AsRef<str>,
+            V: ToVectorValue,
+        {
+            fn from(map: $map<K, V>) -> Self {
+                named_vector(map)
+            }
+        }
+
+        /// Make a named list, with the order of the map.
+        impl<K> From<$map<K, Robj>> for Robj
+        where
+            K: ToVectorValue + AsRef<str>,
+        {
+            fn from(map: $map<K, Robj>) -> Self {
+                named_list(map).unwrap().into()
+            }
+        }
+    };
+}
+
+impl_map_conversions!(HashMap, Eq + std::hash::Hash);
+impl_map_conversions!(BTreeMap, Ord);
+#[cfg(feature = "indexmap")]
+impl_map_conversions!(IndexMap, Eq + std::hash::Hash);
+
+/// Make a named list, sorted by name.
+impl<K, V> TryFrom<BTreeMap<K, V>> for List
+where
+    K: ToVectorValue + AsRef<str>,
+    V: IntoRobj,
+{
+    type Error = Error;
+
+    fn try_from(map: BTreeMap<K, V>) -> Result<Self> {
+        named_list(map)
+    }
+}
+
+/// Make a named list, in the order of insertion.
+#[cfg(feature = "indexmap")]
+impl<K, V> TryFrom<IndexMap<K, V>> for List
+where
+    K: ToVectorValue + AsRef<str>,
+    V: IntoRobj,
+{
+    type Error = Error;
+
+    fn try_from(map: IndexMap<K, V>) -> Result<Self> {
+        named_list(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as extendr_api;
+    use crate::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn from_named_vectors() {
+        test! {
+            let map: HashMap<&str, i32> = R!("c(a = 1L, b = 2L)")?.try_into()?;
+            assert_eq!(map, HashMap::from([("a", 1), ("b", 2)]));
+
+            let map: BTreeMap<String, String> = R!("factor(c(b = 'x', a = 'y'))")?.try_into()?;
+            assert_eq!(map, BTreeMap::from([("a".into(), "y".into()), ("b".into(), "x".into())]));
+
+            let map: HashMap<String, Option<f64>> = R!("list(a = 1, b = NULL)")?.try_into()?;
+            assert_eq!(map["a"], Some(1.0));
+            assert_eq!(map["b"], None);
+
+            let map: HashMap<String, Robj> = R!("list(a = 1, b = 'x')")?.try_into()?;
+            assert_eq!(map["b"], r!("x"));
+
+            let map: Option<HashMap<String, f64>> = r!(NULL).try_into()?;
+            assert_eq!(map, None);
+            assert!(HashMap::<String, f64>::try_from(R!("c(a = 'x')")?).is_err());
+            assert!(HashMap::<String, f64>::try_from(R!("sum")?).is_err());
+
+            let map: HashMap<String, i32> = R!("1:2")?.try_into()?;
+            assert_eq!(map, HashMap::from([("".to_string(), 2)]));
+        }
+    }
+
+    #[test]
+    fn options() {
+        test! {
+            let robj = R!("c(a = 1, b = 2, a = 3)")?;
+            let last: BTreeMap<String, f64> = robj.clone().try_into()?;
+            assert_eq!(last["a"], 3.0);
+            let first = BTreeMap::<String, f64>::from_robj_with(&robj, MapOptions::new().duplicates(DuplicateNames::First))?;
+            assert_eq!(first["a"], 1.0);
+            assert!(BTreeMap::<String, f64>::from_robj_with(&robj, MapOptions::new().duplicates(DuplicateNames::Error)).is_err());
+
+            let robj = R!("setNames(1:2, c('a', NA))")?;
+            let map = HashMap::<String, i32>::try_from(&robj)?;
+            assert_eq!(map["NA"], 2);
+            assert!(HashMap::<String, i32>::from_robj_with(&robj, MapOptions::new().allow_na_names(false)).is_err());
+        }
+    }
+
+    #[test]
+    fn into_robj() {
+        test! {
+            let map = BTreeMap::from([("b", "x"), ("a", "y")]);
+            assert_eq!(Robj::from(map), R!("c(a = 'y', b = 'x')")?);
+
+            let map = BTreeMap::from([("b", r!(1)), ("a", r!("x"))]);
+            assert_eq!(Robj::from(map), R!("list(a = 'x', b = 1)")?);
+
+            let map = BTreeMap::from([("v", vec![1, 2])]);
+            assert_eq!(Robj::from(List::try_from(map)?), R!("list(v = 1:2)")?);
+
+            let map = HashMap::from([("a", 1.0)]);
+            assert_eq!(Robj::from(map), R!("c(a = 1)")?);
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map() {
+        use indexmap::IndexMap;
+        test! {
+            let map: IndexMap<String, i32> = R!("c(z = 1L, a = 2L)")?.try_into()?;
+            assert_eq!(map.keys().collect::<Vec<_>>(), ["z", "a"]);
+            assert_eq!(Robj::from(map), R!("c(z = 1L, a = 2L)")?);
+        }
+    }
+}
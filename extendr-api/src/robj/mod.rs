@@ -24,12 +24,16 @@ use crate::scalar::{Rbool, Rfloat, Rint};
 use crate::*;
 pub use into_robj::*;
 pub use iter::*;
+pub use maps::{DuplicateNames, MapKey, MapOptions, NamedMap};
+pub use nested::{FromNested, IntoNested, Nested};
 pub use operators::Operators;
 use prelude::{c64, Rcplx};
 pub use rinternals::Rinternals;
 
 mod debug;
 mod into_robj;
+mod maps;
+mod nested;
 mod operators;
 mod rinternals;
 mod try_from_robj;
@@ -596,6 +600,7 @@ impl Robj {
     }
 
     /// Get a read-only reference to a scalar string type.
+    /// Returns `None` for strings that are not valid UTF-8.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
@@ -606,20 +611,20 @@ impl Robj {
     /// }
     /// ```
     pub fn as_str<'a>(&self) -> Option<&'a str> {
+        let charsxp = self.scalar_charsxp()?;
+        unsafe { rstr::try_charsxp_to_str(charsxp).ok() }
+    }
+
+    // The `CHARSXP` of a scalar string, character or symbol.
+    pub(crate) fn scalar_charsxp(&self) -> Option<SEXP> {
         unsafe {
-            let charsxp = match self.sexptype() {
-                STRSXP => {
-                    // only allows scalar strings
-                    if self.len() != 1 {
-                        return None;
-                    }
-                    STRING_ELT(self.get(), 0)
-                }
-                CHARSXP => self.get(),
-                SYMSXP => PRINTNAME(self.get()),
-                _ => return None,
-            };
-            rstr::charsxp_to_str(charsxp)
+            match self.sexptype() {
+                // only allows scalar strings
+                STRSXP if self.len() == 1 => Some(STRING_ELT(self.get(), 0)),
+                CHARSXP => Some(self.get()),
+                SYMSXP => Some(PRINTNAME(self.get())),
+                _ => None,
+            }
         }
     }
 
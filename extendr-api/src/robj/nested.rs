@@ -0,0 +1,629 @@
+//! Conversions of values nested in lists, such as `Vec<Vec<f64>>`, `Vec<Option<String>>`
+//! and `Vec<(String, i32)>`.
+//!
+//! [`FromNested`] and [`IntoNested`] are implemented for scalars and [`Robj`], and lift
+//! to `Vec<T>` and `[T; N]` from lists or atomic vectors, to `Option<T>` from `NULL` or
+//! `NA`, and to tuples from lists. Vectors of scalars become atomic vectors, and other
+//! containers become lists. Errors in elements carry their position, such as
+//! `[[3]][[2]]: Expected Numeric, got Strings`.
+//!
+//! The common shapes convert with `TryFrom` and `From`. Use the [`Nested`] wrapper
+//! for the others, such as `Option<Vec<Vec<f64>>>`.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let rows: Vec<Vec<f64>> = R!("list(c(1, 2), 3)")?.try_into()?;
+//!     assert_eq!(rows, [vec![1.0, 2.0], vec![3.0]]);
+//!     assert_eq!(Robj::from(rows), R!("list(c(1, 2), 3)")?);
+//!
+//!     let pairs: Vec<(String, i32)> = R!("list(list('a', 1L), list('b', 2L))")?.try_into()?;
+//!     assert_eq!(pairs, [("a".to_string(), 1), ("b".to_string(), 2)]);
+//!
+//!     let err = Vec::<Vec<f64>>::try_from(R!("list(1, c(2, 3), list(4, 'x'))")?).unwrap_err();
+//!     assert_eq!(err.to_string(), "[[3]][[2]]: Expected Numeric, got Strings");
+//!
+//!     let Nested(grid) = Nested::<Option<Vec<Vec<i32>>>>::try_from(r!(NULL))?;
+//!     assert_eq!(grid, None);
+//! }
+//! ```
+use super::*;
+use crate::conversions::try_into_int::FloatToInt;
+
+/// Conversion from R of values that can be nested in lists.
+pub trait FromNested: Sized {
+    fn from_nested(robj: &Robj) -> Result<Self>;
+
+    /// Convert a vector: the elements of a list or an atomic vector.
+    fn vec_from_nested(robj: &Robj) -> Result<Vec<Self>> {
+        from_elements(robj)
+    }
+
+    /// Convert a vector of optional values, with `None` for `NULL` and `NA`.
+    fn options_from_nested(robj: &Robj) -> Result<Vec<Option<Self>>> {
+        from_elements(robj)
+    }
+}
+
+/// Conversion to R of values that can be nested in lists.
+pub trait IntoNested: Sized {
+    fn into_nested(self) -> Robj;
+
+    /// The R value of `None`: `NA` for scalars and `NULL` otherwise.
+    fn missing() -> Robj {
+        ().into()
+    }
+
+    /// Make a vector: an atomic vector for scalars and a list otherwise.
+    fn vec_into_nested(values: Vec<Self>) -> Robj {
+        List::from_values(values.into_iter().map(IntoNested::into_nested)).into()
+    }
+
+    /// Make a vector of optional values, with [`missing`](IntoNested::missing) for `None`.
+    fn options_into_nested(values: Vec<Option<Self>>) -> Robj {
+        let values = values
+            .into_iter()
+            .map(|value| value.map_or_else(Self::missing, IntoNested::into_nested));
+        List::from_values(values).into()
+    }
+}
+
+/// The elements of a list, or of an atomic vector as vectors of length one.
+pub(crate) fn elements(robj: &Robj) -> Result<Vec<Robj>> {
+    fn scalars<T: Copy + Into<Robj>>(values: &[T]) -> Vec<Robj> {
+        values.iter().map(|&value| value.into()).collect()
+    }
+
+    let elements = match robj.sexptype() {
+        SEXPTYPE::NILSXP | SEXPTYPE::VECSXP => return Ok(ListIter::try_from(robj)?.collect()),
+        SEXPTYPE::LGLSXP => robj.as_logical_slice().map(scalars),
+        SEXPTYPE::INTSXP => robj.as_integer_slice().map(scalars),
+        SEXPTYPE::REALSXP => robj.as_real_slice().map(scalars),
+        SEXPTYPE::CPLXSXP => AsTypedSlice::<Rcplx>::as_typed_slice(robj).map(scalars),
+        SEXPTYPE::STRSXP => robj
+            .as_str_iter()
+            .map(|values| values.map(Robj::from).collect()),
+        SEXPTYPE::RAWSXP => robj.as_raw_slice().map(scalars),
+        _ => None,
+    };
+    elements.ok_or_else(|| Error::ExpectedList(robj.clone()))
+}
+
+// The path segment of the element at `index`, e.g. `[[3]]`.
+fn index_segment(index: usize) -> String {
+    format!("[[{}]]", index + 1)
+}
+
+fn from_elements<T: FromNested>(robj: &Robj) -> Result<Vec<T>> {
+    elements(robj)?
+        .iter()
+        .enumerate()
+        .map(|(i, elt)| T::from_nested(elt).map_err(|err| err.at_path(&index_segment(i))))
+        .collect()
+}
+
+/// Scalars that are read from atomic vectors without an `Robj` for each element.
+trait Leaf: Sized {
+    /// The elements of an atomic vector, with `None` for `NA`. Returns `None` for
+    /// vectors that are converted element by element, such as lists.
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>>;
+}
+
+// The elements of an atomic vector of type `T`, converted by `f`.
+fn leaf_values<T, U, F>(robj: &Robj, f: F) -> Option<Result<Vec<Option<U>>>>
+where
+    T: Copy + CanBeNA,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+    F: Fn(T) -> Result<U>,
+{
+    let values: &[T] = robj.as_typed_slice()?;
+    let values = values.iter().enumerate().map(|(i, &value)| {
+        if value.is_na() {
+            Ok(None)
+        } else {
+            f(value)
+                .map(Some)
+                .map_err(|err| err.at_path(&index_segment(i)))
+        }
+    });
+    Some(values.collect())
+}
+
+// Whole doubles, as integers. Doubles holding `integer64` are converted one by one.
+fn whole_numbers<T>(robj: &Robj) -> Option<Result<Vec<Option<T>>>>
+where
+    f64: FloatToInt<T>,
+{
+    match robj.sexptype() {
+        SEXPTYPE::REALSXP if !robj.is_integer64() => leaf_values(robj, |value: f64| {
+            value
+                .try_into_int()
+                .map_err(|err| Error::ExpectedWholeNumber(value.into(), err))
+        }),
+        _ => None,
+    }
+}
+
+impl Leaf for bool {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        leaf_values(robj, |value: Rbool| Ok(value.is_true()))
+    }
+}
+
+impl Leaf for i32 {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        match robj.sexptype() {
+            SEXPTYPE::INTSXP => leaf_values(robj, |value: i32| Ok(value)),
+            _ => whole_numbers(robj),
+        }
+    }
+}
+
+impl Leaf for i64 {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        match robj.sexptype() {
+            SEXPTYPE::INTSXP => leaf_values(robj, |value: i32| Ok(value.into())),
+            _ => whole_numbers(robj),
+        }
+    }
+}
+
+impl Leaf for f64 {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        match robj.sexptype() {
+            SEXPTYPE::INTSXP => leaf_values(robj, |value: i32| Ok(value.into())),
+            SEXPTYPE::REALSXP if !robj.is_integer64() => leaf_values(robj, |value: f64| Ok(value)),
+            _ => None,
+        }
+    }
+}
+
+impl Leaf for String {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        if robj.sexptype() != SEXPTYPE::STRSXP {
+            return None;
+        }
+        let values = robj.as_str_iter()?;
+        Some(Ok(values
+            .map(|value| (!value.is_na()).then(|| value.to_string()))
+            .collect()))
+    }
+}
+
+impl Leaf for Rint {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        let values = i32::leaf_options(robj)?;
+        Some(values.map(|values| values.into_iter().map(|v| v.map(Rint::from)).collect()))
+    }
+}
+
+impl Leaf for Rfloat {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        let values = f64::leaf_options(robj)?;
+        Some(values.map(|values| values.into_iter().map(|v| v.map(Rfloat::from)).collect()))
+    }
+}
+
+impl Leaf for Rbool {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        leaf_values(robj, |value: Rbool| Ok(value))
+    }
+}
+
+impl Leaf for Rstr {
+    fn leaf_options(robj: &Robj) -> Option<Result<Vec<Option<Self>>>> {
+        let values = Strings::try_from(robj).ok()?;
+        Some(Ok(values
+            .iter()
+            .map(|value| (!value.is_na()).then(|| value.clone()))
+            .collect()))
+    }
+}
+
+// Scalars whose `Option`s are vector values, and become vectors with `NA` for `None`.
+macro_rules! impl_nested_scalar {
+    ($($type: ty),*) => {
+        $(
+            impl FromNested for $type {
+                fn from_nested(robj: &Robj) -> Result<Self> {
+                    robj.try_into()
+                }
+
+                fn vec_from_nested(robj: &Robj) -> Result<Vec<Self>> {
+                    let Some(values) = Self::leaf_options(robj) else {
+                        return from_elements(robj);
+                    };
+                    values?
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, value)| {
+                            value.ok_or_else(|| {
+                                Error::MustNotBeNA(robj.clone()).at_path(&index_segment(i))
+                            })
+                        })
+                        .collect()
+                }
+
+                fn options_from_nested(robj: &Robj) -> Result<Vec<Option<Self>>> {
+                    Self::leaf_options(robj).unwrap_or_else(|| from_elements(robj))
+                }
+            }
+
+            impl IntoNested for $type {
+                fn into_nested(self) -> Robj {
+                    self.into()
+                }
+
+                fn missing() -> Robj {
+                    None::<$type>.into()
+                }
+
+                fn vec_into_nested(values: Vec<Self>) -> Robj {
+                    values.into()
+                }
+
+                fn options_into_nested(values: Vec<Option<Self>>) -> Robj {
+                    values.into()
+                }
+            }
+        )*
+    };
+}
+
+impl_nested_scalar!(bool, i32, i64, f64, String);
+
+// Scalars with their own `NA`.
+macro_rules! impl_nested_na_scalar {
+    ($($type: ty),*) => {
+        $(
+            impl FromNested for $type {
+                fn from_nested(robj: &Robj) -> Result<Self> {
+                    robj.try_into()
+                }
+
+                fn vec_from_nested(robj: &Robj) -> Result<Vec<Self>> {
+                    let Some(values) = Self::leaf_options(robj) else {
+                        return from_elements(robj);
+                    };
+                    Ok(values?
+                        .into_iter()
+                        .map(|value| value.unwrap_or_else(<$type>::na))
+                        .collect())
+                }
+
+                fn options_from_nested(robj: &Robj) -> Result<Vec<Option<Self>>> {
+                    Self::leaf_options(robj).unwrap_or_else(|| from_elements(robj))
+                }
+            }
+
+            impl IntoNested for $type {
+                fn into_nested(self) -> Robj {
+                    self.into()
+                }
+
+                fn missing() -> Robj {
+                    <$type>::na().into()
+                }
+
+                fn vec_into_nested(values: Vec<Self>) -> Robj {
+                    values.into()
+                }
+
+                fn options_into_nested(values: Vec<Option<Self>>) -> Robj {
+                    values
+                        .into_iter()
+                        .map(|value| value.unwrap_or_else(<$type>::na))
+                        .collect_robj()
+                }
+            }
+        )*
+    };
+}
+
+impl_nested_na_scalar!(Rint, Rfloat, Rbool, Rstr);
+
+impl FromNested for Robj {
+    fn from_nested(robj: &Robj) -> Result<Self> {
+        Ok(robj.clone())
+    }
+}
+
+impl IntoNested for Robj {
+    fn into_nested(self) -> Robj {
+        self
+    }
+}
+
+impl<T: FromNested> FromNested for Vec<T> {
+    fn from_nested(robj: &Robj) -> Result<Self> {
+        T::vec_from_nested(robj)
+    }
+}
+
+impl<T: IntoNested> IntoNested for Vec<T> {
+    fn into_nested(self) -> Robj {
+        T::vec_into_nested(self)
+    }
+}
+
+impl<T: FromNested, const N: usize> FromNested for [T; N] {
+    fn from_nested(robj: &Robj) -> Result<Self> {
+        let values = T::vec_from_nested(robj)?;
+        values.try_into().map_err(|_| Error::ExpectedLength(N))
+    }
+}
+
+impl<T: IntoNested, const N: usize> IntoNested for [T; N] {
+    fn into_nested(self) -> Robj {
+        T::vec_into_nested(self.into())
+    }
+}
+
+impl<T: FromNested> FromNested for Option<T> {
+    fn from_nested(robj: &Robj) -> Result<Self> {
+        if robj.is_null() || robj.is_na() {
+            Ok(None)
+        } else {
+            T::from_nested(robj).map(Some)
+        }
+    }
+
+    fn vec_from_nested(robj: &Robj) -> Result<Vec<Self>> {
+        T::options_from_nested(robj)
+    }
+}
+
+impl<T: IntoNested> IntoNested for Option<T> {
+    fn into_nested(self) -> Robj {
+        match self {
+            Some(value) => value.into_nested(),
+            None => T::missing(),
+        }
+    }
+
+    fn vec_into_nested(values: Vec<Self>) -> Robj {
+        T::options_into_nested(values)
+    }
+}
+
+// Tuples are lists of their elements.
+macro_rules! impl_nested_tuple {
+    ($len: literal; $($index: tt $type: ident),+) => {
+        impl<$($type: FromNested),+> FromNested for ($($type,)+) {
+            fn from_nested(robj: &Robj) -> Result<Self> {
+                let values = elements(robj)?;
+                if values.len() != $len {
+                    return Err(Error::ExpectedLength($len));
+                }
+                Ok(($(
+                    $type::from_nested(&values[$index]).map_err(|err| err.at_path(&index_segment($index)))?,
+                )+))
+            }
+        }
+
+        impl<$($type: IntoNested),+> IntoNested for ($($type,)+) {
+            fn into_nested(self) -> Robj {
+                List::from_values([$(self.$index.into_nested()),+]).into()
+            }
+        }
+
+        impl<$($type: FromNested),+> TryFrom<&Robj> for Vec<($($type,)+)> {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                from_elements(robj)
+            }
+        }
+
+        impl<$($type: FromNested),+> TryFrom<Robj> for Vec<($($type,)+)> {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+    };
+}
+
+impl_nested_tuple!(1; 0 T0);
+impl_nested_tuple!(2; 0 T0, 1 T1);
+impl_nested_tuple!(3; 0 T0, 1 T1, 2 T2);
+impl_nested_tuple!(4; 0 T0, 1 T1, 2 T2, 3 T3);
+impl_nested_tuple!(5; 0 T0, 1 T1, 2 T2, 3 T3, 4 T4);
+impl_nested_tuple!(6; 0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5);
+impl_nested_tuple!(7; 0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6);
+impl_nested_tuple!(8; 0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7);
+
+impl<T: FromNested> TryFrom<&Robj> for Vec<Vec<T>> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        from_elements(robj)
+    }
+}
+
+impl<T: FromNested> TryFrom<Robj> for Vec<Vec<T>> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<T: IntoNested> From<Vec<Vec<T>>> for Robj {
+    fn from(values: Vec<Vec<T>>) -> Self {
+        values.into_nested()
+    }
+}
+
+impl<T: FromNested, const N: usize> TryFrom<&Robj> for Vec<[T; N]> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        from_elements(robj)
+    }
+}
+
+impl<T: FromNested, const N: usize> TryFrom<Robj> for Vec<[T; N]> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<T: IntoNested, const N: usize> From<Vec<[T; N]>> for Robj {
+    fn from(values: Vec<[T; N]>) -> Self {
+        values.into_nested()
+    }
+}
+
+// `Vec<Option<i64>>` is converted in `try_from_robj.rs`.
+macro_rules! impl_try_from_vec_of_options {
+    ($($type: ty),*) => {
+        $(
+            impl TryFrom<&Robj> for Vec<Option<$type>> {
+                type Error = Error;
+
+                fn try_from(robj: &Robj) -> Result<Self> {
+                    <$type>::options_from_nested(robj)
+                }
+            }
+
+            impl TryFrom<Robj> for Vec<Option<$type>> {
+                type Error = Error;
+
+                fn try_from(robj: Robj) -> Result<Self> {
+                    Self::try_from(&robj)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_vec_of_options!(bool, i32, f64, String, Robj);
+
+/// Wrapper for values of any shape that [`FromNested`] and [`IntoNested`] convert,
+/// such as `Option<Vec<Vec<f64>>>`, as arguments and results of `#[extendr]` functions.
+///
+/// ```
+/// use extendr_api::prelude::*;
+///
+/// #[extendr]
+/// fn transpose(rows: Nested<Vec<Vec<Option<f64>>>>) -> Nested<Vec<Vec<Option<f64>>>> {
+///     let rows = rows.into_inner();
+///     let ncol = rows.iter().map(Vec::len).max().unwrap_or(0);
+///     let columns = (0..ncol)
+///         .map(|j| rows.iter().map(|row| row.get(j).copied().flatten()).collect())
+///         .collect();
+///     Nested(columns)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nested<T>(pub T);
+
+impl<T> Nested<T> {
+    /// Get the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Nested<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Nested<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: FromNested> TryFrom<&Robj> for Nested<T> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        T::from_nested(robj).map(Nested)
+    }
+}
+
+impl<T: FromNested> TryFrom<Robj> for Nested<T> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<T: IntoNested> From<Nested<T>> for Robj {
+    fn from(value: Nested<T>) -> Self {
+        value.0.into_nested()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as extendr_api;
+    use crate::prelude::*;
+
+    #[test]
+    fn from_lists() {
+        test! {
+            let rows: Vec<Vec<i32>> = R!("list(1:2, integer(0), 3L)")?.try_into()?;
+            assert_eq!(rows, [vec![1, 2], vec![], vec![3]]);
+
+            let names: Vec<Option<String>> = R!("list('a', NULL, NA)")?.try_into()?;
+            assert_eq!(names, [Some("a".to_string()), None, None]);
+            let names: Vec<Option<String>> = R!("c('a', NA)")?.try_into()?;
+            assert_eq!(names, [Some("a".to_string()), None]);
+            let flags: Vec<Option<bool>> = R!("c(TRUE, NA)")?.try_into()?;
+            assert_eq!(flags, [Some(true), None]);
+            let counts: Vec<Option<i32>> = R!("c(1L, NA)")?.try_into()?;
+            assert_eq!(counts, [Some(1), None]);
+
+            let points: Vec<[f64; 2]> = R!("list(c(0, 1), list(2, 3))")?.try_into()?;
+            assert_eq!(points, [[0.0, 1.0], [2.0, 3.0]]);
+            assert!(Vec::<[f64; 2]>::try_from(R!("list(c(0, 1, 2))")?).is_err());
+
+            let Nested(nested) = Nested::<Vec<Option<Vec<bool>>>>::try_from(R!("list(TRUE, NULL)")?)?;
+            assert_eq!(nested, [Some(vec![true]), None]);
+        }
+    }
+
+    #[test]
+    fn error_paths() {
+        test! {
+            let err = Vec::<(String, i32)>::try_from(R!("list(list('a', 1L), list('b', 'c'))")?).unwrap_err();
+            assert_eq!(err, Error::AtPath("[[2]][[2]]".into(), Box::new(i32::try_from(r!("c")).unwrap_err())));
+
+            let err = Vec::<(String, i32)>::try_from(R!("list(list('a'))")?).unwrap_err();
+            assert_eq!(err, Error::AtPath("[[1]]".into(), Box::new(Error::ExpectedLength(2))));
+
+            // Atomic vectors are read without an `Robj` for each element.
+            let rows: Vec<Vec<f64>> = R!("list(1:2, c(3, NaN))")?.try_into()?;
+            assert_eq!(rows[0], [1.0, 2.0]);
+            assert!(rows[1][1].is_nan());
+            let err = Vec::<Vec<i32>>::try_from(R!("list(1, c(2, 2.5))")?).unwrap_err();
+            assert!(err.to_string().starts_with("[[2]][[2]]: "));
+            let err = Vec::<Vec<bool>>::try_from(R!("list(c(TRUE, NA))")?).unwrap_err();
+            assert!(err.to_string().starts_with("[[1]][[2]]: "));
+        }
+    }
+
+    #[test]
+    fn into_robj() {
+        test! {
+            assert_eq!(Robj::from(vec![vec![1, 2], vec![]]), R!("list(1:2, integer(0))")?);
+            assert_eq!(Robj::from(vec![[Rstr::from("a"), Rstr::na()]]), R!("list(c('a', NA))")?);
+            assert_eq!(Robj::from(Nested(vec![("a", 1.0)].into_iter().map(|(k, v)| (k.to_string(), Some(v))).collect::<Vec<_>>())), R!("list(list('a', 1))")?);
+            assert_eq!(Robj::from(Nested(vec![Some(vec![1.0]), None])), R!("list(1, NULL)")?);
+            assert_eq!(Robj::from(Nested(vec![Some(1), None])), R!("c(1L, NA)")?);
+            assert_eq!(Robj::from(Nested(None::<Rfloat>)), R!("NA_real_")?);
+            assert_eq!(Robj::from(Nested(None::<Vec<f64>>)), r!(NULL));
+        }
+    }
+}
@@ -75,6 +75,11 @@ pub trait Rinternals: Types + Conversions {
         self.rtype() == Rtype::ExternalPtr
     }
 
+    /// Return true if this is a weak reference.
+    fn is_weak_ref(&self) -> bool {
+        self.rtype() == Rtype::WeakRef
+    }
+
     /// Get the source ref.
     fn get_current_srcref(val: i32) -> Robj {
         unsafe { Robj::from_sexp(R_GetCurrentSrcref(val as std::ffi::c_int)) }
@@ -320,6 +325,11 @@ pub trait Rinternals: Types + Conversions {
         unsafe { Rf_isInteger(self.get()).into() }
     }
 
+    /// Return true if this is a `bit64::integer64` vector, i.e. doubles (REALSXP) with class `integer64`.
+    fn is_integer64(&self) -> bool {
+        self.is_real() && self.as_robj().inherits("integer64")
+    }
+
     /// Return true if this is a language object (LANGSXP).
     fn is_language(&self) -> bool {
         unsafe { Rf_isLanguage(self.get()).into() }
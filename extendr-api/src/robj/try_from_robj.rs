@@ -24,6 +24,14 @@ macro_rules! impl_try_from_scalar_integer {
                     _ => return Err(Error::ExpectedScalar(robj.clone())),
                 };
 
+                // `bit64::integer64` stores the bits of an `i64` in a double.
+                if robj.is_integer64() {
+                    return match Integers64::try_from(robj)?.elt(0) {
+                        Some(v) => Self::try_from(v).map_err(|_| Error::OutOfLimits(robj.clone())),
+                        None => Err(Error::MustNotBeNA(robj.clone())),
+                    };
+                }
+
                 // Check if the value is not a missing value
                 if robj.is_na() {
                     return Err(Error::MustNotBeNA(robj.clone()));
@@ -66,6 +74,13 @@ macro_rules! impl_try_from_scalar_real {
                     _ => return Err(Error::ExpectedScalar(robj.clone())),
                 };
 
+                if robj.is_integer64() {
+                    return match Integers64::try_from(robj)?.elt(0) {
+                        Some(v) => Ok(v as Self),
+                        None => Err(Error::MustNotBeNA(robj.clone())),
+                    };
+                }
+
                 // Check if the value is not a missing value
                 if robj.is_na() {
                     return Err(Error::MustNotBeNA(robj.clone()));
@@ -238,13 +253,10 @@ impl TryFrom<&Robj> for &str {
         }
         match robj.len() {
             0 => Err(Error::ExpectedNonZeroLength(robj.clone())),
-            1 => {
-                if let Some(s) = robj.as_str() {
-                    Ok(s)
-                } else {
-                    Err(Error::ExpectedString(robj.clone()))
-                }
-            }
+            1 => match robj.scalar_charsxp() {
+                Some(charsxp) => unsafe { rstr::try_charsxp_to_str(charsxp) },
+                None => Err(Error::ExpectedString(robj.clone())),
+            },
             _ => Err(Error::ExpectedScalar(robj.clone())),
         }
     }
@@ -291,10 +303,22 @@ impl TryFrom<&Robj> for Vec<String> {
     type Error = Error;
 
     /// Convert a STRSXP object into a vector of `String`s.
+    /// Strings in other encodings are translated to UTF-8.
     /// Note: Unless you plan to store the result, use a slice instead.
     fn try_from(robj: &Robj) -> Result<Self> {
-        if let Some(iter) = robj.as_str_iter() {
-            // check for NA's in the string vector
+        if let Ok(strings) = Strings::try_from(robj) {
+            strings
+                .iter()
+                .map(|s| {
+                    if s.is_na() {
+                        Err(Error::MustNotBeNA(robj.clone()))
+                    } else {
+                        Ok(s.to_utf8()?.into_owned())
+                    }
+                })
+                .collect()
+        } else if let Some(iter) = robj.as_str_iter() {
+            // factors and scalar CHARSXPs
             if iter.clone().any(|s| s.is_na()) {
                 Err(Error::MustNotBeNA(robj.clone()))
             } else {
@@ -342,6 +366,52 @@ impl TryFrom<&Robj> for Rcplx {
     }
 }
 
+impl TryFrom<&Robj> for Vec<Option<i64>> {
+    type Error = Error;
+
+    /// Convert an `integer64`, integer or double vector to 64-bit integers,
+    /// with `None` for `NA`. Doubles must be whole numbers.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.is_integer64() {
+            return Ok(Integers64::try_from(robj)?.iter().collect());
+        }
+        if let Some(v) = robj.as_integer_slice() {
+            return Ok(v
+                .iter()
+                .map(|&x| if x.is_na() { None } else { Some(i64::from(x)) })
+                .collect());
+        }
+        if let Some(v) = robj.as_real_slice() {
+            return v
+                .iter()
+                .map(|x| {
+                    if x.is_na() || x.is_nan() {
+                        Ok(None)
+                    } else {
+                        x.try_into_int()
+                            .map(Some)
+                            .map_err(|conv_err| Error::ExpectedWholeNumber(robj.clone(), conv_err))
+                    }
+                })
+                .collect();
+        }
+        Err(Error::ExpectedNumeric(robj.clone()))
+    }
+}
+
+impl TryFrom<&Robj> for Vec<i64> {
+    type Error = Error;
+
+    /// Convert an `integer64`, integer or double vector to 64-bit integers.
+    /// `NA` is an error.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        Vec::<Option<i64>>::try_from(robj)?
+            .into_iter()
+            .map(|x| x.ok_or_else(|| Error::MustNotBeNA(robj.clone())))
+            .collect()
+    }
+}
+
 // Convert TryFrom<&Robj> into TryFrom<Robj>. Sadly, we are unable to make a blanket
 // conversion using GetSexp with the current version of Rust.
 macro_rules! impl_try_from_robj {
@@ -406,6 +476,8 @@ impl_try_from_robj!(Vec::<Rcplx>);
 impl_try_from_robj!(Vec::<u8>);
 impl_try_from_robj!(Vec::<i32>);
 impl_try_from_robj!(Vec::<f64>);
+impl_try_from_robj!(Vec::<i64>);
+impl_try_from_robj!(Vec::<Option<i64>>);
 
 impl_try_from_robj!(String);
 
@@ -427,12 +499,6 @@ impl TryFrom<Robj> for Option<Environment> {
     }
 }
 
-impl_try_from_robj!(@generics<T> HashMap::<&str, T> where T: TryFrom<Robj, Error = error::Error>);
-impl_try_from_robj!(@generics<T> HashMap::<String,T> where T: TryFrom<Robj, Error = error::Error>);
-
-impl_try_from_robj!(HashMap::<&str, Robj>);
-impl_try_from_robj!(HashMap::<String, Robj>);
-
 impl TryFrom<&Robj> for Option<()> {
     type Error = Error;
 
@@ -452,35 +518,6 @@ impl TryFrom<Robj> for Option<()> {
     }
 }
 
-impl<T> TryFrom<&Robj> for HashMap<&str, T>
-where
-    T: TryFrom<Robj, Error = error::Error>,
-{
-    type Error = Error;
-
-    fn try_from(value: &Robj) -> Result<Self> {
-        let value: List = value.try_into()?;
-
-        let value = value
-            .iter()
-            .map(|(name, value)| -> Result<(&str, T)> { value.try_into().map(|x| (name, x)) })
-            .collect::<Result<HashMap<_, _>>>()?;
-
-        Ok(value)
-    }
-}
-
-impl<T> TryFrom<&Robj> for HashMap<String, T>
-where
-    T: TryFrom<Robj, Error = error::Error>,
-{
-    type Error = Error;
-    fn try_from(value: &Robj) -> Result<Self> {
-        let value: HashMap<&str, _> = value.try_into()?;
-        Ok(value.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
-    }
-}
-
 macro_rules! impl_try_from_robj_for_arrays {
     ($slice_type:ty) => {
         impl<const N: usize> TryFrom<&Robj> for [$slice_type; N] {
@@ -596,22 +633,3 @@ where
 }
 
 impl_try_from_robj_tuples!((2, 12));
-
-// The following is necessary because it is impossible to define `TryFrom<Robj> for &Robj` as
-// it requires returning a reference to a owned (moved) value
-impl TryFrom<&Robj> for HashMap<&str, Robj> {
-    type Error = Error;
-
-    fn try_from(value: &Robj) -> Result<Self> {
-        let value: List = value.try_into()?;
-        Ok(value.into_iter().collect())
-    }
-}
-
-impl TryFrom<&Robj> for HashMap<String, Robj> {
-    type Error = Error;
-    fn try_from(value: &Robj) -> Result<Self> {
-        let value: HashMap<&str, _> = value.try_into()?;
-        Ok(value.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
-    }
-}
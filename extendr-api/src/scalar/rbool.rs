@@ -104,6 +104,50 @@ impl std::ops::Not for Rbool {
     }
 }
 
+impl std::ops::BitAnd for Rbool {
+    type Output = Self;
+
+    /// Logical and, as R's `&`: `FALSE & NA` is `FALSE`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert!((TRUE & NA_LOGICAL).is_na());
+    ///     assert_eq!(FALSE & NA_LOGICAL, FALSE);
+    /// }
+    /// ```
+    fn bitand(self, rhs: Self) -> Self::Output {
+        if self.is_false() || rhs.is_false() {
+            Rbool::false_value()
+        } else if self.is_na() || rhs.is_na() {
+            Rbool::na()
+        } else {
+            Rbool::true_value()
+        }
+    }
+}
+
+impl std::ops::BitOr for Rbool {
+    type Output = Self;
+
+    /// Logical or, as R's `|`: `TRUE | NA` is `TRUE`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(TRUE | NA_LOGICAL, TRUE);
+    ///     assert!((FALSE | NA_LOGICAL).is_na());
+    /// }
+    /// ```
+    fn bitor(self, rhs: Self) -> Self::Output {
+        if self.is_true() || rhs.is_true() {
+            Rbool::true_value()
+        } else if self.is_na() || rhs.is_na() {
+            Rbool::na()
+        } else {
+            Rbool::false_value()
+        }
+    }
+}
+
 impl std::fmt::Debug for Rbool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -4,12 +4,14 @@ use crate::error::{Error, Result};
 use crate::na::CanBeNA;
 use crate::robj::{Attributes, GetSexp, Length, Rinternals, Types};
 use crate::scalar::{Rbool, Rfloat, Rint};
+use crate::wrapper::symbol::{levels_symbol, row_names_symbol};
 use crate::wrapper::{
-    Doubles, Environment, Expressions, Function, Integers, Language, Logicals, Pairlist, Primitive,
-    Promise, Raw, Rstr, Symbol, S4,
+    Doubles, Environment, Expressions, Function, Integers, Integers64, Language, Logicals,
+    Pairlist, Primitive, Promise, Raw, Rstr, Strings, Symbol, S4,
 };
-use crate::{List, Rany, Robj};
+use crate::{List, Rany, Robj, Rtype};
 use serde::{ser, Serialize};
+use std::cell::RefCell;
 
 impl ser::Error for Error {
     fn custom<T: std::fmt::Display>(msg: T) -> Self {
@@ -19,10 +21,33 @@ impl ser::Error for Error {
 
 struct RobjSerializer {
     robj: Option<Robj>,
+    config: SerializerConfig,
+    // The variant index, if the value was a unit variant.
+    variant: Option<u32>,
+    // The fields of a record, when serializing the rows of a data frame.
+    cells: Option<Vec<Cell>>,
+}
+
+impl RobjSerializer {
+    fn new(config: SerializerConfig) -> Self {
+        Self {
+            robj: None,
+            config,
+            variant: None,
+            cells: None,
+        }
+    }
+}
+
+// A field of a record, which becomes one element of a data frame column.
+struct Cell {
+    name: String,
+    value: Robj,
+    variant: Option<u32>,
 }
 
 struct SerializeSeq<'a> {
-    values: Vec<Robj>,
+    values: Vec<Cell>,
     parent: &'a mut RobjSerializer,
 }
 
@@ -51,6 +76,8 @@ struct SerializeMap<'a> {
 struct SerializeStruct<'a> {
     values: Vec<(String, Robj)>,
     parent: &'a mut RobjSerializer,
+    // Set for `WithAttributes`.
+    attributes: bool,
 }
 
 struct SerializeStructVariant<'a> {
@@ -59,6 +86,303 @@ struct SerializeStructVariant<'a> {
     variant: String,
 }
 
+/// How `None` is represented in R.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoneAs {
+    /// `NULL`, the default.
+    Null,
+    /// `NA`.
+    Na,
+}
+
+/// How enum variants are represented in R.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variants {
+    /// Unit variants are strings, and other variants are a list of one
+    /// element named after the variant, e.g. `list(Circle = list(r = 1))`.
+    /// This is the default.
+    External,
+    /// Variants are a list with the variant name in the `tag` element and its
+    /// contents, if any, in the `content` element,
+    /// e.g. `list(type = "Circle", value = list(r = 1))`.
+    Adjacent {
+        tag: &'static str,
+        content: &'static str,
+    },
+}
+
+/// How `u64` values are represented in R, which has no 64-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum U64As {
+    /// Doubles, which lose precision above 2^53. This is the default.
+    Double,
+    /// Doubles, with an error for values above 2^53.
+    Error,
+    /// Decimal strings.
+    String,
+    /// `bit64::integer64` values, with an error for values above `i64::MAX`.
+    Integer64,
+}
+
+/// Options for converting Rust values to R with [`to_robj_with`].
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::serializer::{to_robj_with, NoneAs, SerializerConfig};
+/// test! {
+///     let config = SerializerConfig::new().none(NoneAs::Na).atomic_vectors(true);
+///     let robj = to_robj_with(&vec![Some(1), None], config)?;
+///     assert_eq!(robj, r!([Some(1), None]));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializerConfig {
+    none: NoneAs,
+    atomic_vectors: bool,
+    variants: Variants,
+    u64_as: U64As,
+    keep_attributes: bool,
+}
+
+impl Default for SerializerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerializerConfig {
+    /// The options used by [`to_robj`].
+    pub fn new() -> Self {
+        Self {
+            none: NoneAs::Null,
+            atomic_vectors: false,
+            variants: Variants::External,
+            u64_as: U64As::Double,
+            keep_attributes: false,
+        }
+    }
+
+    /// Set how `None` is represented.
+    pub fn none(mut self, none: NoneAs) -> Self {
+        self.none = none;
+        self
+    }
+
+    /// Convert sequences of scalars of the same type to atomic vectors, rather
+    /// than lists. Integers and doubles make a double vector, `None`s are `NA`
+    /// and unit variants make a factor.
+    pub fn atomic_vectors(mut self, atomic_vectors: bool) -> Self {
+        self.atomic_vectors = atomic_vectors;
+        self
+    }
+
+    /// Set how enum variants are represented.
+    pub fn variants(mut self, variants: Variants) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Set how `u64` values are represented.
+    pub fn u64_as(mut self, u64_as: U64As) -> Self {
+        self.u64_as = u64_as;
+        self
+    }
+
+    /// Store [`Robj`] values as they are, with their attributes and class,
+    /// rather than converting them through serde.
+    pub fn keep_attributes(mut self, keep_attributes: bool) -> Self {
+        self.keep_attributes = keep_attributes;
+        self
+    }
+
+    // Represent a variant, with no value for unit variants.
+    fn variant(&self, variant: &str, value: Option<Robj>) -> Robj {
+        match (self.variants, value) {
+            (Variants::External, None) => Robj::from(variant),
+            (Variants::External, Some(value)) => List::from_pairs([(variant, value)]).into(),
+            (Variants::Adjacent { tag, .. }, None) => {
+                List::from_pairs([(tag, Robj::from(variant))]).into()
+            }
+            (Variants::Adjacent { tag, content }, Some(value)) => {
+                List::from_pairs([(tag, Robj::from(variant)), (content, value)]).into()
+            }
+        }
+    }
+}
+
+// `WithAttributes` is a struct with this name, so that its attributes can be
+// set on its value, and read from the value when deserializing.
+pub(crate) const ATTRIBUTES_TOKEN: &str = "$extendr::private::WithAttributes";
+
+/// A value with R attributes, such as `class`, `names`, `dim` or `levels`.
+///
+/// Converting to R sets the attributes on the converted value, and converting
+/// from R captures all attributes of the object, so that S3 objects can be
+/// represented faithfully. Other serde formats see a struct with `value`
+/// and `attributes` fields.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::deserializer::from_robj;
+/// use extendr_api::serializer::{to_robj_with, SerializerConfig, WithAttributes};
+/// test! {
+///     let factor = WithAttributes::new(vec![1, 2, 1])
+///         .with_attribute("levels", ["low", "high"])
+///         .with_class(["factor"]);
+///     let robj = to_robj_with(&factor, SerializerConfig::new().atomic_vectors(true))?;
+///     assert_eq!(robj, R!("factor(c('low','high','low'), levels = c('low','high'))")?);
+///
+///     let date: WithAttributes<f64> = from_robj(&R!("as.Date('2024-01-01')")?)?;
+///     assert_eq!(date.value, 19723.0);
+///     assert_eq!(date.class(), Some(vec!["Date"]));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithAttributes<T> {
+    pub value: T,
+    pub attributes: Vec<(String, Robj)>,
+}
+
+impl<T> WithAttributes<T> {
+    /// A value with no attributes.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Set an attribute, replacing any attribute of that name.
+    pub fn with_attribute<V: Into<Robj>>(mut self, name: &str, value: V) -> Self {
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.attributes.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// Set the `class` attribute.
+    pub fn with_class<C>(self, class: C) -> Self
+    where
+        C: IntoIterator,
+        C::IntoIter: ExactSizeIterator,
+        C::Item: AsRef<str>,
+    {
+        self.with_attribute("class", Strings::from_values(class))
+    }
+
+    /// Get an attribute.
+    pub fn attribute(&self, name: &str) -> Option<&Robj> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Get the `class` attribute.
+    pub fn class(&self) -> Option<Vec<&str>> {
+        self.attribute("class")?.as_str_vector()
+    }
+}
+
+impl<T: Serialize> ser::Serialize for WithAttributes<T> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct(ATTRIBUTES_TOKEN, 2)?;
+        s.serialize_field("value", &self.value)?;
+        s.serialize_field("attributes", &AttributeMap(&self.attributes))?;
+        s.end()
+    }
+}
+
+struct AttributeMap<'a>(&'a [(String, Robj)]);
+
+impl ser::Serialize for AttributeMap<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use serde::ser::SerializeMap;
+        let mut s = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in self.0 {
+            s.serialize_entry(name, value)?;
+        }
+        s.end()
+    }
+}
+
+/// A value converted to and from R with serde, e.g. an argument or the
+/// return value of an `#[extendr]` function.
+///
+/// Conversion errors name the argument and the path of the offending element.
+/// An argument can also be marked with `#[extendr(serde)]` to keep its type.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// #[extendr]
+/// fn centroid(#[extendr(serde)] points: Vec<Point>) -> Serde<Point> {
+///     let n = points.len() as f64;
+///     Serde(Point {
+///         x: points.iter().map(|p| p.x).sum::<f64>() / n,
+///         y: points.iter().map(|p| p.y).sum::<f64>() / n,
+///     })
+/// }
+///
+/// test! {
+///     let points = list!(list!(x = 0.0, y = 1.0), list!(x = 2.0, y = "a"));
+///     let err = Serde::<Vec<Point>>::from_arg(&points.into(), "points").unwrap_err();
+///     assert_eq!(err.to_string(), "points[[2]]$y: Expected Numeric, got Strings");
+///
+///     let point = Robj::try_from(Serde(Point { x: 1.0, y: 2.0 }))?;
+///     assert_eq!(point, list!(x = 1.0, y = 2.0));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Serde<T>(pub T);
+
+impl<T> Serde<T> {
+    /// Get the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Serde<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Serde<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// `#[extendr]` functions returning `Serde<T>` raise an R error if the value
+/// cannot be serialized.
+impl<T: Serialize> TryFrom<Serde<T>> for Robj {
+    type Error = Error;
+
+    fn try_from(value: Serde<T>) -> Result<Self> {
+        to_robj(&value.0)
+    }
+}
+
 /// Convert a serializable object to a Robj.
 ///
 /// Requires the "serde" feature.
@@ -93,12 +417,294 @@ pub fn to_robj<T>(value: &T) -> Result<Robj>
 where
     T: Serialize,
 {
-    let mut serializer = RobjSerializer { robj: None };
+    to_robj_with(value, SerializerConfig::new())
+}
 
+/// Convert a serializable object to a Robj, with the given options.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::serializer::{to_robj_with, SerializerConfig, Variants};
+/// use serde::Serialize;
+/// test! {
+///     #[derive(Serialize)]
+///     enum Shape {
+///         Circle { r: f64 },
+///     }
+///
+///     let config = SerializerConfig::new().variants(Variants::Adjacent {
+///         tag: "type",
+///         content: "value",
+///     });
+///     let robj = to_robj_with(&Shape::Circle { r: 1.0 }, config)?;
+///     assert_eq!(robj, list!(type = "Circle", value = list!(r = 1.0)).into());
+/// }
+/// ```
+pub fn to_robj_with<T>(value: &T, config: SerializerConfig) -> Result<Robj>
+where
+    T: ?Sized + Serialize,
+{
+    let mut serializer = RobjSerializer::new(config);
     value.serialize(&mut serializer)?;
     Ok(serializer.robj.unwrap())
 }
 
+fn to_cell<T>(name: String, value: &T, config: SerializerConfig) -> Result<Cell>
+where
+    T: ?Sized + Serialize,
+{
+    let mut serializer = RobjSerializer::new(config);
+    value.serialize(&mut serializer)?;
+    Ok(Cell {
+        name,
+        value: serializer.robj.unwrap(),
+        variant: serializer.variant,
+    })
+}
+
+/// Convert a sequence of records, i.e. structs or maps, to a `data.frame`
+/// with one row per record.
+///
+/// Fields become columns, in the order they first appear. Columns of
+/// scalars are atomic vectors, where `None` and missing fields are `NA`,
+/// and columns of unit variants are factors, whose levels are the variants
+/// that occur, in the order they are declared.
+/// Any other column is a list.
+///
+/// Requires the "serde" feature.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::serializer::to_dataframe;
+/// use serde::Serialize;
+/// test! {
+///     #[derive(Serialize)]
+///     enum Species {
+///         Cat,
+///         Dog,
+///     }
+///
+///     #[derive(Serialize)]
+///     struct Pet {
+///         name: &'static str,
+///         age: Option<i32>,
+///         species: Species,
+///     }
+///
+///     let pets = [
+///         Pet { name: "Tom", age: Some(3), species: Species::Cat },
+///         Pet { name: "Rex", age: None, species: Species::Dog },
+///     ];
+///
+///     let df = to_dataframe(&pets)?;
+///     assert!(df.is_frame());
+///     assert_eq!(df.dollar("name")?, r!(["Tom", "Rex"]));
+///     assert_eq!(df.dollar("age")?, r!([Some(3), None]));
+///     assert_eq!(df.dollar("species")?, factor!(["Cat", "Dog"]));
+/// }
+/// ```
+pub fn to_dataframe<I>(records: I) -> Result<Robj>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    to_dataframe_with(records, SerializerConfig::new())
+}
+
+/// Convert a sequence of records to a `data.frame`, with the given options.
+///
+/// See [`to_dataframe`]. The options apply to the cells, e.g. `None` in list
+/// columns is `NA` with [`NoneAs::Na`].
+pub fn to_dataframe_with<I>(records: I, config: SerializerConfig) -> Result<Robj>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut names: Vec<String> = Vec::new();
+    let mut columns: Vec<Vec<Option<Cell>>> = Vec::new();
+    let mut nrow = 0;
+
+    for record in records {
+        let mut serializer = RobjSerializer {
+            cells: Some(Vec::new()),
+            ..RobjSerializer::new(config)
+        };
+        record.serialize(&mut serializer)?;
+        if let Some(robj) = serializer.robj {
+            return Err(Error::Other(format!(
+                "data frame rows must be structs or maps, got {:?}",
+                robj.rtype()
+            )));
+        }
+
+        for cell in serializer.cells.unwrap_or_default() {
+            let column = match names.iter().position(|name| *name == cell.name) {
+                Some(column) => &mut columns[column],
+                None => {
+                    names.push(cell.name.clone());
+                    columns.push(Vec::new());
+                    columns.last_mut().unwrap()
+                }
+            };
+            if column.len() > nrow {
+                return Err(Error::Other(format!(
+                    "duplicate field `{}` in row {}",
+                    cell.name,
+                    nrow + 1
+                )));
+            }
+            column.resize_with(nrow, || None);
+            column.push(Some(cell));
+        }
+        nrow += 1;
+    }
+
+    let columns = columns
+        .into_iter()
+        .map(|mut column| {
+            column.resize_with(nrow, || None);
+            to_column(column)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut df: Robj = List::from_names_and_values(names, columns)?.into();
+    df.set_attrib(
+        row_names_symbol(),
+        Integers::from_values([Rint::na(), Rint::from(-(nrow as i32))]),
+    )?;
+    df.set_class(["data.frame"])?;
+    Ok(df)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    Logical,
+    Integer,
+    Double,
+    Character,
+    Factor,
+    List,
+}
+
+fn cell_type(cell: &Cell) -> ColumnType {
+    if cell.value.len() != 1 || cell.value.class().is_some() {
+        return ColumnType::List;
+    }
+    match (cell.value.rtype(), cell.variant) {
+        (Rtype::Logicals, _) => ColumnType::Logical,
+        (Rtype::Integers, _) => ColumnType::Integer,
+        (Rtype::Doubles, _) => ColumnType::Double,
+        (Rtype::Strings, None) => ColumnType::Character,
+        (Rtype::Strings, Some(_)) => ColumnType::Factor,
+        _ => ColumnType::List,
+    }
+}
+
+// Make a data frame column or atomic vector, where NULL, NA and missing values are NA.
+// Anything else that is not a scalar makes a list.
+fn to_column(column: Vec<Option<Cell>>) -> Result<Robj> {
+    let is_missing =
+        |cell: &Cell| cell.value.is_null() || (cell.value.is_logical() && cell.value.is_na());
+
+    let column_type = column
+        .iter()
+        .flatten()
+        .filter(|cell| !is_missing(cell))
+        .map(cell_type)
+        .reduce(|a, b| match (a, b) {
+            _ if a == b => a,
+            (ColumnType::Integer, ColumnType::Double)
+            | (ColumnType::Double, ColumnType::Integer) => ColumnType::Double,
+            (ColumnType::Character, ColumnType::Factor)
+            | (ColumnType::Factor, ColumnType::Character) => ColumnType::Character,
+            _ => ColumnType::List,
+        })
+        .unwrap_or(ColumnType::Logical);
+
+    if column_type == ColumnType::List {
+        let values = column
+            .into_iter()
+            .map(|cell| cell.map_or_else(|| Robj::from(()), |cell| cell.value));
+        return Ok(List::from_values(values).into());
+    }
+
+    let cells: Vec<_> = column
+        .into_iter()
+        .map(|cell| cell.filter(|cell| !is_missing(cell)))
+        .collect();
+
+    let robj = match column_type {
+        ColumnType::Logical => Logicals::from_values(
+            cells
+                .iter()
+                .map(|cell| {
+                    cell.as_ref()
+                        .map_or(Ok(Rbool::na()), |cell| Rbool::try_from(&cell.value))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        ColumnType::Integer => Integers::from_values(
+            cells
+                .iter()
+                .map(|cell| {
+                    cell.as_ref()
+                        .map_or(Ok(Rint::na()), |cell| Rint::try_from(&cell.value))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        ColumnType::Double => Doubles::from_values(
+            cells
+                .iter()
+                .map(|cell| {
+                    cell.as_ref()
+                        .map_or(Ok(Rfloat::na()), |cell| Rfloat::try_from(&cell.value))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        ColumnType::Character => Strings::from_values(
+            cells
+                .iter()
+                .map(|cell| {
+                    cell.as_ref().map_or(Ok(Rstr::na()), |cell| {
+                        Strings::try_from(&cell.value).map(|value| value.elt(0))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        ColumnType::Factor => {
+            // Levels are in the order of the variants.
+            let mut levels: Vec<(u32, &str)> = cells
+                .iter()
+                .flatten()
+                .map(|cell| (cell.variant.unwrap(), cell.value.as_str().unwrap()))
+                .collect();
+            levels.sort_unstable();
+            levels.dedup();
+            let codes = cells.iter().map(|cell| match cell {
+                Some(cell) => {
+                    let variant = cell.variant.unwrap();
+                    let code = levels.iter().position(|(v, _)| *v == variant).unwrap();
+                    Rint::from(code as i32 + 1)
+                }
+                None => Rint::na(),
+            });
+            let mut factor: Robj = Integers::from_values(codes).into();
+            factor.set_attrib(
+                levels_symbol(),
+                Strings::from_values(levels.iter().map(|(_, level)| level)),
+            )?;
+            factor.set_class(["factor"])?;
+            factor
+        }
+        ColumnType::List => unreachable!(),
+    };
+    Ok(robj)
+}
+
 impl<'a> ser::Serializer for &'a mut RobjSerializer {
     // The output type produced by this `RobjSerializer` during successful
     // serialization. Most serializers that produce text or binary output should
@@ -173,7 +779,14 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
 
     /// Map a u64 to a Robj.
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.robj = Some(Robj::from(v));
+        const MAX_EXACT: u64 = 1 << f64::MANTISSA_DIGITS;
+        self.robj = Some(match self.config.u64_as {
+            U64As::Double => Robj::from(v),
+            U64As::Error if v <= MAX_EXACT => Robj::from(v),
+            U64As::String => Robj::from(v.to_string()),
+            U64As::Integer64 if v <= i64::MAX as u64 => Integers64::from_values([v as i64]).into(),
+            U64As::Error | U64As::Integer64 => return Err(Error::OutOfLimits(Robj::from(v))),
+        });
         Ok(())
     }
 
@@ -207,9 +820,12 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
         Ok(())
     }
 
-    /// None of an option is NULL.
+    /// None of an option is NULL, or NA.
     fn serialize_none(self) -> Result<()> {
-        self.robj = Some(Robj::from(()));
+        self.robj = Some(match self.config.none {
+            NoneAs::Null => Robj::from(()),
+            NoneAs::Na => Robj::from(Rbool::na()),
+        });
         Ok(())
     }
 
@@ -239,10 +855,11 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
     fn serialize_unit_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.robj = Some(Robj::from(variant));
+        self.robj = Some(self.config.variant(variant, None));
+        self.variant = Some(variant_index);
         Ok(())
     }
 
@@ -251,6 +868,13 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
     where
         T: ?Sized + Serialize,
     {
+        // An `Robj` is stored unchanged, see `impl Serialize for Robj`.
+        if let Some(robj) = take_serialized_robj(value as *const T as *const ()) {
+            if self.config.keep_attributes {
+                self.robj = Some(robj);
+                return Ok(());
+            }
+        }
         value.serialize(self)
     }
 
@@ -265,9 +889,8 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
     where
         T: ?Sized + Serialize,
     {
-        let value = to_robj(&value)?;
-        let list = List::from_pairs([(variant, value)]);
-        self.robj = Some(list.into());
+        let value = to_robj_with(value, self.config)?;
+        self.robj = Some(self.config.variant(variant, Some(value)));
         Ok(())
     }
 
@@ -329,10 +952,11 @@ impl<'a> ser::Serializer for &'a mut RobjSerializer {
     }
 
     /// Start of a struct. Collect just the values in a list.
-    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
         Ok(SerializeStruct {
             values: Vec::with_capacity(len),
             parent: self,
+            attributes: name == ATTRIBUTES_TOKEN,
         })
     }
 
@@ -364,13 +988,19 @@ impl<'a> ser::SerializeSeq for self::SerializeSeq<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push(to_robj(&value)?);
+        self.values
+            .push(to_cell(String::new(), value, self.parent.config)?);
         Ok(())
     }
 
     // Close the sequence.
     fn end(self) -> Result<()> {
-        self.parent.robj = Some(List::from_values(self.values).into());
+        let robj = if self.parent.config.atomic_vectors && !self.values.is_empty() {
+            to_column(self.values.into_iter().map(Some).collect())?
+        } else {
+            List::from_values(self.values.into_iter().map(|cell| cell.value)).into()
+        };
+        self.parent.robj = Some(robj);
         Ok(())
     }
 }
@@ -384,7 +1014,7 @@ impl<'a> ser::SerializeTuple for SerializeTuple<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push(to_robj(&value)?);
+        self.values.push(to_robj_with(value, self.parent.config)?);
         Ok(())
     }
 
@@ -403,7 +1033,7 @@ impl<'a> ser::SerializeTupleStruct for self::SerializeTupleStruct<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push(to_robj(&value)?);
+        self.values.push(to_robj_with(value, self.parent.config)?);
         Ok(())
     }
 
@@ -422,14 +1052,13 @@ impl<'a> ser::SerializeTupleVariant for self::SerializeTupleVariant<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push(to_robj(&value)?);
+        self.values.push(to_robj_with(value, self.parent.config)?);
         Ok(())
     }
 
     fn end(self) -> Result<()> {
         let value = List::from_values(self.values).into();
-        let list = List::from_pairs([(self.variant, value)]);
-        self.parent.robj = Some(list.into());
+        self.parent.robj = Some(self.parent.config.variant(&self.variant, Some(value)));
         Ok(())
     }
 }
@@ -443,7 +1072,7 @@ impl<'a> ser::SerializeMap for self::SerializeMap<'a> {
     where
         T: ?Sized + Serialize,
     {
-        let key = to_robj(&key)?;
+        let key = to_robj_with(key, self.parent.config)?;
         if let Some(key_str) = key.as_str() {
             self.key = key_str.to_string();
             Ok(())
@@ -457,12 +1086,19 @@ impl<'a> ser::SerializeMap for self::SerializeMap<'a> {
         T: ?Sized + Serialize,
     {
         let key = std::mem::take(&mut self.key);
-        self.values.push((key, to_robj(&value)?));
+        if let Some(cells) = &mut self.parent.cells {
+            cells.push(to_cell(key, value, self.parent.config)?);
+        } else {
+            self.values
+                .push((key, to_robj_with(value, self.parent.config)?));
+        }
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.parent.robj = Some(List::from_pairs(self.values).into());
+        if self.parent.cells.is_none() {
+            self.parent.robj = Some(List::from_pairs(self.values).into());
+        }
         Ok(())
     }
 }
@@ -477,12 +1113,41 @@ impl<'a> ser::SerializeStruct for self::SerializeStruct<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push((key.to_string(), to_robj(&value)?));
+        if self.attributes {
+            // Attributes are set as they are.
+            let config = if key == "attributes" {
+                self.parent.config.keep_attributes(true)
+            } else {
+                self.parent.config
+            };
+            self.values
+                .push((key.to_string(), to_robj_with(value, config)?));
+        } else if let Some(cells) = &mut self.parent.cells {
+            cells.push(to_cell(key.to_string(), value, self.parent.config)?);
+        } else {
+            self.values
+                .push((key.to_string(), to_robj_with(value, self.parent.config)?));
+        }
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.parent.robj = Some(List::from_pairs(self.values).into());
+        if self.attributes {
+            let mut fields = self.values.into_iter().map(|(_, value)| value);
+            let (value, attributes) = (fields.next().unwrap(), fields.next().unwrap());
+            let attributes = List::try_from(attributes)?;
+            let mut value = if attributes.is_empty() {
+                value
+            } else {
+                value.duplicate()
+            };
+            for (name, attribute) in attributes.iter() {
+                value.set_attrib(name, attribute)?;
+            }
+            self.parent.robj = Some(value);
+        } else if self.parent.cells.is_none() {
+            self.parent.robj = Some(List::from_pairs(self.values).into());
+        }
         Ok(())
     }
 }
@@ -496,14 +1161,14 @@ impl<'a> ser::SerializeStructVariant for self::SerializeStructVariant<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.values.push((key.to_string(), to_robj(&value)?));
+        self.values
+            .push((key.to_string(), to_robj_with(value, self.parent.config)?));
         Ok(())
     }
 
     fn end(self) -> Result<()> {
         let value = List::from_pairs(self.values).into();
-        let list = List::from_pairs([(self.variant, value)]);
-        self.parent.robj = Some(list.into());
+        self.parent.robj = Some(self.parent.config.variant(&self.variant, Some(value)));
         Ok(())
     }
 }
@@ -739,7 +1404,49 @@ impl ser::Serialize for Robj {
     where
         S: ser::Serializer,
     {
-        match self.as_any() {
+        // Serializers that borrow have no `TypeId`, so extendr's own one is
+        // recognised by name, and gets the contents as a newtype struct. Other
+        // serializers see the contents only. The name decides the shape of the
+        // output, never what is read: the object itself is stashed, and only
+        // handed over for this very `contents` value.
+        let contents = Contents(self);
+        if std::any::type_name::<S>() != std::any::type_name::<&mut RobjSerializer>() {
+            return contents.serialize(serializer);
+        }
+        let addr = &contents as *const Contents as *const ();
+        let previous = SERIALIZED_ROBJ.with(|stash| stash.replace(Some((addr, self.clone()))));
+        let result = serializer.serialize_newtype_struct("Robj", &contents);
+        SERIALIZED_ROBJ.with(|stash| *stash.borrow_mut() = previous);
+        result
+    }
+}
+
+thread_local! {
+    // The `Robj` being serialized, with the address of the `Contents` that it
+    // passes to `RobjSerializer::serialize_newtype_struct`.
+    static SERIALIZED_ROBJ: RefCell<Option<(*const (), Robj)>> = const { RefCell::new(None) };
+}
+
+// The stashed `Robj`, if `value` is the `Contents` it was stashed with.
+fn take_serialized_robj(value: *const ()) -> Option<Robj> {
+    SERIALIZED_ROBJ.with(|stash| {
+        let mut stash = stash.borrow_mut();
+        match stash.as_ref() {
+            Some((addr, _)) if *addr == value => stash.take().map(|(_, robj)| robj),
+            _ => None,
+        }
+    })
+}
+
+// The contents of an `Robj`, converted through serde.
+struct Contents<'a>(&'a Robj);
+
+impl ser::Serialize for Contents<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.0.as_any() {
             Rany::Null(_) => serializer.serialize_unit(),
             Rany::Symbol(value) => value.serialize(serializer),
             Rany::Pairlist(value) => value.serialize(serializer),
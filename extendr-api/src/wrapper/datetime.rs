@@ -0,0 +1,535 @@
+//! Wrappers for R's `Date`, `POSIXct` and `difftime` classes.
+//!
+//! All three are double vectors with a class attribute. Integer storage,
+//! which R uses occasionally, is converted to doubles, and `POSIXlt`
+//! objects are converted with `as.POSIXct()`.
+
+use super::scalar::Rfloat;
+use super::*;
+use crate as extendr_api;
+use extendr_ffi::{Rf_coerceVector, SEXPTYPE::REALSXP};
+use std::iter::FromIterator;
+
+/// Days since 1970-01-01, with class `Date`.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let dates = Dates::from_days([0.0, 19723.0]);
+///     assert_eq!(dates.inherits("Date"), true);
+///     assert_eq!(R!("format({{dates}})")?, r!(["1970-01-01", "2024-01-01"]));
+///
+///     let dates: Dates = R!("as.Date(c('2000-01-01', NA))")?.try_into()?;
+///     assert_eq!(dates.elt(0), 10957.0);
+///     assert!(dates.elt(1).is_na());
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct Dates {
+    pub(crate) robj: Robj,
+}
+
+/// Seconds since 1970-01-01 UTC, with class `POSIXct` and a time zone.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let times = DateTimes::from_seconds([0.0, 3600.0], "UTC");
+///     assert_eq!(times.tzone(), Some("UTC"));
+///     assert_eq!(R!("format({{times}})")?, r!(["1970-01-01 00:00:00", "1970-01-01 01:00:00"]));
+///
+///     // POSIXlt is converted.
+///     let times: DateTimes = R!("as.POSIXlt('2000-01-01', tz = 'UTC')")?.try_into()?;
+///     assert_eq!(times.elt(0), 946684800.0);
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct DateTimes {
+    pub(crate) robj: Robj,
+}
+
+/// Time differences, with class `difftime` and their units.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let diff: Difftimes = R!("as.difftime(c(1.5, NA), units = 'hours')")?.try_into()?;
+///     assert_eq!(diff.units()?, TimeUnits::Hours);
+///     assert_eq!(diff.seconds().collect::<Vec<_>>()[0], 5400.0);
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct Difftimes {
+    pub(crate) robj: Robj,
+}
+
+/// The units of a `difftime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeUnits {
+    Secs,
+    Mins,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl TimeUnits {
+    /// The name R uses for the units, e.g. `"secs"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeUnits::Secs => "secs",
+            TimeUnits::Mins => "mins",
+            TimeUnits::Hours => "hours",
+            TimeUnits::Days => "days",
+            TimeUnits::Weeks => "weeks",
+        }
+    }
+
+    /// The number of seconds in one unit.
+    pub fn seconds(&self) -> f64 {
+        match self {
+            TimeUnits::Secs => 1.0,
+            TimeUnits::Mins => 60.0,
+            TimeUnits::Hours => 3600.0,
+            TimeUnits::Days => 86400.0,
+            TimeUnits::Weeks => 604800.0,
+        }
+    }
+}
+
+impl std::str::FromStr for TimeUnits {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "secs" => Ok(TimeUnits::Secs),
+            "mins" => Ok(TimeUnits::Mins),
+            "hours" => Ok(TimeUnits::Hours),
+            "days" => Ok(TimeUnits::Days),
+            "weeks" => Ok(TimeUnits::Weeks),
+            _ => Err(Error::Other(format!("unknown difftime units {s:?}"))),
+        }
+    }
+}
+
+macro_rules! gen_time_vector_impl {
+    ($type: ident, $doc_name: literal) => {
+        impl Attributes for $type {}
+
+        impl $type {
+            #[doc = concat!("Get a single element of the ", $doc_name, ", or `NA` if out of bounds.")]
+            pub fn elt(&self, index: usize) -> Rfloat {
+                self.deref().get(index).copied().unwrap_or_else(Rfloat::na)
+            }
+
+            #[doc = concat!("Set a single element of the ", $doc_name, ".")]
+            pub fn set_elt(&mut self, index: usize, val: Rfloat) {
+                self[index] = val;
+            }
+
+            #[doc = concat!("Return an iterator for the values of the ", $doc_name, ".")]
+            pub fn iter(&self) -> impl Iterator<Item = Rfloat> + '_ {
+                self.deref().iter().copied()
+            }
+        }
+
+        impl Deref for $type {
+            type Target = [Rfloat];
+
+            fn deref(&self) -> &Self::Target {
+                self.robj.as_typed_slice().unwrap()
+            }
+        }
+
+        impl DerefMut for $type {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                self.robj.as_typed_slice_mut().unwrap()
+            }
+        }
+
+        impl From<$type> for Robj {
+            fn from(val: $type) -> Self {
+                val.robj
+            }
+        }
+
+        impl From<&$type> for Robj {
+            fn from(val: &$type) -> Self {
+                val.robj.clone()
+            }
+        }
+
+        impl From<Option<$type>> for Robj {
+            fn from(value: Option<$type>) -> Self {
+                match value {
+                    None => nil_value(),
+                    Some(value) => value.into(),
+                }
+            }
+        }
+
+        impl TryFrom<Robj> for $type {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                <$type>::try_from(&robj)
+            }
+        }
+
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(stringify!($type))?;
+                f.debug_list().entries(self.iter()).finish()
+            }
+        }
+    };
+}
+
+gen_time_vector_impl!(Dates, "dates");
+gen_time_vector_impl!(DateTimes, "date-times");
+gen_time_vector_impl!(Difftimes, "time differences");
+
+impl Dates {
+    /// Create dates from days since 1970-01-01.
+    pub fn from_days<V>(days: V) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: Into<Rfloat>,
+    {
+        let mut robj = Doubles::from_values(days).robj;
+        robj.set_class(["Date"]).unwrap();
+        Self { robj }
+    }
+
+    /// Create a vector of `NA` dates.
+    pub fn new_with_na(len: usize) -> Self {
+        Self::from_days((0..len).map(|_| Rfloat::na()))
+    }
+}
+
+impl FromIterator<Rfloat> for Dates {
+    fn from_iter<T: IntoIterator<Item = Rfloat>>(iter: T) -> Self {
+        Self::from_days(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl TryFrom<&Robj> for Dates {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if !robj.inherits("Date") {
+            return Err(Error::ExpectedDate(robj.clone()));
+        }
+        let robj = as_double_storage(robj).ok_or_else(|| Error::ExpectedDate(robj.clone()))?;
+        Ok(Self { robj })
+    }
+}
+
+impl DateTimes {
+    /// Create date-times from seconds since 1970-01-01 UTC.
+    ///
+    /// The time zone `tzone` is used for printing. `""` is the local time zone.
+    pub fn from_seconds<V>(seconds: V, tzone: &str) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: Into<Rfloat>,
+    {
+        let mut robj = Doubles::from_values(seconds).robj;
+        robj.set_class(["POSIXct", "POSIXt"]).unwrap();
+        robj.set_attrib("tzone", tzone).unwrap();
+        Self { robj }
+    }
+
+    /// Create a vector of `NA` date-times.
+    pub fn new_with_na(len: usize, tzone: &str) -> Self {
+        Self::from_seconds((0..len).map(|_| Rfloat::na()), tzone)
+    }
+
+    /// The time zone, if any. `""` is the local time zone.
+    pub fn tzone(&self) -> Option<&str> {
+        self.get_attrib("tzone").and_then(|tz| tz.as_str())
+    }
+
+    /// Change the time zone used for printing. The instants are unchanged.
+    pub fn set_tzone(&mut self, tzone: &str) -> Result<&mut Self> {
+        self.set_attrib("tzone", tzone)?;
+        Ok(self)
+    }
+}
+
+impl TryFrom<&Robj> for DateTimes {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.inherits("POSIXlt") {
+            let ct = call!("as.POSIXct", robj)?;
+            return Self::try_from(&ct);
+        }
+        if !robj.inherits("POSIXct") {
+            return Err(Error::ExpectedDateTime(robj.clone()));
+        }
+        let robj = as_double_storage(robj).ok_or_else(|| Error::ExpectedDateTime(robj.clone()))?;
+        Ok(Self { robj })
+    }
+}
+
+impl Difftimes {
+    /// Create time differences in `units`.
+    pub fn from_values<V>(values: V, units: TimeUnits) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: Into<Rfloat>,
+    {
+        let mut robj = Doubles::from_values(values).robj;
+        robj.set_class(["difftime"]).unwrap();
+        robj.set_attrib("units", units.as_str()).unwrap();
+        Self { robj }
+    }
+
+    /// Create time differences from seconds.
+    pub fn from_seconds<V>(seconds: V) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: Into<Rfloat>,
+    {
+        Self::from_values(seconds, TimeUnits::Secs)
+    }
+
+    /// The units of the values.
+    pub fn units(&self) -> Result<TimeUnits> {
+        let units = self.get_attrib("units");
+        units
+            .as_ref()
+            .and_then(|units| units.as_str())
+            .ok_or_else(|| Error::ExpectedDifftime(self.robj.clone()))?
+            .parse()
+    }
+
+    /// The values converted to seconds.
+    pub fn seconds(&self) -> impl Iterator<Item = Rfloat> + '_ {
+        let factor = self
+            .units()
+            .map(|units| units.seconds())
+            .unwrap_or(f64::NAN);
+        self.iter()
+            .map(move |x| if x.is_na() { x } else { (x.0 * factor).into() })
+    }
+}
+
+impl TryFrom<&Robj> for Difftimes {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if !robj.inherits("difftime") {
+            return Err(Error::ExpectedDifftime(robj.clone()));
+        }
+        let robj = as_double_storage(robj).ok_or_else(|| Error::ExpectedDifftime(robj.clone()))?;
+        let res = Self { robj };
+        res.units()?;
+        Ok(res)
+    }
+}
+
+// `robj` with double storage and the same attributes, or `None` if it is not numeric.
+fn as_double_storage(robj: &Robj) -> Option<Robj> {
+    if robj.is_real() {
+        Some(robj.clone())
+    } else if robj.is_integer() {
+        // Unlike `as.double()`, this keeps the attributes.
+        Some(single_threaded(|| unsafe {
+            Robj::from_sexp(Rf_coerceVector(robj.get(), REALSXP))
+        }))
+    } else {
+        None
+    }
+}
+
+// Conversions for the date and time types of `chrono`, `time` and `jiff`.
+
+/// The R class a Rust date or time type converts to.
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimeClass {
+    Date,
+    DateTime,
+    Difftime,
+}
+
+/// A date or time type that can be stored in [Dates], [DateTimes] or [Difftimes].
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+pub(crate) trait TimeValue: Sized {
+    const CLASS: TimeClass;
+
+    /// Days for dates, seconds since the epoch for date-times, seconds for durations.
+    fn to_r(&self) -> f64;
+
+    /// The time zone to store with date-times.
+    fn tzone(&self) -> &str {
+        "UTC"
+    }
+
+    /// The inverse of `to_r`. `value` is finite.
+    fn from_r(value: f64, tzone: &str) -> Result<Self>;
+}
+
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+pub(crate) fn time_values_into_robj<'a, T, I>(values: I) -> Robj
+where
+    T: TimeValue + 'a,
+    I: IntoIterator<Item = Option<&'a T>>,
+    I::IntoIter: ExactSizeIterator + Clone,
+{
+    let values = values.into_iter();
+    let r_values = values
+        .clone()
+        .map(|value| value.map_or(Rfloat::na(), |value| value.to_r().into()));
+    match T::CLASS {
+        TimeClass::Date => Dates::from_days(r_values).into(),
+        TimeClass::DateTime => {
+            // R has a single time zone per vector.
+            let tzone = values.flatten().next().map_or("UTC", |value| value.tzone());
+            DateTimes::from_seconds(r_values, tzone).into()
+        }
+        TimeClass::Difftime => Difftimes::from_seconds(r_values).into(),
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+pub(crate) fn time_values_from_robj<T: TimeValue>(robj: &Robj) -> Result<Vec<Option<T>>> {
+    let (values, tzone): (Vec<Rfloat>, String) = match T::CLASS {
+        TimeClass::Date => (Dates::try_from(robj)?.iter().collect(), String::new()),
+        TimeClass::DateTime => {
+            let times = DateTimes::try_from(robj)?;
+            let tzone = times.tzone().unwrap_or("").to_string();
+            (times.iter().collect(), tzone)
+        }
+        TimeClass::Difftime => (
+            Difftimes::try_from(robj)?.seconds().collect(),
+            String::new(),
+        ),
+    };
+    values
+        .into_iter()
+        .map(|value| {
+            if value.is_na() || value.is_nan() {
+                Ok(None)
+            } else if value.is_infinite() {
+                Err(Error::OutOfRange(robj.clone()))
+            } else {
+                T::from_r(value.0, &tzone).map(Some)
+            }
+        })
+        .collect()
+}
+
+/// Implement `From<T>`, `TryFrom<&Robj>` etc. for a [TimeValue] `T`,
+/// including `Option<T>`, `Vec<T>` and `Vec<Option<T>>`.
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+macro_rules! impl_time_conversions {
+    (@into $type: ty) => {
+        impl From<$type> for Robj {
+            fn from(value: $type) -> Self {
+                $crate::wrapper::datetime::time_values_into_robj([Some(&value)])
+            }
+        }
+
+        impl From<&$type> for Robj {
+            fn from(value: &$type) -> Self {
+                $crate::wrapper::datetime::time_values_into_robj([Some(value)])
+            }
+        }
+
+        impl From<Option<$type>> for Robj {
+            fn from(value: Option<$type>) -> Self {
+                $crate::wrapper::datetime::time_values_into_robj([value.as_ref()])
+            }
+        }
+
+        impl From<Vec<$type>> for Robj {
+            fn from(values: Vec<$type>) -> Self {
+                $crate::wrapper::datetime::time_values_into_robj(values.iter().map(Some))
+            }
+        }
+
+        impl From<Vec<Option<$type>>> for Robj {
+            fn from(values: Vec<Option<$type>>) -> Self {
+                $crate::wrapper::datetime::time_values_into_robj(values.iter().map(Option::as_ref))
+            }
+        }
+    };
+
+    (@try_from $type: ty) => {
+        impl TryFrom<&Robj> for $type {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                let mut values = $crate::wrapper::datetime::time_values_from_robj::<$type>(robj)?;
+                if values.len() != 1 {
+                    return Err(Error::ExpectedScalar(robj.clone()));
+                }
+                values.pop().unwrap().ok_or_else(|| Error::MustNotBeNA(robj.clone()))
+            }
+        }
+
+        impl TryFrom<&Robj> for Option<$type> {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                if robj.is_null() {
+                    return Ok(None);
+                }
+                let mut values = $crate::wrapper::datetime::time_values_from_robj::<$type>(robj)?;
+                if values.len() != 1 {
+                    return Err(Error::ExpectedScalar(robj.clone()));
+                }
+                Ok(values.pop().unwrap())
+            }
+        }
+
+        impl TryFrom<&Robj> for Vec<$type> {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                $crate::wrapper::datetime::time_values_from_robj::<$type>(robj)?
+                    .into_iter()
+                    .map(|value| value.ok_or_else(|| Error::MustNotBeNA(robj.clone())))
+                    .collect()
+            }
+        }
+
+        impl TryFrom<&Robj> for Vec<Option<$type>> {
+            type Error = Error;
+
+            fn try_from(robj: &Robj) -> Result<Self> {
+                $crate::wrapper::datetime::time_values_from_robj::<$type>(robj)
+            }
+        }
+
+        $crate::wrapper::datetime::impl_time_conversions!(@owned $type);
+        $crate::wrapper::datetime::impl_time_conversions!(@owned Option<$type>);
+        $crate::wrapper::datetime::impl_time_conversions!(@owned Vec<$type>);
+        $crate::wrapper::datetime::impl_time_conversions!(@owned Vec<Option<$type>>);
+    };
+
+    (@owned $type: ty) => {
+        impl TryFrom<Robj> for $type {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+    };
+
+    ($type: ty) => {
+        $crate::wrapper::datetime::impl_time_conversions!(@into $type);
+        $crate::wrapper::datetime::impl_time_conversions!(@try_from $type);
+    };
+}
+
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+pub(crate) use impl_time_conversions;
@@ -1,8 +1,11 @@
 //! `ExternalPtr` is a way to leak Rust allocated data to R, forego deallocation
 //! to R and its GC strategy.
 //!
-//! An `ExternalPtr` encompasses three values, an owned pointer to the Rust
-//! type, a `tag` and a `prot`. Tag is a helpful naming of the type, but
+//! An `ExternalPtr` encompasses three values, a pointer to the Rust
+//! type, a `tag` and a `prot`. The pointer usually owns its data, see
+//! [`ExternalPtr::new`], but may also borrow `'static` data or wrap a foreign
+//! pointer with its own release function, see [`ExternalPtr::from_static`]
+//! and [`ExternalPtr::from_raw`]. Tag is a helpful naming of the type, but
 //! it doesn't offer any solid type-checking capability. And `prot` is meant
 //! to be R values, that are supposed to be kept together with the `ExternalPtr`.
 //!
@@ -20,9 +23,10 @@
 use super::*;
 use extendr_ffi::{
     R_ClearExternalPtr, R_ExternalPtrAddr, R_ExternalPtrProtected, R_ExternalPtrTag,
-    R_MakeExternalPtr, R_NilValue, R_SetExternalPtrTag,
+    R_MakeExternalPtr, R_MakeUnwindCont, R_NilValue, R_SetExternalPtrProtected,
+    R_SetExternalPtrTag, R_UnwindProtect, Rboolean, Rf_protect, Rf_unprotect,
 };
-use std::{any::Any, fmt::Debug};
+use std::{any::Any, cell::RefCell, collections::HashMap, fmt::Debug};
 /// Wrapper for creating R objects containing any Rust object.
 ///
 /// ```
@@ -119,6 +123,31 @@ impl<T: 'static> DerefMut for ExternalPtr<T> {
     }
 }
 
+/// The pointee of an external pointer that does not own a boxed `T`,
+/// i.e. borrowed `'static` data or a foreign pointer.
+struct Unowned<T> {
+    ptr: *mut T,
+    mutable: bool,
+    release: Option<Box<dyn FnOnce(*mut T)>>,
+}
+
+impl<T> Drop for Unowned<T> {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release(self.ptr);
+        }
+    }
+}
+
+fn type_name_tag<T>() -> Robj {
+    std::any::type_name::<T>().into()
+}
+
+/// Check that the pointee of an external pointer made by extendr is a `T`.
+fn holds<T: 'static>(pointee: &dyn Any) -> bool {
+    pointee.is::<T>() || pointee.is::<Unowned<T>>()
+}
+
 impl<T: 'static> ExternalPtr<T> {
     /// Construct an external pointer object from any type T.
     /// In this case, the R object owns the data and will drop the Rust object
@@ -127,9 +156,71 @@ impl<T: 'static> ExternalPtr<T> {
     /// An ExternalPtr behaves like a Box except that the information is
     /// tracked by a R object.
     pub fn new(val: T) -> Self {
+        Self::from_pointee(type_name_tag::<T>(), Box::new(val))
+    }
+
+    /// Construct an external pointer to `'static` data, without taking ownership.
+    ///
+    /// The data is never dropped, and the pointer is read-only:
+    /// [`try_addr_mut`](Self::try_addr_mut) returns an error.
+    ///
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     static TABLE: [i32; 3] = [1, 2, 3];
+    ///     let extptr = ExternalPtr::from_static(&TABLE);
+    ///     assert_eq!(extptr[1], 2);
+    /// }
+    /// ```
+    pub fn from_static(val: &'static T) -> Self {
+        Self::from_pointee(
+            type_name_tag::<T>(),
+            Box::new(Unowned {
+                ptr: std::ptr::from_ref(val).cast_mut(),
+                mutable: false,
+                release: None,
+            }),
+        )
+    }
+
+    /// Construct a mutable external pointer to `'static` data, without taking ownership.
+    ///
+    /// The data is never dropped.
+    pub fn from_static_mut(val: &'static mut T) -> Self {
+        Self::from_pointee(
+            type_name_tag::<T>(),
+            Box::new(Unowned {
+                ptr: std::ptr::from_mut(val),
+                mutable: true,
+                release: None,
+            }),
+        )
+    }
+
+    /// Construct an external pointer to data owned elsewhere, typically by a C library.
+    ///
+    /// `release` is called with `ptr` when the pointer is garbage collected or
+    /// [closed](Self::close), for example to call the library's `free` function.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null, or valid for reads and writes of `T` until `release` is called.
+    pub unsafe fn from_raw<F>(ptr: *mut T, release: F) -> Self
+    where
+        F: FnOnce(*mut T) + 'static,
+    {
+        Self::from_pointee(
+            type_name_tag::<T>(),
+            Box::new(Unowned {
+                ptr,
+                mutable: true,
+                release: Some(Box::new(release)),
+            }),
+        )
+    }
+
+    fn from_pointee(tag: Robj, boxed: Box<dyn Any>) -> Self {
         single_threaded(|| unsafe {
-            // This allocates some memory for our object and moves the object into it.
-            let boxed: Box<dyn Any> = Box::new(val);
             let boxed: Box<Box<dyn Any>> = Box::new(boxed);
 
             // This constructs an external pointer to our boxed data.
@@ -137,10 +228,9 @@ impl<T: 'static> ExternalPtr<T> {
             let robj = {
                 let boxed_ptr = Box::into_raw(boxed);
                 let prot = Robj::from(());
-                let type_name: Robj = std::any::type_name::<T>().into();
 
                 Robj::from_sexp(single_threaded(|| {
-                    R_MakeExternalPtr(boxed_ptr.cast(), type_name.get(), prot.get())
+                    R_MakeExternalPtr(boxed_ptr.cast(), tag.get(), prot.get())
                 }))
             };
 
@@ -151,12 +241,17 @@ impl<T: 'static> ExternalPtr<T> {
                     // Free the `tag`, which is the type-name
                     R_SetExternalPtrTag(x, R_NilValue);
 
-                    // Convert the pointer to a box and drop it implictly.
-                    // This frees up the memory we have used and calls the "T::drop" method if there is one.
-                    drop(Box::from_raw(ptr));
+                    // The pointer is `NULL` if it has been closed already.
+                    if ptr.is_null() {
+                        return;
+                    }
 
                     // Now set the pointer in ExternalPTR to C `NULL`
                     R_ClearExternalPtr(x);
+
+                    // Convert the pointer to a box and drop it implictly.
+                    // This frees up the memory we have used and calls the "T::drop" method if there is one.
+                    drop(Box::from_raw(ptr));
                 }
             }
 
@@ -171,9 +266,8 @@ impl<T: 'static> ExternalPtr<T> {
         })
     }
 
-    // TODO: make a constructor for references?
-
-    /// Get the "tag" of an external pointer. This is the type name in the common case.
+    /// Get the "tag" of an external pointer. This is the type name in the common case,
+    /// or the type id and ABI for [tagged](Self::new_tagged) pointers.
     pub fn tag(&self) -> Robj {
         unsafe { Robj::from_sexp(R_ExternalPtrTag(self.robj.get())) }
     }
@@ -183,6 +277,68 @@ impl<T: 'static> ExternalPtr<T> {
         unsafe { Robj::from_sexp(R_ExternalPtrProtected(self.robj.get())) }
     }
 
+    /// Set the "protected" field of an external pointer.
+    ///
+    /// R keeps `prot` alive for as long as the external pointer is alive,
+    /// for example the R object that owns the memory the pointer refers to.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut extptr = ExternalPtr::new(1);
+    ///     extptr.set_protected(r!([1, 2, 3]));
+    ///     assert_eq!(extptr.protected(), r!([1, 2, 3]));
+    /// }
+    /// ```
+    pub fn set_protected<P: Into<Robj>>(&mut self, prot: P) {
+        let prot = prot.into();
+        single_threaded(|| unsafe { R_SetExternalPtrProtected(self.robj.get_mut(), prot.get()) });
+    }
+
+    /// Drop the Rust object now, rather than when R garbage collects the pointer.
+    ///
+    /// The address of the external pointer is set to C `NULL`, in every copy of it,
+    /// so later access returns [`Error::ExpectedExternalNonNullPtr`].
+    /// Closing a closed pointer does nothing.
+    ///
+    /// # Safety
+    ///
+    /// Copies of the pointer, in Rust or in R, share the object. No reference
+    /// to it, obtained through `Deref`, [`try_addr`](Self::try_addr) or the
+    /// like from this pointer or any copy of it, may be alive when it is closed
+    /// or used afterwards.
+    ///
+    /// ## Panics
+    ///
+    /// When the object is borrowed by a running `#[extendr]` method.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut extptr = ExternalPtr::new(String::from("file handle"));
+    ///     let copy = extptr.clone();
+    ///     unsafe { extptr.close() };
+    ///     assert!(copy.is_closed());
+    ///     assert!(copy.try_addr().is_err());
+    /// }
+    /// ```
+    pub unsafe fn close(&mut self) {
+        single_threaded(|| unsafe {
+            let sexp = self.robj.get_mut();
+            let ptr = R_ExternalPtrAddr(sexp).cast::<Box<dyn Any>>();
+            if !ptr.is_null() {
+                let borrowed =
+                    BORROWS.with(|borrows| borrows.borrow().states.contains_key(&(ptr as usize)));
+                assert!(!borrowed, "cannot close an external pointer that is in use");
+                R_ClearExternalPtr(sexp);
+                drop(Box::from_raw(ptr));
+            }
+        })
+    }
+
+    /// Return true if the pointer has been [closed](Self::close) or finalized.
+    pub fn is_closed(&self) -> bool {
+        unsafe { R_ExternalPtrAddr(self.robj.get()).is_null() }
+    }
+
     /// Get the "address" field of an external pointer.
     /// Normally, we will use Deref to do this.
     ///
@@ -198,7 +354,7 @@ impl<T: 'static> ExternalPtr<T> {
     ///
     /// ## Panics
     ///
-    /// When the underlying pointer is C `NULL`.
+    /// When the underlying pointer is C `NULL`, or points to read-only data.
     pub fn addr_mut(&mut self) -> &mut T {
         self.try_addr_mut().unwrap()
     }
@@ -206,33 +362,255 @@ impl<T: 'static> ExternalPtr<T> {
     /// Get the "address" field of an external pointer.
     /// Normally, we will use Deref to do this.
     ///
-    /// ## Panics
-    ///
-    /// When the underlying pointer is C `NULL`.
+    /// Returns an error when the underlying pointer is C `NULL`, or does not hold a `T`.
     pub fn try_addr(&self) -> Result<&T> {
-        unsafe {
+        let null_ptr = || Error::ExpectedExternalNonNullPtr(self.robj.clone());
+        let pointee = unsafe {
             R_ExternalPtrAddr(self.robj.get())
                 .cast::<Box<dyn Any>>()
                 .as_ref()
-                .ok_or_else(|| Error::ExpectedExternalNonNullPtr(self.robj.clone()))
-                .map(|x| x.downcast_ref::<T>().unwrap())
+                .ok_or_else(null_ptr)?
+        };
+        if let Some(value) = pointee.downcast_ref::<T>() {
+            return Ok(value);
+        }
+        if let Some(unowned) = pointee.downcast_ref::<Unowned<T>>() {
+            return unsafe { unowned.ptr.as_ref().ok_or_else(null_ptr) };
         }
+        // A tagged pointer made by another library, whose `TypeId`s differ.
+        if type_tag::is_tagged_as::<T>(&self.tag()) {
+            return unsafe { Ok(&*(&**pointee as *const dyn Any).cast::<T>()) };
+        }
+        Err(self.wrong_type())
     }
 
     /// Get the "address" field of an external pointer as a mutable reference.
     /// Normally, we will use DerefMut to do this.
     ///
-    /// ## Panics
-    ///
-    /// When the underlying pointer is C `NULL`.
+    /// Returns an error when the underlying pointer is C `NULL`, does not hold
+    /// a `T`, or points to read-only data.
     pub fn try_addr_mut(&mut self) -> Result<&mut T> {
-        unsafe {
+        let robj = self.robj.clone();
+        let null_ptr = || Error::ExpectedExternalNonNullPtr(robj.clone());
+        let tagged = type_tag::is_tagged_as::<T>(&self.tag());
+        let pointee = unsafe {
             R_ExternalPtrAddr(self.robj.get_mut())
                 .cast::<Box<dyn Any>>()
                 .as_mut()
-                .ok_or_else(|| Error::ExpectedExternalNonNullPtr(self.robj.clone()))
-                .map(|x| x.downcast_mut::<T>().unwrap())
+                .ok_or_else(null_ptr)?
+        };
+        if pointee.is::<T>() {
+            return Ok(pointee.downcast_mut::<T>().unwrap());
         }
+        if let Some(unowned) = pointee.downcast_mut::<Unowned<T>>() {
+            if !unowned.mutable {
+                return Err(Error::Other(format!(
+                    "external pointer to {} is read-only",
+                    std::any::type_name::<T>()
+                )));
+            }
+            return unsafe { unowned.ptr.as_mut().ok_or_else(null_ptr) };
+        }
+        // A tagged pointer made by another library, whose `TypeId`s differ.
+        if tagged {
+            return unsafe { Ok(&mut *(&mut **pointee as *mut dyn Any).cast::<T>()) };
+        }
+        Err(self.wrong_type())
+    }
+
+    fn wrong_type(&self) -> Error {
+        Error::ExpectedExternalPtrType(self.robj.clone(), std::any::type_name::<T>().to_string())
+    }
+}
+
+impl<T: TypeTag> ExternalPtr<T> {
+    /// Construct an external pointer that owns `val`, tagged with the
+    /// [type id](TypeTag::TYPE_ID) and ABI of `T`, so that other packages
+    /// can use it. See [`type_tag`](crate::wrapper::type_tag).
+    ///
+    /// ## Panics
+    ///
+    /// When another type of this library uses the same type id.
+    pub fn new_tagged(val: T) -> Self {
+        let abi = register_type::<T>().unwrap();
+        Self::from_pointee(abi.to_tag(), Box::new(val))
+    }
+
+    /// Use an external pointer made by [`new_tagged`](Self::new_tagged), possibly by another package.
+    ///
+    /// Returns an error if the pointer is tagged with another type id, or with
+    /// an incompatible ABI. Untagged pointers are checked like [`TryFrom`] does.
+    pub fn try_from_tagged(robj: &Robj) -> Result<&Self> {
+        Self::check_tag(robj)?;
+        unsafe { Ok(std::mem::transmute::<&Robj, &ExternalPtr<T>>(robj)) }
+    }
+
+    /// Use an external pointer made by [`new_tagged`](Self::new_tagged), possibly by another package.
+    ///
+    /// See [`try_from_tagged`](Self::try_from_tagged).
+    pub fn try_from_tagged_mut(robj: &mut Robj) -> Result<&mut Self> {
+        Self::check_tag(robj)?;
+        unsafe { Ok(std::mem::transmute::<&mut Robj, &mut ExternalPtr<T>>(robj)) }
+    }
+
+    // Check the tag of a tagged pointer, and fall back to `TryFrom` for anything else.
+    fn check_tag(robj: &Robj) -> Result<()> {
+        let found = robj
+            .is_external_pointer()
+            .then(|| unsafe { TypeAbi::from_tag(&robj.external_ptr_tag()) })
+            .flatten();
+        let Some(found) = found else {
+            return <&Self>::try_from(robj).map(|_| ());
+        };
+        register_type::<T>()?.check(&found, robj)?;
+        if unsafe { robj.external_ptr_addr::<Box<dyn Any>>().is_null() } {
+            return Err(Error::ExpectedExternalNonNullPtr(robj.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Borrows {
+    // The borrow state of external pointers, by address: the number of
+    // shared borrows, or -1 while borrowed mutably.
+    states: HashMap<usize, isize>,
+    // The borrows in the order they were made, for releasing the borrows
+    // of a call that R unwinds out of.
+    stack: Vec<(usize, bool)>,
+}
+
+impl Borrows {
+    fn release(&mut self, addr: usize, mutable: bool) {
+        if let Some(state) = self.states.get_mut(&addr) {
+            if mutable {
+                *state = 0;
+            } else {
+                *state -= 1;
+            }
+            if *state == 0 {
+                self.states.remove(&addr);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static BORROWS: RefCell<Borrows> = RefCell::new(Borrows::default());
+}
+
+/// A borrow of the object of an external pointer, for the duration of a call.
+///
+/// The wrappers generated by `#[extendr]` borrow each reference argument,
+/// including `self`, like a [`RefCell`] would. Borrowing an object mutably
+/// while it is borrowed, e.g. when R code called back from a `&mut self`
+/// method calls another method of the same object, is an error rather than
+/// undefined behaviour.
+#[doc(hidden)]
+pub struct BorrowGuard {
+    addr: usize,
+    mutable: bool,
+}
+
+impl BorrowGuard {
+    /// Borrow the object of an external pointer, named `name` in errors.
+    /// Other R objects are not tracked, and return `None`.
+    pub fn new(robj: &Robj, mutable: bool, name: &str) -> Result<Option<Self>> {
+        if !robj.is_external_pointer() {
+            return Ok(None);
+        }
+        let addr = unsafe { R_ExternalPtrAddr(robj.get()) } as usize;
+        if addr == 0 {
+            return Ok(None);
+        }
+        BORROWS.with(|borrows| {
+            let mut borrows = borrows.borrow_mut();
+            let state = borrows.states.entry(addr).or_insert(0);
+            match (*state, mutable) {
+                (0, true) => *state = -1,
+                (n, false) if n >= 0 => *state += 1,
+                (_, true) => {
+                    return Err(Error::Other(format!(
+                        "cannot borrow `{name}` mutably, as it is already in use"
+                    )))
+                }
+                (_, false) => {
+                    return Err(Error::Other(format!(
+                        "cannot borrow `{name}`, as it is already borrowed mutably"
+                    )))
+                }
+            }
+            borrows.stack.push((addr, mutable));
+            Ok(Some(BorrowGuard { addr, mutable }))
+        })
+    }
+
+    /// Run `f`, releasing the borrows made in it if R unwinds out of it,
+    /// e.g. when it raises an R error, which skips the guards' destructors.
+    pub fn scope<T, F: FnOnce() -> T>(f: F) -> T {
+        struct Scope<T, F> {
+            f: Option<F>,
+            result: Option<std::thread::Result<T>>,
+        }
+
+        unsafe extern "C" fn body<T, F: FnOnce() -> T>(data: *mut std::ffi::c_void) -> SEXP {
+            let scope = &mut *(data as *mut Scope<T, F>);
+            if let Some(f) = scope.f.take() {
+                // Panics must not unwind through R.
+                scope.result = Some(std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)));
+            }
+            R_NilValue
+        }
+
+        unsafe extern "C" fn cleanup(data: *mut std::ffi::c_void, jump: Rboolean) {
+            if jump == Rboolean::FALSE {
+                return;
+            }
+            let mark = *(data as *const usize);
+            BORROWS.with(|borrows| {
+                let mut borrows = borrows.borrow_mut();
+                let mark = mark.min(borrows.stack.len());
+                let skipped = borrows.stack.split_off(mark);
+                for (addr, mutable) in skipped.into_iter().rev() {
+                    borrows.release(addr, mutable);
+                }
+            });
+        }
+
+        let mark = BORROWS.with(|borrows| borrows.borrow().stack.len());
+        let mut scope = Scope {
+            f: Some(f),
+            result: None,
+        };
+        single_threaded(|| unsafe {
+            let cont = Rf_protect(R_MakeUnwindCont());
+            R_UnwindProtect(
+                Some(body::<T, F>),
+                &mut scope as *mut Scope<T, F> as *mut std::ffi::c_void,
+                Some(cleanup),
+                &mark as *const usize as *mut std::ffi::c_void,
+                cont,
+            );
+            Rf_unprotect(1);
+        });
+        match scope.result {
+            Some(Ok(value)) => value,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => unreachable!("the scope always runs its function"),
+        }
+    }
+}
+
+impl Drop for BorrowGuard {
+    fn drop(&mut self) {
+        BORROWS.with(|borrows| {
+            let mut borrows = borrows.borrow_mut();
+            let entry = (self.addr, self.mutable);
+            if let Some(i) = borrows.stack.iter().rposition(|&e| e == entry) {
+                borrows.stack.remove(i);
+            }
+            borrows.release(self.addr, self.mutable);
+        });
     }
 }
 
@@ -253,7 +631,7 @@ impl<T: 'static> TryFrom<&Robj> for &ExternalPtr<T> {
                 .ok_or_else(|| Error::ExpectedExternalNonNullPtr(value.clone()))?
         };
 
-        if boxed_ptr.downcast_ref::<T>().is_none() {
+        if !holds::<T>(&**boxed_ptr) {
             return Err(Error::ExpectedExternalPtrType(
                 value.clone(),
                 std::any::type_name::<T>().to_string(),
@@ -281,7 +659,7 @@ impl<T: 'static> TryFrom<&mut Robj> for &mut ExternalPtr<T> {
                 .ok_or_else(|| Error::ExpectedExternalNonNullPtr(value.clone()))?
         };
 
-        if boxed_ptr.downcast_ref::<T>().is_none() {
+        if !holds::<T>(&**boxed_ptr) {
             return Err(Error::ExpectedExternalPtrType(
                 value.clone(),
                 std::any::type_name::<T>().to_string(),
@@ -315,6 +693,12 @@ impl<T> From<ExternalPtr<T>> for Robj {
     }
 }
 
+impl<T> From<&ExternalPtr<T>> for Robj {
+    fn from(val: &ExternalPtr<T>) -> Self {
+        val.robj.clone()
+    }
+}
+
 impl<T> From<Option<ExternalPtr<T>>> for Robj {
     fn from(value: Option<ExternalPtr<T>>) -> Self {
         match value {
@@ -382,6 +766,69 @@ mod tests {
         })
     }
 
+    struct Tagged(i32);
+
+    // Stands in for `Tagged` as defined by another library.
+    struct Foreign(#[allow(dead_code)] i32);
+
+    impl TypeTag for Tagged {
+        const TYPE_ID: &'static str = "extendr_api::Tagged/v1";
+    }
+
+    #[test]
+    fn tagged_externalptr_abi() -> std::result::Result<(), Box<dyn Error>> {
+        with_r(|| {
+            let abi = TypeAbi::of::<Tagged>();
+            let robj: Robj = ExternalPtr::new_tagged(Tagged(1)).into();
+            let tagged = ExternalPtr::<Tagged>::try_from_tagged(&robj).unwrap();
+            assert_eq!(TypeAbi::from_tag(&tagged.tag()), Some(abi.clone()));
+            assert_eq!(tagged.0, 1);
+
+            // A pointer made by a build with another rustc.
+            let other_rustc = TypeAbi {
+                rustc: "rustc 0.0.0".to_string(),
+                ..abi.clone()
+            };
+            let robj: Robj =
+                ExternalPtr::<Tagged>::from_pointee(other_rustc.to_tag(), Box::new(Tagged(2)))
+                    .into();
+            assert!(matches!(
+                ExternalPtr::<Tagged>::try_from_tagged(&robj),
+                Err(crate::Error::IncompatibleExternalPtrType(..))
+            ));
+
+            // A pointer to another type.
+            let other_type = TypeAbi {
+                type_id: "extendr_api::Other/v1".to_string(),
+                ..abi
+            };
+            let robj: Robj =
+                ExternalPtr::<Tagged>::from_pointee(other_type.to_tag(), Box::new(Tagged(3)))
+                    .into();
+            assert!(matches!(
+                ExternalPtr::<Tagged>::try_from_tagged(&robj),
+                Err(crate::Error::ExpectedExternalPtrType(..))
+            ));
+
+            // A pointer made by another library, which defines its own `Tagged`.
+            let robj: Robj = ExternalPtr::<Tagged>::from_pointee(
+                TypeAbi::of::<Tagged>().to_tag(),
+                Box::new(Foreign(4)),
+            )
+            .into();
+            assert_eq!(ExternalPtr::<Tagged>::try_from_tagged(&robj)?.0, 4);
+
+            // A type with the same layout is not the same type.
+            let robj: Robj = ExternalPtr::new_tagged(Tagged(5)).into();
+            let same_layout = unsafe { std::mem::transmute::<&Robj, &ExternalPtr<Foreign>>(&robj) };
+            assert!(matches!(
+                same_layout.try_addr(),
+                Err(crate::Error::ExpectedExternalPtrType(..))
+            ));
+            Ok(())
+        })
+    }
+
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
     struct Wrapper(i32);
 
@@ -0,0 +1,189 @@
+use super::*;
+use extendr_ffi::{dataptr, SEXPTYPE::REALSXP};
+use std::iter::FromIterator;
+
+/// The `NA` value of `bit64::integer64` vectors.
+pub const NA_INTEGER64: i64 = i64::MIN;
+
+/// A wrapper for 64-bit integer vectors of class `integer64`, as used by the
+/// [bit64](https://cran.r-project.org/package=bit64) package.
+///
+/// R has no 64-bit integers; `bit64` stores the bits of each `i64` in a
+/// double vector, with [`NA_INTEGER64`] for `NA`. Unlike converting `i64`
+/// to doubles, this is lossless.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let big = 2_i64.pow(53) + 1;
+///     let vec = Integers64::from_values([Some(big), None]);
+///     assert_eq!(vec.inherits("integer64"), true);
+///     assert_eq!(vec.elt(0), Some(big));
+///     assert_eq!(vec.elt(1), None);
+///     assert_eq!(vec.as_slice(), &[big, NA_INTEGER64]);
+///
+///     let robj: Robj = vec.into();
+///     assert_eq!(Vec::<Option<i64>>::try_from(&robj)?, vec![Some(big), None]);
+///     assert_eq!(i64::try_from(&robj).is_err(), true);
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct Integers64 {
+    pub(crate) robj: Robj,
+}
+
+impl Attributes for Integers64 {}
+
+impl Integers64 {
+    /// Create a vector of `len` zeros.
+    pub fn new(len: usize) -> Self {
+        Self::from_values((0..len).map(|_| 0))
+    }
+
+    /// Create a vector of `len` `NA`s.
+    pub fn new_with_na(len: usize) -> Self {
+        Self::from_values((0..len).map(|_| None))
+    }
+
+    /// Create a vector from values, where `None` is `NA`.
+    pub fn from_values<V>(values: V) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: Into<Option<i64>>,
+    {
+        single_threaded(|| {
+            let values = values.into_iter();
+            let mut robj = Robj::alloc_vector(REALSXP, values.len());
+            let dest = unsafe {
+                std::slice::from_raw_parts_mut(dataptr(robj.get_mut()) as *mut i64, robj.len())
+            };
+            for (d, v) in dest.iter_mut().zip(values) {
+                *d = v.into().unwrap_or(NA_INTEGER64);
+            }
+            robj.set_class(["integer64"]).unwrap();
+            Self { robj }
+        })
+    }
+
+    /// Get a single element, or `None` if it is `NA` or out of bounds.
+    pub fn elt(&self, index: usize) -> Option<i64> {
+        self.as_slice()
+            .get(index)
+            .copied()
+            .filter(|&v| v != NA_INTEGER64)
+    }
+
+    /// Set a single element, where `None` is `NA`.
+    pub fn set_elt(&mut self, index: usize, val: Option<i64>) {
+        self.as_mut_slice()[index] = val.unwrap_or(NA_INTEGER64);
+    }
+
+    /// Return an iterator over the elements, where `None` is `NA`.
+    pub fn iter(&self) -> impl Iterator<Item = Option<i64>> + '_ {
+        self.as_slice()
+            .iter()
+            .map(|&v| if v == NA_INTEGER64 { None } else { Some(v) })
+    }
+
+    /// The raw values, with `NA` as [`NA_INTEGER64`].
+    pub fn as_slice(&self) -> &[i64] {
+        unsafe { std::slice::from_raw_parts(dataptr(self.get()) as *const i64, self.len()) }
+    }
+
+    /// The raw values, with `NA` as [`NA_INTEGER64`].
+    pub fn as_mut_slice(&mut self) -> &mut [i64] {
+        unsafe { std::slice::from_raw_parts_mut(dataptr(self.get_mut()) as *mut i64, self.len()) }
+    }
+}
+
+impl Default for Integers64 {
+    fn default() -> Self {
+        Integers64::new(0)
+    }
+}
+
+impl Deref for Integers64 {
+    type Target = [i64];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for Integers64 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl FromIterator<i64> for Integers64 {
+    fn from_iter<T: IntoIterator<Item = i64>>(iter: T) -> Self {
+        Self::from_values(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl FromIterator<Option<i64>> for Integers64 {
+    fn from_iter<T: IntoIterator<Item = Option<i64>>>(iter: T) -> Self {
+        Self::from_values(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl From<Vec<i64>> for Integers64 {
+    fn from(values: Vec<i64>) -> Self {
+        Self::from_values(values)
+    }
+}
+
+impl From<Vec<Option<i64>>> for Integers64 {
+    fn from(values: Vec<Option<i64>>) -> Self {
+        Self::from_values(values)
+    }
+}
+
+impl From<Integers64> for Robj {
+    fn from(val: Integers64) -> Self {
+        val.robj
+    }
+}
+
+impl From<&Integers64> for Robj {
+    fn from(val: &Integers64) -> Self {
+        val.robj.clone()
+    }
+}
+
+impl From<Option<Integers64>> for Robj {
+    fn from(value: Option<Integers64>) -> Self {
+        match value {
+            None => nil_value(),
+            Some(value) => value.into(),
+        }
+    }
+}
+
+impl TryFrom<&Robj> for Integers64 {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.is_integer64() {
+            Ok(Self { robj: robj.clone() })
+        } else {
+            Err(Error::ExpectedInteger64(robj.clone()))
+        }
+    }
+}
+
+impl TryFrom<Robj> for Integers64 {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl std::fmt::Debug for Integers64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Integers64")?;
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
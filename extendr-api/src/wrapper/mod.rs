@@ -7,12 +7,14 @@ use crate::*;
 pub mod altrep;
 pub mod complexes;
 pub mod dataframe;
+pub mod datetime;
 pub mod doubles;
 pub mod environment;
 pub mod expr;
 pub mod externalptr;
 pub mod function;
 pub mod integers;
+pub mod integers64;
 pub mod lang;
 pub mod list;
 pub mod logicals;
@@ -25,11 +27,16 @@ pub mod promise;
 pub mod raw;
 pub mod rstr;
 pub mod s4;
+pub mod shared;
 pub mod strings;
+pub mod subset;
 pub mod symbol;
+pub mod type_tag;
+pub mod vector_ops;
+pub mod weakref;
 pub mod wrapper_macros;
 
-pub use self::rstr::Rstr;
+pub use self::rstr::{Encoding, Rstr, RstrCache};
 #[cfg(use_r_altlist)]
 pub use altrep::AltListImpl;
 pub use altrep::{
@@ -38,12 +45,14 @@ pub use altrep::{
 };
 pub use complexes::Complexes;
 pub use dataframe::{Dataframe, IntoDataFrameRow};
+pub use datetime::{DateTimes, Dates, Difftimes, TimeUnits};
 pub use doubles::Doubles;
 pub use environment::{EnvIter, Environment};
 pub use expr::Expressions;
-pub use externalptr::ExternalPtr;
+pub use externalptr::{BorrowGuard, ExternalPtr};
 pub use function::Function;
 pub use integers::Integers;
+pub use integers64::{Integers64, NA_INTEGER64};
 pub use lang::Language;
 pub use list::{FromList, List, ListIter};
 pub use logicals::Logicals;
@@ -54,6 +63,11 @@ pub use primitive::Primitive;
 pub use promise::Promise;
 pub use raw::Raw;
 pub use s4::S4;
+pub use shared::Shared;
 pub use strings::Strings;
+pub use subset::{Subscript, Subset};
 pub use symbol::Symbol;
+pub use type_tag::{register_type, registered_type, TypeAbi, TypeTag};
+pub use vector_ops::Comparisons;
+pub use weakref::WeakRef;
 pub use wrapper_macros::*;
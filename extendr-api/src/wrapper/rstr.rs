@@ -1,5 +1,12 @@
 use super::*;
-use extendr_ffi::{R_BlankString, R_NaString, R_NilValue, Rf_xlength, R_CHAR, SEXPTYPE, TYPEOF};
+use extendr_ffi::{
+    cetype_t, vmaxget, vmaxset, R_BlankString, R_NaString, R_NilValue, Rf_getCharCE,
+    Rf_translateCharUTF8, Rf_xlength, R_CHAR, SEXPTYPE, TYPEOF,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+
 /// Wrapper for creating CHARSXP objects.
 /// These are used only as the contents of a character
 /// vector.
@@ -17,22 +24,68 @@ pub struct Rstr {
     pub(crate) robj: Robj,
 }
 
+/// The encoding of a character object, as marked by R.
+///
+/// ASCII strings are never marked, and are always [`Encoding::Native`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The encoding of the current locale, which is usually UTF-8.
+    Native,
+    Utf8,
+    Latin1,
+    /// Raw bytes, which R never translates.
+    Bytes,
+}
+
+/// Returns the bytes of a `CHARSXP`, without the trailing null.
+pub(crate) unsafe fn charsxp_to_bytes(charsxp: SEXP) -> &'static [u8] {
+    let length = Rf_xlength(charsxp);
+    std::slice::from_raw_parts(R_CHAR(charsxp).cast(), length.try_into().unwrap())
+}
+
+pub(crate) unsafe fn charsxp_encoding(charsxp: SEXP) -> Encoding {
+    match Rf_getCharCE(charsxp) {
+        cetype_t::CE_UTF8 => Encoding::Utf8,
+        cetype_t::CE_LATIN1 => Encoding::Latin1,
+        cetype_t::CE_BYTES => Encoding::Bytes,
+        _ => Encoding::Native,
+    }
+}
+
 /// Returns a rust string-slice based on the provided `SEXP`, which is of type
 /// [`SEXPTYPE::CHARSXP`]. Note that the length of a `CHARSXP` is exactly
 /// the number of non-null bytes in said R character.
+///
+/// # Panics
+/// If the string is not valid UTF-8, e.g. when it is marked as latin1.
+/// Use [`Rstr::to_utf8`] to translate such strings.
 pub(crate) unsafe fn charsxp_to_str(charsxp: SEXP) -> Option<&'static str> {
-    assert_eq!(TYPEOF(charsxp), SEXPTYPE::CHARSXP);
     if charsxp == R_NilValue {
-        None
-    } else if charsxp == R_NaString {
-        Some(<&str>::na())
+        return None;
+    }
+    match try_charsxp_to_str(charsxp) {
+        Ok(s) => Some(s),
+        Err(_) => panic!(
+            "{:?} string is not valid UTF-8, use Rstr::to_utf8() or Rstr::to_string_lossy()",
+            charsxp_encoding(charsxp)
+        ),
+    }
+}
+
+/// Like [`charsxp_to_str`], but returns [`Error::ExpectedUtf8`] for strings
+/// that are not valid UTF-8.
+pub(crate) unsafe fn try_charsxp_to_str(charsxp: SEXP) -> Result<&'static str> {
+    assert_eq!(TYPEOF(charsxp), SEXPTYPE::CHARSXP);
+    if charsxp == R_NaString {
+        return Ok(<&str>::na());
     } else if charsxp == R_BlankString {
-        Some("")
+        return Ok("");
+    }
+    let all_bytes = charsxp_to_bytes(charsxp);
+    if Rf_getCharCE(charsxp) == cetype_t::CE_UTF8 || all_bytes.is_ascii() {
+        Ok(std::str::from_utf8_unchecked(all_bytes))
     } else {
-        let length = Rf_xlength(charsxp);
-        let all_bytes =
-            std::slice::from_raw_parts(R_CHAR(charsxp).cast(), length.try_into().unwrap());
-        Some(std::str::from_utf8_unchecked(all_bytes))
+        std::str::from_utf8(all_bytes).map_err(|_| Error::ExpectedUtf8(Robj::from_sexp(charsxp)))
     }
 }
 
@@ -81,6 +134,82 @@ impl Rstr {
     pub fn as_str(&self) -> &str {
         self.into()
     }
+
+    /// Get the encoding R has marked the string with.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let latin1 = R!("iconv('café', 'UTF-8', 'latin1')")?;
+    ///     assert_eq!(latin1.as_char().unwrap().encoding(), Encoding::Latin1);
+    ///     assert_eq!(Rstr::from("café").encoding(), Encoding::Utf8);
+    ///     assert_eq!(Rstr::from("cafe").encoding(), Encoding::Native);
+    /// }
+    /// ```
+    pub fn encoding(&self) -> Encoding {
+        unsafe { charsxp_encoding(self.robj.get()) }
+    }
+
+    /// Get the bytes of the string, in its own encoding.
+    /// For `NA`, these are the bytes of `"NA"`.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { charsxp_to_bytes(self.robj.get()) }
+    }
+
+    /// Get the string as UTF-8, translating it from its encoding if necessary.
+    /// If the string is NA, then the special na_str() is returned.
+    ///
+    /// Returns an error for strings marked as `"bytes"`, and for strings
+    /// that are not valid in their encoding.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let latin1 = R!("iconv('café', 'UTF-8', 'latin1')")?;
+    ///     let latin1 = latin1.as_char().unwrap();
+    ///     assert_eq!(latin1.as_bytes(), b"caf\xe9");
+    ///     assert_eq!(latin1.to_utf8()?, "café");
+    ///
+    ///     let bytes = R!("x <- 'caf\\xe9'; Encoding(x) <- 'bytes'; x")?;
+    ///     assert!(bytes.as_char().unwrap().to_utf8().is_err());
+    /// }
+    /// ```
+    pub fn to_utf8(&self) -> Result<Cow<'_, str>> {
+        let charsxp = unsafe { self.robj.get() };
+        let bytes = self.as_bytes();
+        if self.is_na() {
+            return Ok(Cow::Borrowed(<&str>::na()));
+        }
+        match self.encoding() {
+            Encoding::Utf8 => return Ok(Cow::Borrowed(self.as_ref())),
+            _ if bytes.is_ascii() => return Ok(Cow::Borrowed(self.as_ref())),
+            Encoding::Bytes => return Err(Error::ExpectedUtf8(self.robj.clone())),
+            Encoding::Native | Encoding::Latin1 => {}
+        }
+        single_threaded(|| unsafe {
+            // R allocates the translation on the R_alloc stack, so release it here.
+            let vmax = vmaxget();
+            let translated =
+                catch_r_error(move || Rf_translateCharUTF8(charsxp) as SEXP).and_then(|ptr| {
+                    let ptr = ptr as *const std::os::raw::c_char;
+                    let utf8 = std::str::from_utf8(CStr::from_ptr(ptr).to_bytes())
+                        .map_err(|_| Error::ExpectedUtf8(self.robj.clone()))?;
+                    if ptr == R_CHAR(charsxp) {
+                        // Native strings in a UTF-8 locale are returned as they are.
+                        Ok(Cow::Borrowed(std::str::from_utf8_unchecked(bytes)))
+                    } else {
+                        Ok(Cow::Owned(utf8.to_string()))
+                    }
+                });
+            vmaxset(vmax);
+            translated
+        })
+    }
+
+    /// Get the string as UTF-8, like [`to_utf8`](Rstr::to_utf8), but replace
+    /// bytes that cannot be translated with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        self.to_utf8()
+            .unwrap_or_else(|_| String::from_utf8_lossy(self.as_bytes()))
+    }
 }
 
 impl AsRef<str> for Rstr {
@@ -176,16 +305,89 @@ impl std::fmt::Debug for Rstr {
         if self.is_na() {
             write!(f, "NA_CHARACTER")
         } else {
-            let s: &str = self.as_ref();
-            write!(f, "{:?}", s)
+            write!(f, "{:?}", self.to_string_lossy())
         }
     }
 }
 
 impl std::fmt::Display for Rstr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s: &str = self.as_ref();
-        write!(f, "{}", s)
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+/// A cache of character objects, for building many string vectors
+/// from a small set of values, such as the levels of a category.
+///
+/// R interns every string it makes, but finding a string in R's cache
+/// means hashing it again every time. An `RstrCache` looks strings up
+/// on the Rust side instead, and keeps its character objects protected
+/// for as long as it lives.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let mut cache = RstrCache::new();
+///     let first = Strings::from_values_cached(["a", "b", "a"], &mut cache);
+///     let second = Strings::from_values_cached(["b", "c"], &mut cache);
+///     assert_eq!(first, Strings::from_values(["a", "b", "a"]));
+///     assert_eq!(second, Strings::from_values(["b", "c"]));
+///     assert_eq!(cache.len(), 3);
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct RstrCache {
+    strings: HashMap<Box<str>, Rstr>,
+}
+
+impl RstrCache {
+    /// Make an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make an empty cache with room for `capacity` distinct strings.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            strings: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Get the character object of a string, making it if it is not in the cache.
+    /// NA strings are not cached.
+    pub fn intern(&mut self, s: &str) -> Rstr {
+        let sexp = self.intern_sexp(s);
+        Rstr {
+            robj: unsafe { Robj::from_sexp(sexp) },
+        }
+    }
+
+    // The character object of a string, protected by the cache for as long
+    // as the string stays in it. Lets callers fill vectors without cloning.
+    pub(crate) fn intern_sexp(&mut self, s: &str) -> SEXP {
+        if s.is_na() {
+            return unsafe { R_NaString };
+        }
+        if let Some(rstr) = self.strings.get(s) {
+            return unsafe { rstr.get() };
+        }
+        let rstr = Rstr::from(s);
+        let sexp = unsafe { rstr.get() };
+        self.strings.insert(s.into(), rstr);
+        sexp
+    }
+
+    /// The number of distinct strings in the cache.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Remove all strings, releasing their character objects.
+    pub fn clear(&mut self) {
+        self.strings.clear();
     }
 }
 
@@ -216,4 +418,45 @@ mod tests {
             assert_eq!(x.as_ref(), "xyz");
         }
     }
+
+    #[test]
+    fn test_rstr_encoding() {
+        test! {
+            let utf8 = Rstr::from("naïve");
+            assert_eq!(utf8.encoding(), Encoding::Utf8);
+            assert_eq!(utf8.as_bytes(), "naïve".as_bytes());
+            assert!(matches!(utf8.to_utf8()?, Cow::Borrowed("naïve")));
+
+            let latin1 = R!("iconv('naïve', 'UTF-8', 'latin1')")?;
+            let latin1 = latin1.as_char().unwrap();
+            assert_eq!(latin1.encoding(), Encoding::Latin1);
+            assert_eq!(latin1.as_bytes(), b"na\xefve");
+            assert_eq!(latin1.to_utf8()?, "naïve");
+            assert_eq!(latin1.to_string_lossy(), "naïve");
+            assert_eq!(format!("{}", latin1), "naïve");
+
+            let bytes = R!("x <- 'na\\xefve'; Encoding(x) <- 'bytes'; x")?;
+            let bytes = bytes.as_char().unwrap();
+            assert_eq!(bytes.encoding(), Encoding::Bytes);
+            assert!(bytes.to_utf8().is_err());
+            assert_eq!(bytes.to_string_lossy(), "na\u{fffd}ve");
+
+            assert_eq!(Rstr::na().to_utf8()?, <&str>::na());
+
+            let mixed = R!("c('naïve', iconv('naïve', 'UTF-8', 'latin1'))")?;
+            assert_eq!(Vec::<String>::try_from(mixed)?, ["naïve", "naïve"]);
+            let with_bytes = R!("x <- c('a', 'na\\xefve'); Encoding(x) <- 'bytes'; x")?;
+            assert!(matches!(
+                Vec::<String>::try_from(with_bytes.clone()),
+                Err(Error::ExpectedUtf8(_))
+            ));
+
+            // Borrowing a string that is not UTF-8 fails instead of panicking.
+            let latin1 = R!("iconv('naïve', 'UTF-8', 'latin1')")?;
+            assert!(matches!(<&str>::try_from(&latin1), Err(Error::ExpectedUtf8(_))));
+            assert_eq!(latin1.as_str(), None);
+            assert!(with_bytes.as_str_iter().is_none());
+            assert!(with_bytes.as_str_vector().is_none());
+        }
+    }
 }
@@ -0,0 +1,137 @@
+use super::*;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Wrapper for Rust objects shared between R and other threads, as an `Arc<Mutex<T>>`.
+///
+/// `#[extendr]` objects are owned by R, and can only be used on R's thread.
+/// A `Shared<T>` is stored in an external pointer too, but each copy of it
+/// holds a reference to the same object, which can be sent to other threads,
+/// and access is synchronised by the mutex.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let counter = Shared::new(0);
+///     let worker = counter.clone();
+///     std::thread::spawn(move || *worker.lock() += 1).join().unwrap();
+///
+///     let robj: Robj = counter.into();
+///     let counter = Shared::<i32>::try_from(&robj)?;
+///     assert_eq!(*counter.lock(), 1);
+/// }
+/// ```
+///
+/// Types marked `#[extendr(shared)]`, together with their `impl` block, are
+/// stored as a `Shared<T>`, and their methods lock the object for the call.
+///
+/// ```
+/// use extendr_api::prelude::*;
+///
+/// #[extendr(shared)]
+/// struct Counter {
+///     count: i32,
+/// }
+///
+/// #[extendr(shared)]
+/// impl Counter {
+///     fn increment(&mut self) -> i32 {
+///         self.count += 1;
+///         self.count
+///     }
+/// }
+///
+/// test! {
+///     let robj: Robj = Counter { count: 0 }.into();
+///     let counter = Shared::<Counter>::try_from(&robj)?;
+///     std::thread::spawn(move || counter.lock().count += 1).join().unwrap();
+/// }
+/// ```
+///
+/// As with any mutex, locking an object that the thread has locked already
+/// deadlocks, e.g. when a function that holds the lock calls R code that
+/// locks it again. Use [`try_lock`](Shared::try_lock) where that can happen.
+pub struct Shared<T> {
+    inner: Arc<Mutex<T>>,
+}
+
+impl<T: Send + 'static> Shared<T> {
+    /// Share `val`.
+    pub fn new(val: T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(val)),
+        }
+    }
+
+    /// Lock the object, blocking until it is available.
+    /// A panic while the object was locked does not poison it.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the object, or return an error if it is locked already.
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>> {
+        self.inner.try_lock().or_else(|err| match err {
+            std::sync::TryLockError::Poisoned(err) => Ok(err.into_inner()),
+            std::sync::TryLockError::WouldBlock => Err(Error::Other(format!(
+                "{} is locked",
+                std::any::type_name::<T>()
+            ))),
+        })
+    }
+
+    /// Get the shared object.
+    pub fn as_arc(&self) -> &Arc<Mutex<T>> {
+        &self.inner
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> From<Arc<Mutex<T>>> for Shared<T> {
+    fn from(inner: Arc<Mutex<T>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> From<Shared<T>> for Arc<Mutex<T>> {
+    fn from(shared: Shared<T>) -> Self {
+        shared.inner
+    }
+}
+
+impl<T: Send + 'static> From<Shared<T>> for Robj {
+    fn from(shared: Shared<T>) -> Self {
+        ExternalPtr::new(shared.inner).into()
+    }
+}
+
+impl<T: Send + 'static> TryFrom<&Robj> for Shared<T> {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let extptr: &ExternalPtr<Arc<Mutex<T>>> = robj.try_into()?;
+        Ok(Self {
+            inner: extptr.try_addr()?.clone(),
+        })
+    }
+}
+
+impl<T: Send + 'static> TryFrom<Robj> for Shared<T> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Shared").field(&self.inner).finish()
+    }
+}
@@ -2,9 +2,13 @@ use super::*;
 use extendr_ffi::{
     R_xlen_t, SET_STRING_ELT, STRING_ELT, STRING_IS_SORTED, STRING_NO_NA, STRING_PTR_RO,
 };
+use std::collections::HashMap;
 use std::convert::From;
 use std::iter::FromIterator;
 
+/// Inputs with fewer strings than this are validated on the calling thread.
+const PARALLEL_VALIDATION_THRESHOLD: usize = 1 << 16;
+
 #[derive(PartialEq, Clone)]
 pub struct Strings {
     pub(crate) robj: Robj,
@@ -65,6 +69,94 @@ impl Strings {
         })
     }
 
+    /// Make a string vector like [`from_values`](Strings::from_values), but look up
+    /// each distinct value only once. This is much faster for vectors with many
+    /// repeated values, such as categories.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let colours = ["red", "green", "red", "red", "green"];
+    ///     let strings = Strings::from_values_interned((0..1000).map(|i| colours[i % 5]));
+    ///     assert_eq!(strings.len(), 1000);
+    ///     assert_eq!(strings.elt(2), "red");
+    /// }
+    /// ```
+    pub fn from_values_interned<V>(values: V) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: AsRef<str>,
+    {
+        single_threaded(|| unsafe {
+            let values = values.into_iter();
+            let maxlen = values.len();
+            let mut robj = Robj::alloc_vector(SEXPTYPE::STRSXP, maxlen);
+            let sexp = robj.get_mut();
+            // The character objects are protected by the vector they are stored in.
+            let mut interned: HashMap<Box<str>, SEXP> = HashMap::new();
+            for (i, v) in values.take(maxlen).enumerate() {
+                let v = v.as_ref();
+                let ch = if v.is_na() {
+                    str_to_character(v)
+                } else if let Some(&ch) = interned.get(v) {
+                    ch
+                } else {
+                    let ch = str_to_character(v);
+                    interned.insert(v.into(), ch);
+                    ch
+                };
+                SET_STRING_ELT(sexp, i as R_xlen_t, ch);
+            }
+            Self { robj }
+        })
+    }
+
+    /// Make a string vector like [`from_values_interned`](Strings::from_values_interned),
+    /// but keep the distinct values in `cache` for the next vector.
+    pub fn from_values_cached<V>(values: V, cache: &mut RstrCache) -> Self
+    where
+        V: IntoIterator,
+        V::IntoIter: ExactSizeIterator,
+        V::Item: AsRef<str>,
+    {
+        single_threaded(|| unsafe {
+            let values = values.into_iter();
+            let maxlen = values.len();
+            let mut robj = Robj::alloc_vector(SEXPTYPE::STRSXP, maxlen);
+            let sexp = robj.get_mut();
+            for (i, v) in values.take(maxlen).enumerate() {
+                SET_STRING_ELT(sexp, i as R_xlen_t, cache.intern_sexp(v.as_ref()));
+            }
+            Self { robj }
+        })
+    }
+
+    /// Make a string vector from UTF-8 encoded bytes, with repeated values interned.
+    ///
+    /// Large inputs are validated on several threads before the vector is made.
+    /// Returns an error naming the first element that is not valid UTF-8.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let strings = Strings::from_utf8(&[b"caf\xc3\xa9".as_slice(), b"tea"])?;
+    ///     assert_eq!(strings, Strings::from_values(["café", "tea"]));
+    ///     assert!(Strings::from_utf8(&[b"caf\xe9"]).is_err());
+    /// }
+    /// ```
+    pub fn from_utf8<B: AsRef<[u8]> + Sync>(values: &[B]) -> Result<Self> {
+        if let Some((i, err)) = find_invalid_utf8(values) {
+            return Err(Error::Other(format!(
+                "element {} is not valid UTF-8: {}",
+                i + 1,
+                err
+            )));
+        }
+        Ok(Self::from_values_interned(values.iter().map(|v| {
+            // Safety: all values were validated above.
+            unsafe { std::str::from_utf8_unchecked(v.as_ref()) }
+        })))
+    }
+
     /// This is a relatively expensive operation, so use a variable if using this in a loop.
     pub fn as_slice<'a>(&self) -> &'a [Rstr] {
         unsafe {
@@ -112,6 +204,35 @@ impl Strings {
     }
 }
 
+/// Find the first value that is not valid UTF-8, splitting large inputs between threads.
+fn find_invalid_utf8<B: AsRef<[u8]> + Sync>(values: &[B]) -> Option<(usize, std::str::Utf8Error)> {
+    let find = |offset: usize, chunk: &[B]| {
+        chunk.iter().enumerate().find_map(|(i, v)| {
+            std::str::from_utf8(v.as_ref())
+                .err()
+                .map(|err| (offset + i, err))
+        })
+    };
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if threads == 1 || values.len() < PARALLEL_VALIDATION_THRESHOLD {
+        return find(0, values);
+    }
+
+    let chunk_size = values.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = values
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(c, chunk)| scope.spawn(move || find(c * chunk_size, chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .next()
+    })
+}
+
 impl Attributes for Strings {}
 
 impl<T: AsRef<str>> FromIterator<T> for Strings {
@@ -191,4 +312,38 @@ mod tests {
             assert_eq!(vec.len(), manual_vec.len());
         }
     }
+
+    #[test]
+    fn interned() {
+        use crate::na::CanBeNA;
+        test! {
+            let values = ["a", "b", <&str>::na(), "NA", "", "a"];
+            let expected = Strings::from_values(values);
+            assert_eq!(Strings::from_values_interned(values), expected);
+
+            let mut cache = RstrCache::new();
+            assert_eq!(Strings::from_values_cached(values, &mut cache), expected);
+            assert_eq!(Strings::from_values_cached(values, &mut cache), expected);
+            // NA is not cached, and "NA" is not NA.
+            assert_eq!(cache.len(), 4);
+            assert!(Strings::from_values_interned(values).elt(2).is_na());
+            assert!(!Strings::from_values_interned(values).elt(3).is_na());
+        }
+    }
+
+    #[test]
+    fn from_utf8() {
+        test! {
+            let mut values = vec![b"x".to_vec(); PARALLEL_VALIDATION_THRESHOLD * 2];
+            assert_eq!(Strings::from_utf8(&values)?.len(), values.len());
+
+            values[PARALLEL_VALIDATION_THRESHOLD + 3] = b"\xff".to_vec();
+            values[PARALLEL_VALIDATION_THRESHOLD + 5] = b"\xfe".to_vec();
+            assert_eq!(
+                find_invalid_utf8(&values).map(|(i, _)| i),
+                Some(PARALLEL_VALIDATION_THRESHOLD + 3)
+            );
+            assert!(Strings::from_utf8(&values).is_err());
+        }
+    }
 }
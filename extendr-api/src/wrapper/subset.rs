@@ -0,0 +1,753 @@
+//! Indexing and subsetting of vectors, lists and arrays, as R's `x[i]`, `x[[i]]` and `x[i] <- value`.
+//!
+//! A [`Subscript`] selects elements by zero-based position, by leaving positions
+//! out, by a logical mask or by name. Subscripts made from R objects, such as the
+//! arguments of an `#[extendr]` function, follow R's rules instead:
+//! positions are one-based and negative positions are left out.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let mut x = Doubles::from_values([1.0, 2.0, 3.0]);
+//!     x.set_names(["a", "b", "c"])?;
+//!
+//!     assert_eq!(Robj::from(x.subset([2, 0])?), R!("c(c = 3, a = 1)")?);
+//!     assert_eq!(Robj::from(x.subset(Subscript::exclude([1]))?), R!("c(a = 1, c = 3)")?);
+//!     assert_eq!(Robj::from(x.subset([true, false])?), R!("c(a = 1, c = 3)")?);
+//!     assert_eq!(Robj::from(x.subset(["b", "z"])?), R!("c(a = 1, b = 2, c = 3)[c('b', 'z')]")?);
+//!     assert_eq!(x.element("c")?, 3.0);
+//!
+//!     let index = R!("-1")?;
+//!     assert_eq!(Robj::from(x.subset(Subscript::try_from(&index)?)?), R!("c(b = 2, c = 3)")?);
+//!
+//!     x.set_subset(["d"], &[Rfloat::from(4.0)])?;
+//!     assert_eq!(Robj::from(x), R!("c(a = 1, b = 2, c = 3, d = 4)")?);
+//! }
+//! ```
+use super::scalar::{Rcplx, Rfloat, Rint};
+use super::*;
+use extendr_ffi::{
+    R_NaString, R_NilValue, R_xlen_t, Rf_copyMostAttrib, Rf_xlengthgets, SET_STRING_ELT,
+    SET_VECTOR_ELT, STRING_ELT, VECTOR_ELT,
+};
+use std::collections::HashMap;
+use std::ops::{Range, RangeFull, RangeInclusive};
+
+/// Subsetting, as R's `[`, `[[` and `[<-`.
+pub trait Subset: Sized {
+    /// The index: a [`Subscript`] for vectors and lists, or one per dimension for arrays.
+    type Index;
+
+    /// The type of a single element.
+    type Element;
+
+    /// Select elements, as R's `x[i]`.
+    ///
+    /// Names are selected with the elements, and other attributes, such as
+    /// the class, are kept. Positions and names that are not in a vector
+    /// select `NA`, or `NULL` for lists. For arrays, they are an error.
+    fn subset<I: Into<Self::Index>>(&self, index: I) -> Result<Self>;
+
+    /// Select a single element, as R's `x[[i]]`.
+    fn element<I: Into<Self::Index>>(&self, index: I) -> Result<Self::Element>;
+
+    /// Replace elements, as R's `x[i] <- values`.
+    ///
+    /// The values are recycled. Vectors and lists are extended, as in R,
+    /// for positions past the end, and for names that they do not have.
+    fn set_subset<I: Into<Self::Index>>(
+        &mut self,
+        index: I,
+        values: &[Self::Element],
+    ) -> Result<()>;
+}
+
+/// Zero-based positions of selected elements, with `None` for `NA`.
+type Selected = Vec<Option<usize>>;
+
+/// The position and name of an element added by assignment to a new name.
+type NewName = (usize, String);
+
+/// A selection of elements of a vector, for [`Subset`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscript(Selection);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selection {
+    All,
+    /// `None` selects `NA`.
+    Positions(Vec<Option<usize>>),
+    Exclude(Vec<usize>),
+    Logical(Vec<Rbool>),
+    /// `None` selects `NA`.
+    Names(Vec<Option<String>>),
+}
+
+impl Subscript {
+    /// Select every element, as R's empty index in `x[]` or `m[, j]`.
+    pub fn all() -> Self {
+        Subscript(Selection::All)
+    }
+
+    /// Select elements by zero-based position.
+    pub fn positions<I: IntoIterator<Item = usize>>(positions: I) -> Self {
+        Subscript(Selection::Positions(
+            positions.into_iter().map(Some).collect(),
+        ))
+    }
+
+    /// Select all but the elements at these zero-based positions, as R's `x[-i]`.
+    pub fn exclude<I: IntoIterator<Item = usize>>(positions: I) -> Self {
+        Subscript(Selection::Exclude(positions.into_iter().collect()))
+    }
+
+    /// Select the elements where `mask` is `TRUE`, recycling it as R does.
+    pub fn logical<I: IntoIterator<Item = Rbool>>(mask: I) -> Self {
+        Subscript(Selection::Logical(mask.into_iter().collect()))
+    }
+
+    /// Select elements by name.
+    pub fn names<I>(names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Subscript(Selection::Names(
+            names
+                .into_iter()
+                .map(|name| Some(name.as_ref().to_string()))
+                .collect(),
+        ))
+    }
+
+    /// Zero-based positions of the selected elements of a vector of length `len`.
+    /// Positions and names that are not in the vector are `None`.
+    fn select(&self, len: usize, names: Option<&Robj>) -> Vec<Option<usize>> {
+        match &self.0 {
+            Selection::All => (0..len).map(Some).collect(),
+            Selection::Positions(positions) => {
+                positions.iter().map(|p| p.filter(|&p| p < len)).collect()
+            }
+            Selection::Exclude(excluded) => {
+                let mut keep = vec![true; len];
+                for &p in excluded.iter().filter(|&&p| p < len) {
+                    keep[p] = false;
+                }
+                (0..len).filter(|&p| keep[p]).map(Some).collect()
+            }
+            Selection::Logical(mask) => {
+                if mask.is_empty() {
+                    return Vec::new();
+                }
+                (0..len.max(mask.len()))
+                    .filter_map(|p| {
+                        let selected = mask[p % mask.len()];
+                        if selected.is_na() {
+                            Some(None)
+                        } else if selected.is_true() {
+                            Some(Some(p))
+                        } else {
+                            None
+                        }
+                    })
+                    .map(|p| p.filter(|&p| p < len))
+                    .collect()
+            }
+            Selection::Names(wanted) => {
+                let lookup = name_lookup(names);
+                wanted
+                    .iter()
+                    .map(|name| name.as_deref().and_then(|name| lookup.get(name).copied()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Zero-based positions to assign to in a vector of length `len`, which
+    /// may be past the end, and the names of the elements that are added by name.
+    fn select_for_assignment(
+        &self,
+        len: usize,
+        names: Option<&Robj>,
+    ) -> Result<(Vec<usize>, Vec<NewName>)> {
+        let na_error = || Error::Other("NAs are not allowed in subscripted assignments".into());
+        match &self.0 {
+            Selection::Positions(positions) => {
+                let positions = positions.iter().map(|p| p.ok_or_else(na_error));
+                Ok((positions.collect::<Result<_>>()?, Vec::new()))
+            }
+            Selection::Logical(mask) if mask.len() > len => {
+                Subscript::logical(mask.iter().copied()).select_for_assignment(mask.len(), names)
+            }
+            Selection::Names(wanted) => {
+                let mut lookup = name_lookup(names);
+                let mut added = Vec::new();
+                let mut positions = Vec::with_capacity(wanted.len());
+                for name in wanted {
+                    let name = name.as_deref().ok_or_else(na_error)?;
+                    let position = *lookup.entry(name).or_insert_with(|| {
+                        added.push((len + added.len(), name.to_string()));
+                        len + added.len() - 1
+                    });
+                    positions.push(position);
+                }
+                Ok((positions, added))
+            }
+            _ => {
+                let positions = self.select(len, names).into_iter();
+                Ok((
+                    positions
+                        .map(|p| p.ok_or_else(na_error))
+                        .collect::<Result<_>>()?,
+                    Vec::new(),
+                ))
+            }
+        }
+    }
+}
+
+/// The first position of each name, ignoring `NA` and empty names.
+fn name_lookup(names: Option<&Robj>) -> HashMap<&str, usize> {
+    let mut lookup = HashMap::new();
+    if let Some(names) = names.and_then(|names| names.as_str_iter()) {
+        for (p, name) in names.enumerate() {
+            if !name.is_na() && !name.is_empty() {
+                lookup.entry(name).or_insert(p);
+            }
+        }
+    }
+    lookup
+}
+
+impl From<RangeFull> for Subscript {
+    fn from(_: RangeFull) -> Self {
+        Subscript::all()
+    }
+}
+
+impl From<usize> for Subscript {
+    fn from(position: usize) -> Self {
+        Subscript::positions([position])
+    }
+}
+
+impl From<Range<usize>> for Subscript {
+    fn from(positions: Range<usize>) -> Self {
+        Subscript::positions(positions)
+    }
+}
+
+impl From<RangeInclusive<usize>> for Subscript {
+    fn from(positions: RangeInclusive<usize>) -> Self {
+        Subscript::positions(positions)
+    }
+}
+
+impl From<&str> for Subscript {
+    fn from(name: &str) -> Self {
+        Subscript::names([name])
+    }
+}
+
+impl From<String> for Subscript {
+    fn from(name: String) -> Self {
+        Subscript::names([name])
+    }
+}
+
+fn logical_from_bools<I: IntoIterator<Item = bool>>(mask: I) -> Subscript {
+    Subscript::logical(mask.into_iter().map(Rbool::from))
+}
+
+macro_rules! impl_subscript_from {
+    ($elt: ty, $constructor: expr) => {
+        impl<const N: usize> From<[$elt; N]> for Subscript {
+            fn from(values: [$elt; N]) -> Self {
+                $constructor(values)
+            }
+        }
+
+        impl From<&[$elt]> for Subscript {
+            fn from(values: &[$elt]) -> Self {
+                $constructor(values.iter().cloned())
+            }
+        }
+
+        impl From<Vec<$elt>> for Subscript {
+            fn from(values: Vec<$elt>) -> Self {
+                $constructor(values)
+            }
+        }
+    };
+}
+
+impl_subscript_from!(usize, Subscript::positions);
+impl_subscript_from!(bool, logical_from_bools);
+impl_subscript_from!(Rbool, Subscript::logical);
+impl_subscript_from!(&str, Subscript::names);
+impl_subscript_from!(String, Subscript::names);
+
+impl From<&Logicals> for Subscript {
+    fn from(mask: &Logicals) -> Self {
+        Subscript::logical(mask.iter())
+    }
+}
+
+impl From<&Strings> for Subscript {
+    fn from(names: &Strings) -> Self {
+        Subscript(Selection::Names(
+            names
+                .iter()
+                .map(|name| (!name.is_na()).then(|| name.to_string()))
+                .collect(),
+        ))
+    }
+}
+
+impl TryFrom<&Robj> for Subscript {
+    type Error = Error;
+
+    /// Make a subscript from an R index, which follows R's rules:
+    /// positions are one-based, zeros are ignored, and negative
+    /// positions are left out.
+    fn try_from(index: &Robj) -> Result<Self> {
+        if index.is_null() {
+            return Ok(Subscript::positions([]));
+        }
+        if let Some(mask) = index.as_logical_slice() {
+            return Ok(Subscript::logical(mask.iter().copied()));
+        }
+        if let Ok(names) = Strings::try_from(index) {
+            return Ok(Subscript::from(&names));
+        }
+        let positions: Vec<Option<f64>> = if let Some(positions) = index.as_integer_slice() {
+            let positions = positions.iter();
+            positions
+                .map(|&p| (!p.is_na()).then_some(p as f64))
+                .collect()
+        } else if let Some(positions) = index.as_real_slice() {
+            positions
+                .iter()
+                .map(|&p| (!p.is_na()).then_some(p.trunc()))
+                .collect()
+        } else {
+            return Err(Error::Other(format!(
+                "invalid subscript type '{:?}'",
+                index.rtype()
+            )));
+        };
+
+        if positions.iter().flatten().any(|&p| p < 0.0) {
+            if positions.iter().any(|p| p.map_or(true, |p| p > 0.0)) {
+                return Err(Error::Other(
+                    "can't mix positive and negative subscripts".into(),
+                ));
+            }
+            let excluded = positions.iter().flatten().filter(|&&p| p < 0.0);
+            Ok(Subscript::exclude(excluded.map(|&p| (-p) as usize - 1)))
+        } else {
+            let positions = positions
+                .iter()
+                .filter(|p| **p != Some(0.0))
+                .map(|p| p.map(|p| p as usize - 1));
+            Ok(Subscript(Selection::Positions(positions.collect())))
+        }
+    }
+}
+
+impl TryFrom<Robj> for Subscript {
+    type Error = Error;
+
+    fn try_from(index: Robj) -> Result<Self> {
+        Self::try_from(&index)
+    }
+}
+
+/// Copy the elements of `robj` at `positions` to a new vector, with `NA` or `NULL` for `None`.
+fn gather(robj: &Robj, positions: &[Option<usize>]) -> Robj {
+    macro_rules! gather_typed {
+        ($type: ty, $na: expr) => {{
+            let mut result = Robj::alloc_vector(robj.sexptype(), positions.len());
+            let source: &[$type] = robj.as_typed_slice().unwrap();
+            let dest: &mut [$type] = result.as_typed_slice_mut().unwrap();
+            for (d, p) in dest.iter_mut().zip(positions) {
+                *d = p.map_or($na, |p| source[p]);
+            }
+            result
+        }};
+    }
+
+    single_threaded(|| unsafe {
+        match robj.sexptype() {
+            SEXPTYPE::LGLSXP => gather_typed!(Rbool, Rbool::na()),
+            SEXPTYPE::INTSXP => gather_typed!(Rint, Rint::na()),
+            SEXPTYPE::REALSXP => gather_typed!(Rfloat, Rfloat::na()),
+            SEXPTYPE::CPLXSXP => gather_typed!(Rcplx, Rcplx::na()),
+            SEXPTYPE::RAWSXP => gather_typed!(u8, 0),
+            sexptype @ (SEXPTYPE::STRSXP | SEXPTYPE::VECSXP | SEXPTYPE::EXPRSXP) => {
+                let mut result = Robj::alloc_vector(sexptype, positions.len());
+                let (source, dest) = (robj.get(), result.get_mut());
+                for (i, p) in positions.iter().enumerate() {
+                    let i = i as R_xlen_t;
+                    match (sexptype, p) {
+                        (SEXPTYPE::STRSXP, Some(p)) => {
+                            SET_STRING_ELT(dest, i, STRING_ELT(source, *p as R_xlen_t))
+                        }
+                        (SEXPTYPE::STRSXP, None) => SET_STRING_ELT(dest, i, R_NaString),
+                        (_, Some(p)) => {
+                            SET_VECTOR_ELT(dest, i, VECTOR_ELT(source, *p as R_xlen_t));
+                        }
+                        (_, None) => {
+                            SET_VECTOR_ELT(dest, i, R_NilValue);
+                        }
+                    }
+                }
+                result
+            }
+            sexptype => unreachable!("cannot subset {:?}", sexptype),
+        }
+    })
+}
+
+/// Select elements of a vector, with their names and the other attributes.
+fn subset_vector(robj: &Robj, index: &Subscript) -> Robj {
+    let names = robj.get_attrib(wrapper::symbol::names_symbol());
+    let positions = index.select(robj.len(), names.as_ref());
+    let mut result = gather(robj, &positions);
+    if let Some(names) = names {
+        let names = gather(&names, &positions);
+        result
+            .set_attrib(wrapper::symbol::names_symbol(), names)
+            .unwrap();
+    }
+    unsafe { Rf_copyMostAttrib(robj.get(), result.get_mut()) };
+    result
+}
+
+/// Select a single element of a vector.
+fn select_element(robj: &Robj, index: &Subscript) -> Result<Option<usize>> {
+    let names = robj.get_attrib(wrapper::symbol::names_symbol());
+    match index.select(robj.len(), names.as_ref()).as_slice() {
+        [Some(p)] => Ok(Some(*p)),
+        // x[["missing"]] is NULL for lists, not an error.
+        [None] if robj.is_list() && matches!(index.0, Selection::Names(_)) => Ok(None),
+        [None] => Err(Error::Other("subscript out of bounds".into())),
+        [] => Err(Error::Other(
+            "attempt to select less than one element".into(),
+        )),
+        _ => Err(Error::Other(
+            "attempt to select more than one element".into(),
+        )),
+    }
+}
+
+/// Prepare a vector for assignment to `index`, extending it if necessary,
+/// and return the positions to assign to.
+fn prepare_assignment(robj: &mut Robj, index: &Subscript, nvalues: usize) -> Result<Vec<usize>> {
+    let names = robj.get_attrib(wrapper::symbol::names_symbol());
+    let (positions, added) = index.select_for_assignment(robj.len(), names.as_ref())?;
+    if positions.is_empty() {
+        return Ok(positions);
+    }
+    if nvalues == 0 {
+        return Err(Error::Other("replacement has length zero".into()));
+    }
+    if positions.len() % nvalues != 0 {
+        crate::conditions::signal_warning(
+            "number of items to replace is not a multiple of replacement length",
+        )?;
+    }
+
+    let len = positions.iter().max().map_or(0, |&p| p + 1).max(robj.len());
+    if len > robj.len() {
+        single_threaded(|| unsafe {
+            // This fills in NA, or NULL for lists, and extends the names with "".
+            let mut extended = Robj::from_sexp(Rf_xlengthgets(robj.get(), len as R_xlen_t));
+            Rf_copyMostAttrib(robj.get(), extended.get_mut());
+            if !added.is_empty() {
+                let mut names: Strings = match extended.get_attrib(wrapper::symbol::names_symbol())
+                {
+                    Some(names) => names.try_into()?,
+                    None => Strings::from_values(std::iter::repeat("").take(len)),
+                };
+                for (p, name) in added {
+                    names.set_elt(p, name.into());
+                }
+                extended.set_attrib(wrapper::symbol::names_symbol(), names)?;
+            }
+            *robj = extended;
+            Ok::<_, Error>(())
+        })?;
+    }
+    Ok(positions)
+}
+
+/// Types that can be written to a vector by [`Subset::set_subset`].
+trait SubsetElement: Clone {
+    fn assign(robj: &mut Robj, positions: &[usize], values: &[Self]);
+}
+
+macro_rules! impl_subset_element {
+    ($($type: ty),*) => {
+        $(
+            impl SubsetElement for $type {
+                fn assign(robj: &mut Robj, positions: &[usize], values: &[Self]) {
+                    let dest: &mut [$type] = robj.as_typed_slice_mut().unwrap();
+                    for (p, v) in positions.iter().zip(values.iter().cycle()) {
+                        dest[*p] = *v;
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_subset_element!(Rint, Rfloat, Rbool, Rcplx, i32, f64, u8);
+
+impl SubsetElement for Rstr {
+    fn assign(robj: &mut Robj, positions: &[usize], values: &[Self]) {
+        single_threaded(|| unsafe {
+            let dest = robj.get_mut();
+            for (p, v) in positions.iter().zip(values.iter().cycle()) {
+                SET_STRING_ELT(dest, *p as R_xlen_t, v.get());
+            }
+        })
+    }
+}
+
+impl SubsetElement for Robj {
+    fn assign(robj: &mut Robj, positions: &[usize], values: &[Self]) {
+        single_threaded(|| unsafe {
+            let dest = robj.get_mut();
+            for (p, v) in positions.iter().zip(values.iter().cycle()) {
+                SET_VECTOR_ELT(dest, *p as R_xlen_t, v.get());
+            }
+        })
+    }
+}
+
+macro_rules! impl_subset {
+    ($type: ident, $elt: ty, |$robj: ident, $p: ident| $element: expr, $missing: expr) => {
+        impl Subset for $type {
+            type Index = Subscript;
+            type Element = $elt;
+
+            fn subset<I: Into<Subscript>>(&self, index: I) -> Result<Self> {
+                Ok(Self {
+                    robj: subset_vector(&self.robj, &index.into()),
+                })
+            }
+
+            fn element<I: Into<Subscript>>(&self, index: I) -> Result<$elt> {
+                let $robj = self;
+                match select_element(&self.robj, &index.into())? {
+                    Some($p) => Ok($element),
+                    None => Ok($missing),
+                }
+            }
+
+            fn set_subset<I: Into<Subscript>>(&mut self, index: I, values: &[$elt]) -> Result<()> {
+                let positions = prepare_assignment(&mut self.robj, &index.into(), values.len())?;
+                <$elt>::assign(&mut self.robj, &positions, values);
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_subset!(Integers, Rint, |x, p| x.elt(p), Rint::na());
+impl_subset!(Doubles, Rfloat, |x, p| x.elt(p), Rfloat::na());
+impl_subset!(Logicals, Rbool, |x, p| x.elt(p), Rbool::na());
+impl_subset!(Strings, Rstr, |x, p| x.elt(p), Rstr::na());
+impl_subset!(List, Robj, |x, p| x.elt(p)?, nil_value());
+
+impl<T, const NDIM: usize> Subset for RArray<T, NDIM>
+where
+    T: SubsetElement + Copy,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    type Index = [Subscript; NDIM];
+    type Element = T;
+
+    /// Select a block of an array, as R's `x[i, j, drop = FALSE]`.
+    fn subset<I: Into<Self::Index>>(&self, index: I) -> Result<Self> {
+        let (positions, selected) = array_positions(self, &index.into())?;
+        let mut result = gather(self, &positions);
+        let dim: Vec<i32> = selected.iter().map(|s| s.len() as i32).collect();
+        result.set_attrib(wrapper::symbol::dim_symbol(), dim)?;
+        if let Some(dimnames) = self.get_attrib(wrapper::symbol::dimnames_symbol()) {
+            let dimnames: List = dimnames.try_into()?;
+            let dimnames = dimnames.values().zip(&selected).map(|(names, selected)| {
+                if names.is_null() {
+                    names
+                } else {
+                    gather(&names, selected)
+                }
+            });
+            result.set_attrib(
+                wrapper::symbol::dimnames_symbol(),
+                List::from_values(dimnames),
+            )?;
+        }
+        unsafe { Rf_copyMostAttrib(self.get(), result.get_mut()) };
+        Ok(RArray::from_parts(result))
+    }
+
+    fn element<I: Into<Self::Index>>(&self, index: I) -> Result<T> {
+        match array_positions(self, &index.into())?.0.as_slice() {
+            [Some(p)] => Ok(self.data()[*p]),
+            [] => Err(Error::Other(
+                "attempt to select less than one element".into(),
+            )),
+            _ => Err(Error::Other(
+                "attempt to select more than one element".into(),
+            )),
+        }
+    }
+
+    /// Replace a block of an array, as R's `x[i, j] <- values`.
+    /// Arrays are not extended.
+    fn set_subset<I: Into<Self::Index>>(&mut self, index: I, values: &[T]) -> Result<()> {
+        let (positions, _) = array_positions(self, &index.into())?;
+        let positions: Vec<usize> = positions.into_iter().flatten().collect();
+        if positions.is_empty() {
+            return Ok(());
+        }
+        if values.is_empty() {
+            return Err(Error::Other("replacement has length zero".into()));
+        }
+        if positions.len() % values.len() != 0 {
+            return Err(Error::Other(
+                "number of items to replace is not a multiple of replacement length".into(),
+            ));
+        }
+        T::assign(self, &positions, values);
+        Ok(())
+    }
+}
+
+/// The linear positions of a block of an array, in column-major order,
+/// and the positions selected in each dimension.
+fn array_positions<T, const NDIM: usize>(
+    array: &RArray<T, NDIM>,
+    index: &[Subscript; NDIM],
+) -> Result<(Selected, Vec<Selected>)> {
+    let dim = array.get_dim();
+    let dimnames = array
+        .get_attrib(wrapper::symbol::dimnames_symbol())
+        .and_then(|dimnames| List::try_from(dimnames).ok());
+    let mut selected = Vec::with_capacity(NDIM);
+    for (k, subscript) in index.iter().enumerate() {
+        let names = dimnames.as_ref().and_then(|dimnames| dimnames.elt(k).ok());
+        let positions = subscript.select(dim[k], names.as_ref());
+        if positions.iter().any(Option::is_none) {
+            return Err(Error::Other("subscript out of bounds".into()));
+        }
+        selected.push(positions);
+    }
+
+    let len = selected.iter().map(Vec::len).product();
+    let mut positions = Vec::with_capacity(len);
+    let mut counter = [0; NDIM];
+    for _ in 0..len {
+        let offset = (0..NDIM).rev().fold(0, |offset, k| {
+            offset * dim[k] + selected[k][counter[k]].unwrap()
+        });
+        positions.push(Some(offset));
+        // Advance the first dimension fastest, as R stores arrays.
+        for k in 0..NDIM {
+            counter[k] += 1;
+            if counter[k] < selected[k].len() {
+                break;
+            }
+            counter[k] = 0;
+        }
+    }
+    Ok((positions, selected))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as extendr_api;
+    use crate::prelude::*;
+
+    #[test]
+    fn subset_vectors() {
+        test! {
+            let x = Integers::from_values([10, 20, 30]);
+            assert_eq!(Robj::from(x.subset([0, 5])?), R!("c(10L, 20L, 30L)[c(1, 6)]")?);
+            assert_eq!(Robj::from(x.subset(1..3)?), R!("c(20L, 30L)")?);
+            assert_eq!(Robj::from(x.subset([true, false, true, true])?), R!("c(10L, 30L, NA)")?);
+            assert_eq!(x.element(2)?, 30);
+            assert!(x.element(3).is_err());
+            assert!(x.element([0, 1]).is_err());
+
+            let s = Strings::from_values(["a", "b"]);
+            assert_eq!(Robj::from(s.subset([1, 2])?), R!("c('b', NA)")?);
+
+            let f = R!("factor(c('a', 'b', 'a'))")?;
+            let f: Integers = f.try_into()?;
+            assert_eq!(Robj::from(f.subset([2, 1])?), R!("factor(c('a', 'b', 'a'))[c(3, 2)]")?);
+        }
+    }
+
+    #[test]
+    fn subset_lists() {
+        test! {
+            let l = List::from_pairs([("a", r!(1)), ("b", r!("x"))]);
+            assert_eq!(Robj::from(l.subset(["b", "c"])?), R!("list(a = 1, b = 'x')[c('b', 'c')]")?);
+            assert_eq!(l.element("b")?, r!("x"));
+            assert_eq!(l.element("c")?, r!(NULL));
+            assert!(l.element(2).is_err());
+        }
+    }
+
+    #[test]
+    fn r_subscripts() {
+        test! {
+            let x = Doubles::from_values([1.0, 2.0, 3.0, 4.0]);
+            let index = |src: &str| -> extendr_api::Result<Subscript> { Subscript::try_from(&eval_string(src)?) };
+            assert_eq!(Robj::from(x.subset(index("c(0, 2, NA)")?)?), R!("c(1, 2, 3, 4)[c(0, 2, NA)]")?);
+            assert_eq!(Robj::from(x.subset(index("-c(1, 3)")?)?), R!("c(2, 4)")?);
+            assert_eq!(Robj::from(x.subset(index("c(TRUE, FALSE)")?)?), R!("c(1, 3)")?);
+            assert_eq!(Robj::from(x.subset(index("NULL")?)?), R!("numeric(0)")?);
+            assert!(index("c(-1, 2)").is_err());
+        }
+    }
+
+    #[test]
+    fn assign() {
+        test! {
+            let mut x = Integers::from_values([1, 2]);
+            x.set_subset([0], &[Rint::from(10)])?;
+            x.set_subset([3], &[Rint::from(40)])?;
+            assert_eq!(Robj::from(x.clone()), R!("c(10L, 2L, NA, 40L)")?);
+            x.set_subset(.., &[Rint::from(0)])?;
+            assert_eq!(Robj::from(x.clone()), R!("integer(4)")?);
+            assert!(x.set_subset([0], &[]).is_err());
+
+            let mut l = List::from_pairs([("a", r!(1))]);
+            l.set_subset(["b", "a", "b"], &[r!(2), r!(3), r!(4)])?;
+            assert_eq!(Robj::from(l), R!("list(a = 3, b = 4)")?);
+
+            let mut s = Strings::from_values(["a"]);
+            s.set_subset(Subscript::logical([TRUE, FALSE, TRUE]), &[Rstr::from("z")])?;
+            assert_eq!(Robj::from(s), R!("c('z', NA, 'z')")?);
+        }
+    }
+
+    #[test]
+    fn arrays() {
+        test! {
+            let m: RMatrix<f64> = R!("matrix(1:6 + 0, 2, dimnames = list(c('a', 'b'), NULL))")?.try_into()?;
+            let block = m.subset([Subscript::from("b"), Subscript::from([2, 0])])?;
+            assert_eq!(Robj::from(block), R!("matrix(1:6 + 0, 2, dimnames = list(c('a', 'b'), NULL))['b', c(3, 1), drop = FALSE]")?);
+            assert_eq!(m.element([Subscript::from(1), Subscript::from(2)])?, 6.0);
+            assert!(m.subset([Subscript::from(2), Subscript::all()]).is_err());
+
+            let mut m = m;
+            m.set_subset([Subscript::all(), Subscript::from(1)], &[0.0])?;
+            assert_eq!(m.data(), &[1.0, 2.0, 0.0, 0.0, 5.0, 6.0]);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Stable type identities for [`ExternalPtr`]s shared between packages.
+//!
+//! By default, an `ExternalPtr<T>` is only recognised by the library that
+//! created it: its type check relies on [`std::any::TypeId`], which differs
+//! between builds. A type that implements [`TypeTag`], usually through
+//! `#[extendr(type_id = "mypkg::Model/v2")]`, is instead tagged with its
+//! type id and the ABI it was built with. Another package that defines the
+//! same type with the same type id can then use the object, provided it was
+//! built with the same `rustc` and extendr versions and the type has the same
+//! size and alignment.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//!
+//! struct Model {
+//!     weights: Vec<f64>,
+//! }
+//!
+//! impl TypeTag for Model {
+//!     const TYPE_ID: &'static str = "mypkg::Model/v1";
+//! }
+//!
+//! test! {
+//!     let model = ExternalPtr::new_tagged(Model { weights: vec![0.5] });
+//!     let robj: Robj = model.into();
+//!     let model = ExternalPtr::<Model>::try_from_tagged(&robj)?;
+//!     assert_eq!(model.weights, [0.5]);
+//!     assert_eq!(registered_type("mypkg::Model/v1"), Some(TypeAbi::of::<Model>()));
+//! }
+//! ```
+use super::*;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+
+/// A stable identifier for a type stored in an [`ExternalPtr`].
+///
+/// Change the type id, e.g. from `"mypkg::Model/v1"` to `"mypkg::Model/v2"`,
+/// whenever the definition of the type changes.
+pub trait TypeTag: 'static {
+    const TYPE_ID: &'static str;
+}
+
+/// The type id and ABI of a tagged type, as stored in the tag of its external pointers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeAbi {
+    pub type_id: String,
+    pub size: usize,
+    pub align: usize,
+    pub rustc: String,
+    pub extendr: String,
+}
+
+const TAG_CLASS: &str = "extendr_type_tag";
+const TAG_NAMES: [&str; 5] = ["type_id", "size", "align", "rustc", "extendr"];
+
+impl TypeAbi {
+    /// The type id and ABI of `T` in this build.
+    pub fn of<T: TypeTag>() -> Self {
+        Self {
+            type_id: T::TYPE_ID.to_string(),
+            size: std::mem::size_of::<T>(),
+            align: std::mem::align_of::<T>(),
+            rustc: env!("EXTENDR_RUSTC_VERSION").to_string(),
+            extendr: concat!(
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                ".",
+                env!("CARGO_PKG_VERSION_MINOR")
+            )
+            .to_string(),
+        }
+    }
+
+    /// Read the type id and ABI from the tag of an external pointer.
+    /// Returns `None` if the external pointer is not tagged.
+    pub fn from_tag(tag: &Robj) -> Option<Self> {
+        if !tag.inherits(TAG_CLASS) {
+            return None;
+        }
+        match tag.as_str_vector()?.as_slice() {
+            [type_id, size, align, rustc, extendr] => Some(Self {
+                type_id: type_id.to_string(),
+                size: size.parse().ok()?,
+                align: align.parse().ok()?,
+                rustc: rustc.to_string(),
+                extendr: extendr.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Make the tag of an external pointer.
+    pub fn to_tag(&self) -> Robj {
+        let mut tag: Robj = Strings::from_values([
+            self.type_id.clone(),
+            self.size.to_string(),
+            self.align.to_string(),
+            self.rustc.clone(),
+            self.extendr.clone(),
+        ])
+        .into();
+        tag.set_names(TAG_NAMES).unwrap();
+        tag.set_class([TAG_CLASS]).unwrap();
+        tag
+    }
+
+    /// Check that an external pointer `robj`, tagged with `found`, can be used as this type.
+    pub(crate) fn check(&self, found: &TypeAbi, robj: &Robj) -> Result<()> {
+        if found.type_id != self.type_id {
+            return Err(Error::ExpectedExternalPtrType(
+                robj.clone(),
+                self.type_id.clone(),
+            ));
+        }
+        if found != self {
+            return Err(Error::IncompatibleExternalPtrType(
+                robj.clone(),
+                format!(
+                    "{} was created with {}, expected {}",
+                    self.type_id, found, self
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for TypeAbi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "size {}, align {}, {}, extendr {}",
+            self.size, self.align, self.rustc, self.extendr
+        )
+    }
+}
+
+static REGISTRY: Lazy<Mutex<HashMap<&'static str, (std::any::TypeId, TypeAbi)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Register `T` under its type id, and return its ABI.
+///
+/// This is done when tagged pointers are made or checked. It fails if another
+/// type of this library was registered with the same type id.
+pub fn register_type<T: TypeTag>() -> Result<TypeAbi> {
+    let mut registry = REGISTRY.lock().unwrap();
+    let (type_id, abi) = registry
+        .entry(T::TYPE_ID)
+        .or_insert_with(|| (std::any::TypeId::of::<T>(), TypeAbi::of::<T>()));
+    if *type_id != std::any::TypeId::of::<T>() {
+        return Err(Error::Other(format!(
+            "type id {} of {} is already used by another type",
+            T::TYPE_ID,
+            std::any::type_name::<T>()
+        )));
+    }
+    Ok(abi.clone())
+}
+
+/// Check that a pointer tagged with `tag` holds a `T`: its type id is registered
+/// for `T` in this library, with the same ABI.
+pub(crate) fn is_tagged_as<T: 'static>(tag: &Robj) -> bool {
+    let Some(found) = TypeAbi::from_tag(tag) else {
+        return false;
+    };
+    let registry = REGISTRY.lock().unwrap();
+    matches!(
+        registry.get(found.type_id.as_str()),
+        Some((type_id, abi)) if *type_id == std::any::TypeId::of::<T>() && *abi == found
+    )
+}
+
+/// Get the ABI of a type registered in this library.
+pub fn registered_type(type_id: &str) -> Option<TypeAbi> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.get(type_id).map(|(_, abi)| abi.clone())
+}
@@ -0,0 +1,741 @@
+//! Element-wise arithmetic, comparison and logical operators for vectors and matrices.
+//!
+//! These follow R's rules, without calling R:
+//!
+//! * The shorter operand is recycled, with a warning if the longer length
+//!   is not a multiple of the shorter one.
+//! * `NA` propagates, and integer overflow gives `NA` with a warning.
+//! * Integer division gives doubles, and mixing integers with doubles gives doubles.
+//! * Attributes are copied from the longer operand, or from both if they have
+//!   the same length, with those of the left hand side taking precedence.
+//!   Comparisons and logical operators only keep `names`, `dim` and `dimnames`.
+//! * Factors and `integer64` vectors, whose elements are not the numbers
+//!   they stand for, are rejected.
+//!
+//! Errors, such as non-conformable arrays, are raised as R errors, as are
+//! warnings that R turns into errors.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let x = Integers::from_values([1, 2, 3, 4]);
+//!     let y = Integers::from_values([Rint::from(10), Rint::na()]);
+//!     assert_eq!(Robj::from(&x + &y), R!("c(11L, NA, 13L, NA)")?);
+//!     assert_eq!(Robj::from(&x / 2), R!("c(0.5, 1, 1.5, 2)")?);
+//!     assert_eq!(Robj::from(x.greater_than(2)), R!("c(FALSE, FALSE, TRUE, TRUE)")?);
+//! }
+//! ```
+use super::scalar::{Rfloat, Rint};
+use super::*;
+use crate::conditions::signal_warning;
+use extendr_ffi::{
+    Rboolean, Rf_conformable, Rf_copyMostAttrib,
+    SEXPTYPE::{INTSXP, LGLSXP, REALSXP},
+};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Sub};
+
+/// Element-wise comparisons, as R's `==`, `!=`, `<`, `<=`, `>` and `>=`.
+///
+/// Comparisons with `NA` or `NaN` are `NA`.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let x = Doubles::from_values([1.0, 2.0, f64::NAN]);
+///     let y = Doubles::from_values([2.0, 2.0, 2.0]);
+///     let less = x.less_than(&y);
+///     assert_eq!(less.elt(0), TRUE);
+///     assert_eq!(less.elt(1), FALSE);
+///     assert!(less.elt(2).is_na());
+/// }
+/// ```
+pub trait Comparisons<Rhs> {
+    type Output;
+
+    fn equal(&self, rhs: Rhs) -> Self::Output;
+    fn not_equal(&self, rhs: Rhs) -> Self::Output;
+    fn less_than(&self, rhs: Rhs) -> Self::Output;
+    fn less_equal(&self, rhs: Rhs) -> Self::Output;
+    fn greater_than(&self, rhs: Rhs) -> Self::Output;
+    fn greater_equal(&self, rhs: Rhs) -> Self::Output;
+}
+
+/// An operand of an element-wise operation: a vector, or a scalar that is recycled.
+trait Operand {
+    type Elt: Copy;
+
+    fn elts(&self) -> &[Self::Elt];
+
+    /// The object whose attributes are kept in the result, if any.
+    fn robj(&self) -> Option<&Robj> {
+        None
+    }
+}
+
+/// The result of an element-wise operation.
+trait FromResult {
+    fn from_result(robj: Robj) -> Self;
+}
+
+macro_rules! impl_vector_operand {
+    ($type: ident, $elt: ty) => {
+        impl Operand for $type {
+            type Elt = $elt;
+
+            fn elts(&self) -> &[$elt] {
+                self
+            }
+
+            fn robj(&self) -> Option<&Robj> {
+                Some(&self.robj)
+            }
+        }
+
+        impl FromResult for $type {
+            fn from_result(robj: Robj) -> Self {
+                Self { robj }
+            }
+        }
+    };
+}
+
+impl_vector_operand!(Integers, Rint);
+impl_vector_operand!(Doubles, Rfloat);
+impl_vector_operand!(Logicals, Rbool);
+
+macro_rules! impl_scalar_operand {
+    ($($type: ty),*) => {
+        $(
+            impl Operand for $type {
+                type Elt = $type;
+
+                fn elts(&self) -> &[$type] {
+                    std::slice::from_ref(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_operand!(Rint, Rfloat, Rbool, i32, f64, bool);
+
+impl<T: Operand + ?Sized> Operand for &T {
+    type Elt = T::Elt;
+
+    fn elts(&self) -> &[T::Elt] {
+        (**self).elts()
+    }
+
+    fn robj(&self) -> Option<&Robj> {
+        (**self).robj()
+    }
+}
+
+impl<T, const NDIM: usize> Operand for RArray<T, NDIM>
+where
+    T: Copy,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    type Elt = T;
+
+    fn elts(&self) -> &[T] {
+        self.data()
+    }
+
+    fn robj(&self) -> Option<&Robj> {
+        Some(&**self)
+    }
+}
+
+impl<T, const NDIM: usize> FromResult for RArray<T, NDIM>
+where
+    Robj: for<'a> AsTypedSlice<'a, T>,
+{
+    fn from_result(robj: Robj) -> Self {
+        RArray::from_parts(robj)
+    }
+}
+
+/// Which attributes of the operands are kept in the result.
+#[derive(Clone, Copy, PartialEq)]
+enum Kept {
+    All,
+    Structure,
+}
+
+/// Raise the error of an operation as an R error, as the operator traits
+/// cannot return one.
+fn or_throw(result: Result<Robj>) -> Robj {
+    match result {
+        Ok(robj) => robj,
+        Err(err) => {
+            let msg = err.to_string();
+            drop(err);
+            throw_r_error(msg)
+        }
+    }
+}
+
+/// Reject operands whose elements are codes rather than the numbers they stand for.
+fn check_operand(robj: Option<&Robj>) -> Result<()> {
+    match robj {
+        Some(robj) if robj.is_factor() => Err(Error::Other(
+            "operators are not meaningful for factors".into(),
+        )),
+        Some(robj) if robj.is_integer64() => Err(Error::Other(
+            "operators are not meaningful for integer64 vectors".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The length of the result of an operation on operands of lengths `lhs` and `rhs`.
+fn recycled_len(lhs: usize, rhs: usize) -> Result<usize> {
+    if lhs == 0 || rhs == 0 {
+        return Ok(0);
+    }
+    let len = lhs.max(rhs);
+    if len % lhs.min(rhs) != 0 {
+        signal_warning("longer object length is not a multiple of shorter object length")?;
+    }
+    Ok(len)
+}
+
+/// Check that the operands, if they are arrays, fit the result.
+fn check_conformable(lhs: Option<&Robj>, rhs: Option<&Robj>, len: usize) -> Result<()> {
+    let has_dim = |robj: &&Robj| robj.has_attrib(wrapper::symbol::dim_symbol());
+    let (lhs, rhs) = (lhs.filter(has_dim), rhs.filter(has_dim));
+    if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+        if unsafe { Rf_conformable(lhs.get(), rhs.get()) } == Rboolean::FALSE {
+            return Err(Error::Other("non-conformable arrays".into()));
+        }
+    }
+    for array in [lhs, rhs].into_iter().flatten() {
+        if array.len() != len {
+            return Err(Error::Other(format!(
+                "dims [product {}] do not match the length of object [{}]",
+                array.len(),
+                len
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Copy the attributes of the operands to `result`.
+fn copy_attributes(result: &mut Robj, lhs: Option<&Robj>, rhs: Option<&Robj>, kept: Kept) {
+    let len = result.len();
+    // In order of increasing precedence.
+    for source in [rhs, lhs].into_iter().flatten() {
+        if source.len() != len {
+            continue;
+        }
+        if kept == Kept::All {
+            unsafe { Rf_copyMostAttrib(source.get(), result.get_mut()) };
+        }
+        for name in [
+            wrapper::symbol::names_symbol(),
+            wrapper::symbol::dim_symbol(),
+            wrapper::symbol::dimnames_symbol(),
+        ] {
+            if let Some(value) = source.get_attrib(&name) {
+                result.set_attrib(name, value).unwrap();
+            }
+        }
+    }
+}
+
+/// Apply `f` to the elements of `lhs` and `rhs`, recycling the shorter one.
+fn zip_with<L, R, T, F>(lhs: &L, rhs: &R, sexptype: SEXPTYPE, kept: Kept, f: F) -> Result<Robj>
+where
+    L: Operand + ?Sized,
+    R: Operand + ?Sized,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+    F: Fn(L::Elt, R::Elt) -> T,
+{
+    check_operand(lhs.robj())?;
+    check_operand(rhs.robj())?;
+    single_threaded(|| {
+        let (l, r) = (lhs.elts(), rhs.elts());
+        let len = if l.len() == r.len() {
+            l.len()
+        } else {
+            recycled_len(l.len(), r.len())?
+        };
+        check_conformable(lhs.robj(), rhs.robj(), len)?;
+
+        let mut result = Robj::alloc_vector(sexptype, len);
+        let dest: &mut [T] = result.as_typed_slice_mut().unwrap();
+        if l.len() == r.len() {
+            for (d, (&a, &b)) in dest.iter_mut().zip(l.iter().zip(r)) {
+                *d = f(a, b);
+            }
+        } else if len != 0 {
+            let values = l.iter().cycle().zip(r.iter().cycle());
+            for (d, (&a, &b)) in dest.iter_mut().zip(values) {
+                *d = f(a, b);
+            }
+        }
+        copy_attributes(&mut result, lhs.robj(), rhs.robj(), kept);
+        Ok(result)
+    })
+}
+
+/// Apply `f` to each element of `x`.
+fn map_with<X, T, F>(x: &X, sexptype: SEXPTYPE, kept: Kept, f: F) -> Result<Robj>
+where
+    X: Operand + ?Sized,
+    Robj: for<'a> AsTypedSlice<'a, T>,
+    F: Fn(X::Elt) -> T,
+{
+    check_operand(x.robj())?;
+    single_threaded(|| {
+        let values = x.elts();
+        let mut result = Robj::alloc_vector(sexptype, values.len());
+        let dest: &mut [T] = result.as_typed_slice_mut().unwrap();
+        for (d, &v) in dest.iter_mut().zip(values) {
+            *d = f(v);
+        }
+        copy_attributes(&mut result, x.robj(), None, kept);
+        Ok(result)
+    })
+}
+
+/// Integer arithmetic, which warns if any result overflowed to `NA`.
+fn integer_arith<L, R, F>(lhs: &L, rhs: &R, f: F) -> Result<Robj>
+where
+    L: Operand + ?Sized,
+    R: Operand + ?Sized,
+    L::Elt: CanBeNA,
+    R::Elt: CanBeNA,
+    F: Fn(L::Elt, R::Elt) -> Rint,
+{
+    let overflow = Cell::new(false);
+    let result = zip_with(lhs, rhs, INTSXP, Kept::All, |a, b| {
+        let res = f(a, b);
+        if res.is_na() && !a.is_na() && !b.is_na() {
+            overflow.set(true);
+        }
+        res
+    })?;
+    if overflow.get() {
+        signal_warning("NAs produced by integer overflow")?;
+    }
+    Ok(result)
+}
+
+fn real_arith<L, R, F>(lhs: &L, rhs: &R, f: F) -> Result<Robj>
+where
+    L: Operand + ?Sized,
+    R: Operand + ?Sized,
+    F: Fn(L::Elt, R::Elt) -> Rfloat,
+{
+    zip_with(lhs, rhs, REALSXP, Kept::All, f)
+}
+
+fn logical_op<L, R, F>(lhs: &L, rhs: &R, f: F) -> Result<Robj>
+where
+    L: Operand + ?Sized,
+    R: Operand + ?Sized,
+    F: Fn(L::Elt, R::Elt) -> Rbool,
+{
+    zip_with(lhs, rhs, LGLSXP, Kept::Structure, f)
+}
+
+fn compare<L, R, C>(lhs: &L, rhs: &R, cmp: C, pred: fn(Ordering) -> bool) -> Result<Robj>
+where
+    L: Operand + ?Sized,
+    R: Operand + ?Sized,
+    C: Fn(L::Elt, R::Elt) -> Option<Ordering>,
+{
+    logical_op(lhs, rhs, |a, b| match cmp(a, b) {
+        Some(ordering) => Rbool::from(pred(ordering)),
+        None => Rbool::na(),
+    })
+}
+
+/// Implement a binary operator for all combinations of owned and borrowed operands.
+macro_rules! impl_binop {
+    ($opname: ident, $lhs: ty, $rhs: ty, $output: ty, $kernel: ident, $f: expr) => {
+        paste::paste! {
+            impl $opname<&$rhs> for &$lhs {
+                type Output = $output;
+
+                fn [<$opname:lower>](self, rhs: &$rhs) -> $output {
+                    <$output>::from_result(or_throw($kernel(self, rhs, $f)))
+                }
+            }
+
+            impl $opname<$rhs> for &$lhs {
+                type Output = $output;
+
+                fn [<$opname:lower>](self, rhs: $rhs) -> $output {
+                    self.[<$opname:lower>](&rhs)
+                }
+            }
+
+            impl $opname<&$rhs> for $lhs {
+                type Output = $output;
+
+                fn [<$opname:lower>](self, rhs: &$rhs) -> $output {
+                    (&self).[<$opname:lower>](rhs)
+                }
+            }
+
+            impl $opname<$rhs> for $lhs {
+                type Output = $output;
+
+                fn [<$opname:lower>](self, rhs: $rhs) -> $output {
+                    (&self).[<$opname:lower>](&rhs)
+                }
+            }
+        }
+    };
+}
+
+/// Implement `+`, `-`, `*` and `/` for a pair of operands.
+macro_rules! impl_arith {
+    (integer: $lhs: ty, $rhs: ty, $output: ty, $div_output: ty) => {
+        impl_binop!(Add, $lhs, $rhs, $output, integer_arith, |a: Rint, b| a + b);
+        impl_binop!(Sub, $lhs, $rhs, $output, integer_arith, |a: Rint, b| a - b);
+        impl_binop!(Mul, $lhs, $rhs, $output, integer_arith, |a: Rint, b| a * b);
+        impl_binop!(Div, $lhs, $rhs, $div_output, real_arith, |a, b| {
+            Rfloat::from(a) / Rfloat::from(b)
+        });
+    };
+    (real: $lhs: ty, $rhs: ty, $output: ty) => {
+        impl_binop!(Add, $lhs, $rhs, $output, real_arith, |a, b| {
+            Rfloat::from(a) + Rfloat::from(b)
+        });
+        impl_binop!(Sub, $lhs, $rhs, $output, real_arith, |a, b| {
+            Rfloat::from(a) - Rfloat::from(b)
+        });
+        impl_binop!(Mul, $lhs, $rhs, $output, real_arith, |a, b| {
+            Rfloat::from(a) * Rfloat::from(b)
+        });
+        impl_binop!(Div, $lhs, $rhs, $output, real_arith, |a, b| {
+            Rfloat::from(a) / Rfloat::from(b)
+        });
+    };
+}
+
+/// Implement [`Comparisons`] for a pair of operands.
+macro_rules! impl_comparisons {
+    ($lhs: ty, $rhs: ty, $output: ty, $cmp: expr) => {
+        impl_comparisons!(@impl $lhs, &$rhs, $output, $cmp);
+    };
+    (scalar: $lhs: ty, $rhs: ty, $output: ty, $cmp: expr) => {
+        impl_comparisons!(@impl $lhs, $rhs, $output, $cmp);
+    };
+    (@impl $lhs: ty, $rhs: ty, $output: ty, $cmp: expr) => {
+        impl Comparisons<$rhs> for $lhs {
+            type Output = $output;
+
+            fn equal(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_eq)))
+            }
+
+            fn not_equal(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_ne)))
+            }
+
+            fn less_than(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_lt)))
+            }
+
+            fn less_equal(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_le)))
+            }
+
+            fn greater_than(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_gt)))
+            }
+
+            fn greater_equal(&self, rhs: $rhs) -> $output {
+                <$output>::from_result(or_throw(compare(self, &rhs, $cmp, Ordering::is_ge)))
+            }
+        }
+    };
+}
+
+fn cmp_real<A: Into<Rfloat>, B: Into<Rfloat>>(a: A, b: B) -> Option<Ordering> {
+    a.into().partial_cmp(&b.into())
+}
+
+fn cmp_int<A: Into<Rint>, B: Into<Rint>>(a: A, b: B) -> Option<Ordering> {
+    a.into().partial_cmp(&b.into())
+}
+
+// Integers
+impl_arith!(integer: Integers, Integers, Integers, Doubles);
+impl_arith!(integer: Integers, Rint, Integers, Doubles);
+impl_arith!(integer: Integers, i32, Integers, Doubles);
+impl_arith!(real: Integers, Doubles, Doubles);
+impl_arith!(real: Integers, Rfloat, Doubles);
+impl_arith!(real: Integers, f64, Doubles);
+
+// Doubles
+impl_arith!(real: Doubles, Doubles, Doubles);
+impl_arith!(real: Doubles, Integers, Doubles);
+impl_arith!(real: Doubles, Rfloat, Doubles);
+impl_arith!(real: Doubles, f64, Doubles);
+impl_arith!(real: Doubles, Rint, Doubles);
+impl_arith!(real: Doubles, i32, Doubles);
+
+// Logicals
+impl_binop!(
+    BitAnd,
+    Logicals,
+    Logicals,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| a & b
+);
+impl_binop!(
+    BitAnd,
+    Logicals,
+    Rbool,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| a & b
+);
+impl_binop!(
+    BitAnd,
+    Logicals,
+    bool,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| { a & Rbool::from(b) }
+);
+impl_binop!(
+    BitOr,
+    Logicals,
+    Logicals,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| a | b
+);
+impl_binop!(
+    BitOr,
+    Logicals,
+    Rbool,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| a | b
+);
+impl_binop!(
+    BitOr,
+    Logicals,
+    bool,
+    Logicals,
+    logical_op,
+    |a: Rbool, b| { a | Rbool::from(b) }
+);
+
+// Matrices
+impl_arith!(integer: RMatrix<Rint>, RMatrix<Rint>, RMatrix<Rint>, RMatrix<Rfloat>);
+impl_arith!(integer: RMatrix<Rint>, Rint, RMatrix<Rint>, RMatrix<Rfloat>);
+impl_arith!(integer: RMatrix<Rint>, i32, RMatrix<Rint>, RMatrix<Rfloat>);
+impl_arith!(real: RMatrix<Rfloat>, RMatrix<Rfloat>, RMatrix<Rfloat>);
+impl_arith!(real: RMatrix<Rfloat>, Rfloat, RMatrix<Rfloat>);
+impl_arith!(real: RMatrix<Rfloat>, f64, RMatrix<Rfloat>);
+impl_arith!(real: RMatrix<f64>, RMatrix<f64>, RMatrix<f64>);
+impl_arith!(real: RMatrix<f64>, f64, RMatrix<f64>);
+impl_binop!(
+    BitAnd,
+    RMatrix<Rbool>,
+    RMatrix<Rbool>,
+    RMatrix<Rbool>,
+    logical_op,
+    |a: Rbool, b| a & b
+);
+impl_binop!(
+    BitOr,
+    RMatrix<Rbool>,
+    RMatrix<Rbool>,
+    RMatrix<Rbool>,
+    logical_op,
+    |a: Rbool, b| a | b
+);
+
+// Unary operators
+impl Neg for &Integers {
+    type Output = Integers;
+
+    fn neg(self) -> Integers {
+        Integers::from_result(or_throw(map_with(self, INTSXP, Kept::All, |x: Rint| -x)))
+    }
+}
+
+impl Neg for Integers {
+    type Output = Integers;
+
+    fn neg(self) -> Integers {
+        -&self
+    }
+}
+
+impl Neg for &Doubles {
+    type Output = Doubles;
+
+    fn neg(self) -> Doubles {
+        Doubles::from_result(or_throw(map_with(self, REALSXP, Kept::All, |x: Rfloat| -x)))
+    }
+}
+
+impl Neg for Doubles {
+    type Output = Doubles;
+
+    fn neg(self) -> Doubles {
+        -&self
+    }
+}
+
+impl Not for &Logicals {
+    type Output = Logicals;
+
+    fn not(self) -> Logicals {
+        Logicals::from_result(or_throw(map_with(
+            self,
+            LGLSXP,
+            Kept::Structure,
+            |x: Rbool| !x,
+        )))
+    }
+}
+
+impl Not for Logicals {
+    type Output = Logicals;
+
+    fn not(self) -> Logicals {
+        !&self
+    }
+}
+
+// Comparisons
+impl_comparisons!(Integers, Integers, Logicals, cmp_int::<Rint, Rint>);
+impl_comparisons!(scalar: Integers, Rint, Logicals, cmp_int::<Rint, Rint>);
+impl_comparisons!(scalar: Integers, i32, Logicals, cmp_int::<Rint, i32>);
+impl_comparisons!(Integers, Doubles, Logicals, cmp_real::<Rint, Rfloat>);
+impl_comparisons!(scalar: Integers, f64, Logicals, cmp_real::<Rint, f64>);
+impl_comparisons!(Doubles, Doubles, Logicals, cmp_real::<Rfloat, Rfloat>);
+impl_comparisons!(Doubles, Integers, Logicals, cmp_real::<Rfloat, Rint>);
+impl_comparisons!(scalar: Doubles, Rfloat, Logicals, cmp_real::<Rfloat, Rfloat>);
+impl_comparisons!(scalar: Doubles, f64, Logicals, cmp_real::<Rfloat, f64>);
+impl_comparisons!(Logicals, Logicals, Logicals, |a: Rbool, b: Rbool| a
+    .partial_cmp(&b));
+impl_comparisons!(
+    RMatrix<Rint>,
+    RMatrix<Rint>,
+    RMatrix<Rbool>,
+    cmp_int::<Rint, Rint>
+);
+impl_comparisons!(scalar: RMatrix<Rint>, i32, RMatrix<Rbool>, cmp_int::<Rint, i32>);
+impl_comparisons!(
+    RMatrix<Rfloat>,
+    RMatrix<Rfloat>,
+    RMatrix<Rbool>,
+    cmp_real::<Rfloat, Rfloat>
+);
+impl_comparisons!(scalar: RMatrix<Rfloat>, f64, RMatrix<Rbool>, cmp_real::<Rfloat, f64>);
+impl_comparisons!(
+    RMatrix<f64>,
+    RMatrix<f64>,
+    RMatrix<Rbool>,
+    cmp_real::<f64, f64>
+);
+impl_comparisons!(scalar: RMatrix<f64>, f64, RMatrix<Rbool>, cmp_real::<f64, f64>);
+
+#[cfg(test)]
+mod tests {
+    use crate as extendr_api;
+    use crate::prelude::*;
+
+    #[test]
+    fn integer_arithmetic() {
+        test! {
+            let x = Integers::from_values([1, 2, 3, 4]);
+            let y = Integers::from_values([Rint::from(10), Rint::na()]);
+            assert_eq!(Robj::from(&x + &y), R!("1:4 + c(10L, NA)")?);
+            assert_eq!(Robj::from(&x - 1), R!("1:4 - 1L")?);
+            assert_eq!(Robj::from(&x * &x), R!("1:4 * 1:4")?);
+            assert_eq!(Robj::from(&x / &y), R!("1:4 / c(10L, NA)")?);
+            assert_eq!(Robj::from(-x.clone()), R!("-(1:4)")?);
+
+            let big = Integers::from_values([i32::MAX, 1]);
+            assert_eq!(Robj::from(big + 1), R!("c(NA, 2L)")?);
+
+            let doubles = Doubles::from_values([0.5]);
+            assert_eq!(Robj::from(&x + &doubles), R!("1:4 + 0.5")?);
+            assert_eq!(Robj::from(&doubles * &x), R!("0.5 * 1:4")?);
+        }
+    }
+
+    #[test]
+    fn recycling() {
+        test! {
+            let x = Doubles::from_values([1.0, 2.0, 3.0]);
+            let y = Doubles::from_values([10.0, 20.0]);
+            assert_eq!(Robj::from(&x + &y), R!("c(11, 22, 13)")?);
+            assert_eq!(Robj::from(&x + &Doubles::new(0)).len(), 0);
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        test! {
+            let mut x = Doubles::from_values([1.0, 2.0]);
+            x.set_names(["a", "b"])?;
+            x.set_class(["myclass"])?;
+            let y = Doubles::from_values([1.0]);
+            assert_eq!(Robj::from(&y + &x), R!("structure(c(a = 2, b = 3), class = 'myclass')")?);
+            assert_eq!(Robj::from(x.equal(1.0)), R!("c(a = TRUE, b = FALSE)")?);
+
+            let m: RMatrix<f64> = R!("matrix(1:6 + 0, 2)")?.try_into()?;
+            let sum = &m + &m;
+            assert_eq!(sum.dim(), vec![2, 3]);
+            assert_eq!(Robj::from(sum), R!("matrix(1:6 * 2, 2)")?);
+            assert_eq!(Robj::from(m.greater_than(3.0)), R!("matrix(1:6 > 3, 2)")?);
+
+            let t: RMatrix<f64> = R!("matrix(1:6 + 0, 3)")?.try_into()?;
+            assert!(catch_r_error(|| unsafe { Robj::from(&m + &t).get() }).is_err());
+        }
+    }
+
+    #[test]
+    fn coded_operands() {
+        test! {
+            let factor: Integers = R!("factor(c('a', 'b'))")?.try_into()?;
+            assert!(catch_r_error(|| unsafe { Robj::from(&factor + 1).get() }).is_err());
+            assert!(catch_r_error(|| unsafe { Robj::from(factor.equal(1)).get() }).is_err());
+
+            let mut big = Doubles::from_values([1.0]);
+            big.set_class(["integer64"])?;
+            assert!(catch_r_error(|| unsafe { Robj::from(-&big).get() }).is_err());
+        }
+    }
+
+    #[test]
+    fn logical() {
+        test! {
+            let x = Logicals::from_values([TRUE, FALSE, NA_LOGICAL]);
+            let y = Logicals::from_values([NA_LOGICAL, NA_LOGICAL, TRUE]);
+            assert_eq!(Robj::from(&x & &y), R!("c(TRUE, FALSE, NA) & c(NA, NA, TRUE)")?);
+            assert_eq!(Robj::from(&x | &y), R!("c(TRUE, FALSE, NA) | c(NA, NA, TRUE)")?);
+            assert_eq!(Robj::from(!x.clone()), R!("!c(TRUE, FALSE, NA)")?);
+            assert_eq!(Robj::from(x & true), R!("c(TRUE, FALSE, NA)")?);
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        test! {
+            let x = Integers::from_values([Rint::from(1), Rint::from(5), Rint::na()]);
+            let y = Doubles::from_values([2.0, 2.0, 2.0]);
+            assert_eq!(Robj::from(x.less_than(&y)), R!("c(1L, 5L, NA) < 2")?);
+            assert_eq!(Robj::from(x.not_equal(5)), R!("c(1L, 5L, NA) != 5L")?);
+            assert_eq!(Robj::from(y.greater_equal(&x)), R!("2 >= c(1L, 5L, NA)")?);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::*;
+use extendr_ffi::{
+    R_MakeWeakRef, R_MakeWeakRefC, R_NilValue, R_WeakRefKey, R_WeakRefValue, Rboolean,
+};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+
+type Finalizer = Box<dyn FnOnce(Robj) + Send>;
+
+// Rust finalizers by the address of their key. R gives C finalizers the key only,
+// so every weak reference of a key runs one of the key's finalizers. R runs them
+// on its own thread, whichever thread registered them.
+static FINALIZERS: Lazy<Mutex<HashMap<usize, Vec<Finalizer>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn finalizers() -> std::sync::MutexGuard<'static, HashMap<usize, Vec<Finalizer>>> {
+    FINALIZERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Remove one of the finalizers of `key`. The lock is released before the
+// finalizer runs, as it may make weak references.
+fn take_finalizer(key: usize) -> Option<Finalizer> {
+    let mut finalizers = finalizers();
+    let pending = finalizers.get_mut(&key)?;
+    let finalizer = pending.pop();
+    if pending.is_empty() {
+        finalizers.remove(&key);
+    }
+    finalizer
+}
+
+extern "C" fn run_finalizer(key: SEXP) {
+    let finalizer = take_finalizer(key as usize);
+    if let Some(finalizer) = finalizer {
+        let key = unsafe { Robj::from_sexp(key) };
+        // A panic must not unwind into R.
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| finalizer(key)));
+    }
+}
+
+/// Wrapper for weak references (WEAKREFSXP).
+///
+/// A weak reference keeps its value alive for as long as its key is alive,
+/// without keeping the key alive. Keys must be environments, external pointers or `NULL`.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let key = new_env(Environment::global(), true, 0);
+///     let weak_ref = WeakRef::new(&key, r!("cached"))?;
+///     assert_eq!(weak_ref.key(), Robj::from(key));
+///     assert_eq!(weak_ref.value(), r!("cached"));
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct WeakRef {
+    pub(crate) robj: Robj,
+}
+
+impl WeakRef {
+    /// Make a weak reference from `key` to `value`.
+    pub fn new<K: Into<Robj>, V: Into<Robj>>(key: K, value: V) -> Result<Self> {
+        let key = weak_ref_key(key.into())?;
+        let value = value.into();
+        single_threaded(|| unsafe {
+            let sexp = R_MakeWeakRef(key.get(), value.get(), R_NilValue, Rboolean::FALSE);
+            Ok(WeakRef {
+                robj: Robj::from_sexp(sexp),
+            })
+        })
+    }
+
+    /// Make a weak reference from `key` to `value`, which calls `finalizer`
+    /// with the key once the key has been garbage collected, or when R exits.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// test! {
+    ///     let finalized = Arc::new(AtomicBool::new(false));
+    ///     let flag = finalized.clone();
+    ///     let key = ExternalPtr::new(1);
+    ///     let weak_ref = WeakRef::with_finalizer(key, (), move |_key| flag.store(true, Ordering::SeqCst))?;
+    ///
+    ///     R!("gc()")?;
+    ///     assert!(finalized.load(Ordering::SeqCst));
+    ///     assert_eq!(weak_ref.key(), r!(NULL));
+    /// }
+    /// ```
+    pub fn with_finalizer<K, V, F>(key: K, value: V, finalizer: F) -> Result<Self>
+    where
+        K: Into<Robj>,
+        V: Into<Robj>,
+        F: FnOnce(Robj) + Send + 'static,
+    {
+        let key = weak_ref_key(key.into())?;
+        // R never finalizes a NULL key, so the finalizer would be kept forever.
+        if key.is_null() {
+            return Err(Error::Other(
+                "weak references with finalizers need an environment or external pointer key"
+                    .into(),
+            ));
+        }
+        let value = value.into();
+        single_threaded(|| unsafe {
+            let sexp = R_MakeWeakRefC(key.get(), value.get(), Some(run_finalizer), Rboolean::TRUE);
+            finalizers()
+                .entry(key.get() as usize)
+                .or_default()
+                .push(Box::new(finalizer));
+            Ok(WeakRef {
+                robj: Robj::from_sexp(sexp),
+            })
+        })
+    }
+
+    /// Get the key of the weak reference. This is NULL once the key has been garbage collected.
+    pub fn key(&self) -> Robj {
+        unsafe { Robj::from_sexp(R_WeakRefKey(self.robj.get())) }
+    }
+
+    /// Get the value of the weak reference. This is NULL once the key has been garbage collected.
+    pub fn value(&self) -> Robj {
+        unsafe { Robj::from_sexp(R_WeakRefValue(self.robj.get())) }
+    }
+}
+
+fn weak_ref_key(key: Robj) -> Result<Robj> {
+    if key.is_environment() || key.is_external_pointer() || key.is_null() {
+        Ok(key)
+    } else {
+        Err(Error::Other(format!(
+            "weak reference keys must be environments, external pointers or NULL, got {:?}",
+            key.rtype()
+        )))
+    }
+}
+
+impl std::fmt::Debug for WeakRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakRef")
+            .field("key", &self.key())
+            .field("value", &self.value())
+            .finish()
+    }
+}
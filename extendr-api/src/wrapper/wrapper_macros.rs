@@ -171,7 +171,18 @@ make_conversions!(
 
 make_conversions!(Strings, ExpectedString, is_string, "Not a string vector");
 
+make_conversions!(
+    WeakRef,
+    ExpectedWeakRef,
+    is_weak_ref,
+    "Not a weak reference"
+);
+
 make_getsexp!(Dataframe<T>, impl<T>);
+make_getsexp!(Dates, impl);
+make_getsexp!(DateTimes, impl);
+make_getsexp!(Difftimes, impl);
+make_getsexp!(Integers64, impl);
 
 // impl Deref for Integers {
 //     type Target = [Rint];
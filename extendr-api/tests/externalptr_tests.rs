@@ -1,6 +1,92 @@
 use extendr_api::prelude::*;
 use once_cell::sync::Lazy;
 
+#[extendr(type_id = "extendr_tests::Counter/v1")]
+#[derive(Debug)]
+struct Counter {
+    count: i32,
+}
+
+#[extendr]
+impl Counter {
+    fn increment(&mut self) -> &mut Self {
+        self.count += 1;
+        self
+    }
+}
+
+#[extendr]
+#[derive(Debug)]
+struct Account {
+    balance: i32,
+}
+
+#[extendr]
+impl Account {
+    fn deposit(&mut self, amount: i32) {
+        self.balance += amount;
+    }
+
+    fn transfer(&mut self, from: &mut Account, amount: i32) {
+        from.balance -= amount;
+        self.balance += amount;
+    }
+
+    fn absorb(&mut self, mut other: ExternalPtr<Account>) {
+        self.balance += other.balance;
+        other.balance = 0;
+    }
+
+    fn compare(&self, other: &Account) -> bool {
+        self.balance == other.balance
+    }
+
+    // Call a method of `account` while `self` is borrowed, like an R callback could.
+    fn deposit_into(&mut self, account: Robj) -> bool {
+        let account = unsafe { account.get() };
+        let amount = r!(1);
+        let amount = unsafe { amount.get() };
+        catch_r_error(move || wrap__Account__deposit(account, amount)).is_ok()
+    }
+
+    fn freeze(&mut self) {
+        throw_r_error("the account is frozen");
+    }
+}
+
+#[extendr(shared)]
+#[derive(Debug)]
+struct Tally {
+    count: i32,
+}
+
+#[extendr(shared)]
+impl Tally {
+    fn add(&mut self, n: i32) -> i32 {
+        self.count += n;
+        self.count
+    }
+
+    fn count(&self) -> i32 {
+        self.count
+    }
+
+    // Call a method of `tally` while `self` is locked, like an R callback could.
+    fn add_to(&self, tally: Robj) -> bool {
+        let tally = unsafe { tally.get() };
+        let n = r!(1);
+        let n = unsafe { n.get() };
+        catch_r_error(move || wrap__Tally__add(tally, n)).is_ok()
+    }
+}
+
+#[extendr]
+fn shared_push(values: Shared<Vec<i32>>, value: i32) -> usize {
+    let mut values = values.lock();
+    values.push(value);
+    values.len()
+}
+
 #[test]
 fn test_externalptr() {
     test! {
@@ -60,3 +146,268 @@ fn test_externalptr_deref() {
         assert_eq!(extptr.y, 2);
     }
 }
+
+#[test]
+fn test_externalptr_from_static() {
+    test! {
+        static VALUES: [i32; 3] = [1, 2, 3];
+
+        let mut extptr = ExternalPtr::from_static(&VALUES);
+        assert_eq!(extptr.as_ref(), &[1, 2, 3]);
+        assert!(extptr.try_addr_mut().is_err());
+
+        // The pointer converts like an owning one.
+        let robj: Robj = extptr.into();
+        let extptr: ExternalPtr<[i32; 3]> = robj.try_into()?;
+        assert_eq!(extptr[2], 3);
+
+        // Closing does not drop borrowed data.
+        let mut extptr = extptr;
+        unsafe { extptr.close() };
+        assert_eq!(VALUES, [1, 2, 3]);
+    }
+}
+
+#[test]
+fn test_externalptr_from_raw() {
+    test! {
+        static RELEASED: Lazy<std::sync::Mutex<Vec<i32>>> =
+            Lazy::new(|| std::sync::Mutex::new(Vec::new()));
+
+        // Stand-ins for a C library's constructor and destructor.
+        fn create(value: i32) -> *mut i32 {
+            Box::into_raw(Box::new(value))
+        }
+        fn release(ptr: *mut i32) {
+            let value = unsafe { Box::from_raw(ptr) };
+            RELEASED.lock().unwrap().push(*value);
+        }
+
+        let mut extptr = unsafe { ExternalPtr::from_raw(create(1), release) };
+        *extptr += 1;
+        assert_eq!(*extptr, 2);
+
+        // Released when garbage collected.
+        drop(extptr);
+        R!("gc()")?;
+        assert_eq!(*RELEASED.lock().unwrap(), vec![2]);
+
+        // Released once when closed.
+        let mut extptr = unsafe { ExternalPtr::from_raw(create(3), release) };
+        unsafe { extptr.close() };
+        assert_eq!(*RELEASED.lock().unwrap(), vec![2, 3]);
+        drop(extptr);
+        R!("gc()")?;
+        assert_eq!(*RELEASED.lock().unwrap(), vec![2, 3]);
+
+        // A null pointer is never dereferenced.
+        let extptr = unsafe { ExternalPtr::<i32>::from_raw(std::ptr::null_mut(), |_| {}) };
+        assert!(extptr.try_addr().is_err());
+    }
+}
+
+#[test]
+fn test_externalptr_close() {
+    test! {
+        let mut extptr = ExternalPtr::new(vec![1, 2, 3]);
+        let robj: Robj = extptr.clone().into();
+        assert!(!extptr.is_closed());
+
+        unsafe { extptr.close() };
+        assert!(extptr.is_closed());
+        assert!(extptr.try_addr().is_err());
+        assert!(ExternalPtr::<Vec<i32>>::try_from(&robj).is_err());
+
+        // Closing twice, and finalizing a closed pointer, are fine.
+        unsafe { extptr.close() };
+        drop(extptr);
+        drop(robj);
+        R!("gc()")?;
+    }
+}
+
+#[test]
+fn test_externalptr_protected() {
+    test! {
+        let mut extptr = ExternalPtr::new(1);
+        assert!(extptr.protected().is_null());
+
+        let data = R!("c(1, 2, 3)")?;
+        extptr.set_protected(&data);
+        drop(data);
+        R!("gc()")?;
+        assert_eq!(extptr.protected(), r!([1., 2., 3.]));
+    }
+}
+
+#[test]
+fn test_weakref() {
+    test! {
+        let key = ExternalPtr::new(1);
+        let weak_ref = WeakRef::new(&key, r!("value"))?;
+        assert_eq!(weak_ref.key(), Robj::from(&key));
+        assert_eq!(weak_ref.value(), r!("value"));
+
+        let robj: Robj = weak_ref.clone().into();
+        assert_eq!(WeakRef::try_from(robj)?, weak_ref);
+        assert!(WeakRef::try_from(r!(1)).is_err());
+        assert!(WeakRef::new(r!(1), r!(2)).is_err());
+
+        drop(key);
+        R!("gc()")?;
+        assert!(weak_ref.key().is_null());
+        assert!(weak_ref.value().is_null());
+    }
+}
+
+#[test]
+fn test_weakref_finalizer() {
+    test! {
+        static FINALIZED: Lazy<std::sync::Mutex<Vec<String>>> =
+            Lazy::new(|| std::sync::Mutex::new(Vec::new()));
+
+        let key = ExternalPtr::new(String::from("key"));
+        let _first = WeakRef::with_finalizer(&key, (), |key| {
+            let key: ExternalPtr<String> = key.try_into().unwrap();
+            FINALIZED.lock().unwrap().push(format!("first {}", *key));
+        })?;
+        let _second = WeakRef::with_finalizer(&key, (), |_key| {
+            FINALIZED.lock().unwrap().push("second".into());
+        })?;
+
+        R!("gc()")?;
+        assert!(FINALIZED.lock().unwrap().is_empty());
+
+        drop(key);
+        R!("gc()")?;
+        let mut finalized = FINALIZED.lock().unwrap().clone();
+        finalized.sort();
+        assert_eq!(finalized, vec!["first key", "second"]);
+
+        // R never finalizes NULL keys.
+        assert!(WeakRef::with_finalizer((), (), |_key| {}).is_err());
+    }
+}
+
+#[test]
+fn test_externalptr_type_id() {
+    test! {
+        let robj: Robj = Counter { count: 1 }.into();
+        let counter = ExternalPtr::<Counter>::try_from_tagged(&robj)?;
+        assert_eq!(TypeAbi::from_tag(&counter.tag()), Some(TypeAbi::of::<Counter>()));
+        assert_eq!(registered_type("extendr_tests::Counter/v1"), Some(TypeAbi::of::<Counter>()));
+
+        // Methods of tagged types can return a reference to self.
+        let result = unsafe { Robj::from_sexp(wrap__Counter__increment(robj.get())) };
+        assert_eq!(result, robj);
+        let counter: &Counter = (&robj).try_into()?;
+        assert_eq!(counter.count, 2);
+
+        // Untagged pointers of the same type are accepted too.
+        let robj: Robj = ExternalPtr::new(Counter { count: 3 }).into();
+        assert_eq!(ExternalPtr::<Counter>::try_from_tagged(&robj)?.count, 3);
+        assert!(ExternalPtr::<Counter>::try_from_tagged(&r!(1)).is_err());
+    }
+}
+
+#[test]
+fn test_externalptr_borrows() {
+    test! {
+        let account: Robj = Account { balance: 10 }.into();
+        let other: Robj = Account { balance: 10 }.into();
+        let call = |result: std::result::Result<extendr_api::SEXP, _>| result.map(|sexp| unsafe { Robj::from_sexp(sexp) });
+
+        // Shared borrows of the same object are fine.
+        let same = call(catch_r_error(|| unsafe { wrap__Account__compare(account.get(), account.get()) }))?;
+        assert_eq!(same, r!(true));
+
+        // Two mutable borrows of the same object are not.
+        let amount = r!(5);
+        assert!(catch_r_error(|| unsafe {
+            wrap__Account__transfer(account.get(), account.get(), amount.get())
+        })
+        .is_err());
+        call(catch_r_error(|| unsafe {
+            wrap__Account__transfer(account.get(), other.get(), amount.get())
+        }))?;
+        assert_eq!(<&Account>::try_from(&account)?.balance, 15);
+        assert_eq!(<&Account>::try_from(&other)?.balance, 5);
+
+        // Nor are a mutable borrow and an `ExternalPtr` argument.
+        assert!(catch_r_error(|| unsafe { wrap__Account__absorb(other.get(), other.get()) }).is_err());
+        assert_eq!(<&Account>::try_from(&other)?.balance, 5);
+
+        // Nor is re-entering a method of an object that is borrowed mutably.
+        let reentered = call(catch_r_error(|| unsafe {
+            wrap__Account__deposit_into(account.get(), account.get())
+        }))?;
+        assert_eq!(reentered, r!(false));
+        let reentered = call(catch_r_error(|| unsafe {
+            wrap__Account__deposit_into(account.get(), other.get())
+        }))?;
+        assert_eq!(reentered, r!(true));
+        assert_eq!(<&Account>::try_from(&other)?.balance, 6);
+
+        // The borrows are released after each call.
+        call(catch_r_error(|| unsafe { wrap__Account__deposit(account.get(), amount.get()) }))?;
+        assert_eq!(<&Account>::try_from(&account)?.balance, 20);
+        call(catch_r_error(|| unsafe { wrap__Account__absorb(account.get(), other.get()) }))?;
+        assert_eq!(<&Account>::try_from(&account)?.balance, 26);
+        assert_eq!(<&Account>::try_from(&other)?.balance, 0);
+    }
+}
+
+#[test]
+fn test_borrows_after_r_error() {
+    test! {
+        let account: Robj = Account { balance: 10 }.into();
+
+        // An R error in a method releases its borrow of the object.
+        assert!(catch_r_error(|| unsafe { wrap__Account__freeze(account.get()) }).is_err());
+        let amount = r!(5);
+        assert!(catch_r_error(|| unsafe { wrap__Account__deposit(account.get(), amount.get()) }).is_ok());
+        assert_eq!(<&Account>::try_from(&account)?.balance, 15);
+    }
+}
+
+#[test]
+fn test_shared_impl() {
+    test! {
+        let tally: Robj = Tally { count: 0 }.into();
+        assert!(tally.inherits("Tally"));
+        let call = |result: std::result::Result<extendr_api::SEXP, _>| result.map(|sexp| unsafe { Robj::from_sexp(sexp) });
+
+        let n = r!(2);
+        assert_eq!(call(catch_r_error(|| unsafe { wrap__Tally__add(tally.get(), n.get()) }))?, r!(2));
+
+        // Other threads use the object through `Shared`.
+        let shared = Shared::<Tally>::try_from(&tally)?;
+        std::thread::spawn(move || shared.lock().count += 1).join().unwrap();
+        assert_eq!(call(catch_r_error(|| unsafe { wrap__Tally__count(tally.get()) }))?, r!(3));
+
+        // Re-entering a method of a locked object is an error rather than a deadlock.
+        let reentered = call(catch_r_error(|| unsafe { wrap__Tally__add_to(tally.get(), tally.get()) }))?;
+        assert_eq!(reentered, r!(false));
+        assert_eq!(call(catch_r_error(|| unsafe { wrap__Tally__count(tally.get()) }))?, r!(3));
+    }
+}
+
+#[test]
+fn test_shared() {
+    test! {
+        let values = Shared::new(vec![1]);
+        let robj: Robj = values.clone().into();
+        let value = r!(2);
+        let len = catch_r_error(|| unsafe { wrap__shared_push(robj.get(), value.get()) })?;
+        assert_eq!(unsafe { Robj::from_sexp(len) }, r!(2));
+
+        let worker = Shared::<Vec<i32>>::try_from(&robj)?;
+        std::thread::spawn(move || worker.lock().push(3)).join().unwrap();
+        assert_eq!(*values.lock(), [1, 2, 3]);
+
+        let guard = values.lock();
+        assert!(values.try_lock().is_err());
+        drop(guard);
+        assert!(ExternalPtr::<i32>::try_from(&robj).is_err());
+    }
+}
@@ -3,7 +3,7 @@ mod graphics_tests {
     use std::fmt::Write;
 
     use extendr_api::graphics::color::predefined::{antiquewhite, black, darkkhaki, deepskyblue};
-    use extendr_api::graphics::color::Color;
+    use extendr_api::graphics::color::{palette, Color, Hcl};
     use extendr_api::graphics::{
         Context, DevDesc, Device, DeviceDescriptor, DeviceDriver, FontFace, R_GE_gcontext, Raster,
         TextMetric, Unit,
@@ -356,4 +356,109 @@ mod graphics_tests {
             assert!(closed);
         }
     }
+
+    #[test]
+    fn color_parse_test() {
+        use std::str::FromStr;
+
+        assert_eq!(Color::from_str("#FF0000").unwrap(), Color::rgb(0xff, 0, 0));
+        assert_eq!(
+            Color::from_str("#ff000080").unwrap(),
+            Color::rgba(0xff, 0, 0, 0x80)
+        );
+        assert_eq!(Color::from_str("#F0F").unwrap(), Color::hex3(0xF0F));
+        assert_eq!(
+            Color::from_str("#F0F8").unwrap(),
+            Color::rgba(0xff, 0, 0xff, 0x88)
+        );
+        assert_eq!(
+            Color::from_str("transparent").unwrap(),
+            Color::transparent()
+        );
+        assert_eq!(Color::from_hex_str("#GG0000"), None);
+        assert_eq!(
+            Color::from_hex_str("#FF00"),
+            Some(Color::rgba(0xff, 0xff, 0, 0))
+        );
+
+        let c = Color::rgba(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(
+            (c.red(), c.green(), c.blue(), c.alpha()),
+            (0x12, 0x34, 0x56, 0x78)
+        );
+        assert_eq!(c.to_string(), "#12345678");
+        assert_eq!(c.with_alpha(0xff).to_string(), "#123456");
+        assert_eq!(Color::from_i32(c.to_i32()), c);
+
+        test! {
+            // Names and palette indices are resolved by R, like col2rgb().
+            assert_eq!(Color::from_str("steelblue")?, Color::hex(0x4682B4));
+            assert_eq!(Color::from_str("gray")?, Color::hex(0xBEBEBE));
+            assert!(Color::from_str("not-a-color").is_err());
+
+            let colors = Color::from_robj(&r!([Some("red"), Some("#0000FF80"), NA_STRING]))?;
+            assert_eq!(colors, vec![Color::hex(0xFF0000), Color::rgba(0, 0, 0xff, 0x80), Color::transparent()]);
+
+            let expected: Vec<Color> = R!("grDevices::palette()[1:3]")?.try_into()?;
+            assert_eq!(Color::from_robj(&r!([1, 2, 3]))?, expected);
+
+            // Colors become R color strings.
+            let robj = r!(vec![Color::hex(0xFF0000), Color::rgba(0, 0, 0xff, 0x80)]);
+            assert_eq!(robj, r!(["#FF0000", "#0000FF80"]));
+            assert_eq!(R!("grDevices::col2rgb({{robj}}, alpha = TRUE)[4, ]")?, r!([255, 128]));
+        }
+    }
+
+    #[test]
+    fn color_space_test() {
+        let steelblue = Color::hex(0x4682B4);
+
+        let hsv = steelblue.to_hsv();
+        assert!((hsv.h - 0.5757576).abs() < 1e-6);
+        assert!((hsv.s - 0.6111111).abs() < 1e-6);
+        assert!((hsv.v - 0.7058824).abs() < 1e-6);
+        assert_eq!(Color::from_hsv(hsv), steelblue);
+
+        assert_eq!(Color::from_lab(steelblue.to_lab()), steelblue);
+        assert_eq!(Color::from_linear_rgb(steelblue.to_linear_rgb()), steelblue);
+        assert_eq!(Color::from_hcl(steelblue.to_hcl()), steelblue);
+
+        // hcl(0, 0, 100) is white.
+        let white = Color::from_hcl(Hcl {
+            h: 0.,
+            c: 0.,
+            l: 100.,
+        });
+        assert_eq!(white, Color::hex(0xFFFFFF));
+        let lab = white.to_lab();
+        assert!((lab.l - 100.).abs() < 1e-2 && lab.a.abs() < 1e-2 && lab.b.abs() < 1e-2);
+
+        // Compositing.
+        let half_red = Color::rgba(0xff, 0, 0, 0x80);
+        assert_eq!(
+            half_red.over(Color::hex(0xFFFFFF)),
+            Color::rgb(0xff, 0x7f, 0x7f)
+        );
+        assert_eq!(Color::hex(0x0000FF).over(half_red), Color::hex(0x0000FF));
+        assert_eq!(
+            Color::hex(0x000000).mix(Color::hex(0xFFFFFF), 0.5),
+            Color::hex(0x808080)
+        );
+
+        // Palettes.
+        let hues = palette::qualitative(3, 15., 100., 65.);
+        assert_eq!(hues.len(), 3);
+        assert_eq!(palette::viridis(0), vec![]);
+        let viridis = palette::viridis(5);
+        assert_eq!(viridis.len(), 5);
+        assert!(viridis[0].to_hcl().l < viridis[4].to_hcl().l);
+        assert_eq!(
+            palette::gradient(Color::hex(0x000000), Color::hex(0xFFFFFF), 3),
+            vec![
+                Color::hex(0x000000),
+                Color::hex(0x808080),
+                Color::hex(0xFFFFFF)
+            ]
+        );
+    }
 }
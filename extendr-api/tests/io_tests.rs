@@ -44,3 +44,138 @@ UTF-8
         assert_eq!(res, Ok(Robj::from(1_i32)));
     }
 }
+
+#[test]
+fn test_captured_output() {
+    use extendr_api::prelude::*;
+    test! {
+        let (value, captured) = with_captured_output(|| {
+            rprintln!("from rust");
+            R!(r#"cat("no newline"); message("a message"); 1L"#)
+        })?;
+        assert_eq!(value?, r!(1));
+        assert_eq!(captured.output, "from rust\nno newline\n");
+        assert_eq!(captured.messages, "a message\n");
+
+        // The sinks are removed afterwards, also when the closure panics.
+        let panicked = std::panic::catch_unwind(|| {
+            with_captured_output(|| panic!("oops"))
+        });
+        assert!(panicked.is_err());
+        assert_eq!(R!("sink.number()")?, r!(0));
+        assert_eq!(R!(r#"sink.number(type = "message")"#)?, r!(2));
+
+        // Captures nest.
+        let ((_, inner), outer) = with_captured_output(|| {
+            rprintln!("outer");
+            with_captured_output(|| {
+                rprintln!("inner");
+            })
+            .unwrap()
+        })?;
+        assert_eq!(inner.output, "inner\n");
+        assert_eq!(outer.output, "outer\n");
+    }
+}
+
+#[test]
+fn test_io_errors() {
+    use extendr_api::{io::PstreamFormat, io::Save, test, Robj};
+    use std::io::{Read, Write};
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk on fire"))
+        }
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk on fire"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    test! {
+        let err = Robj::from_reader(&mut Failing, PstreamFormat::R_pstream_xdr_format, None).unwrap_err();
+        assert!(err.to_string().contains("disk on fire"));
+
+        // A truncated stream is an error, not an abort.
+        let mut w = Vec::new();
+        Robj::from(vec![1.0; 100]).to_writer(&mut w, PstreamFormat::R_pstream_xdr_format, 3, None)?;
+        let mut c = std::io::Cursor::new(&w[..w.len() / 2]);
+        assert!(Robj::from_reader(&mut c, PstreamFormat::R_pstream_xdr_format, None).is_err());
+
+        let err = Robj::from(1).to_writer(&mut Failing, PstreamFormat::R_pstream_xdr_format, 3, None).unwrap_err();
+        assert!(err.to_string().contains("disk on fire"));
+    }
+}
+
+#[test]
+fn test_rds() {
+    use extendr_api::io::{
+        load_rdata, read_rds, read_rds_with, write_rds_with, Compression, RdsOptions,
+    };
+    use extendr_api::prelude::*;
+    test! {
+        let dir = std::env::temp_dir();
+        let value = r!(list!(a = [1, 2, 3], b = "text"));
+
+        for (i, compression) in [Compression::None, Compression::Gzip, Compression::Bzip2, Compression::Xz]
+            .into_iter()
+            .enumerate()
+        {
+            let path = dir.join(format!("extendr_test_rds_{i}.rds"));
+            let options = RdsOptions { compression, ..Default::default() };
+            write_rds_with(&value, &path, &options)?;
+
+            let header = std::fs::read(&path).unwrap();
+            assert_eq!(Compression::detect(&header), compression);
+            assert_eq!(read_rds(&path)?, value);
+
+            // R reads what we write, and we read what R writes.
+            let path_str = path.to_str().unwrap();
+            assert_eq!(R!("readRDS({{path_str}})")?, value);
+            R!("saveRDS(1:3, {{path_str}}, version = 2)")?;
+            assert_eq!(read_rds(&path)?, r!([1, 2, 3]));
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        // Environments are passed through the refhook.
+        let path = dir.join("extendr_test_rds_refhook.rds");
+        let env = R!("new.env()")?;
+        let options = RdsOptions {
+            refhook: Some(R!("function(x) if (is.environment(x)) 'my env' else NULL")?.try_into()?),
+            ..Default::default()
+        };
+        write_rds_with(&env, &path, &options)?;
+        let options = RdsOptions {
+            refhook: Some(R!("function(x) paste('restored', x)")?.try_into()?),
+            ..Default::default()
+        };
+        assert_eq!(read_rds_with(&path, &options)?, r!("restored my env"));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(read_rds(dir.join("extendr_test_rds_missing.rds")).is_err());
+
+        // Invalid options leave an existing file untouched.
+        let path = dir.join("extendr_test_rds_invalid.rds");
+        write_rds_with(&value, &path, &RdsOptions::default())?;
+        let options = RdsOptions { version: 4, ..Default::default() };
+        assert!(write_rds_with(&r!(1), &path, &options).is_err());
+        assert_eq!(read_rds(&path)?, value);
+        std::fs::remove_file(&path).unwrap();
+
+        let path = dir.join("extendr_test_rdata.RData");
+        let path_str = path.to_str().unwrap();
+        R!("local({ x <- 42L; save(x, file = {{path_str}}) })")?;
+        let env = load_rdata(&path)?;
+        assert_eq!(env.local(sym!(x))?, r!(42));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta, Utc};
+use extendr_api::prelude::*;
+
+#[test]
+fn test_naive_date() {
+    test! {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let robj = Robj::from(date);
+        assert!(robj.inherits("Date"));
+        assert_eq!(R!("format({{&robj}})")?, r!("2024-02-29"));
+        assert_eq!(NaiveDate::try_from(robj)?, date);
+
+        let dates: Vec<Option<NaiveDate>> = R!("as.Date(c('1969-12-31', NA))")?.try_into()?;
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(1969, 12, 31), None]);
+        assert!(NaiveDate::try_from(R!("as.Date(NA)")?).is_err());
+        assert_eq!(Option::<NaiveDate>::try_from(R!("as.Date(NA)")?)?, None);
+
+        // Integer storage.
+        let dates: Vec<NaiveDate> = R!("structure(0L, class = 'Date')")?.try_into()?;
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()]);
+
+        assert!(NaiveDate::try_from(r!(1.0)).is_err());
+    }
+}
+
+#[test]
+fn test_date_time() {
+    test! {
+        let time = DateTime::<Utc>::from_timestamp(1_700_000_000, 500_000_000).unwrap();
+        let robj = Robj::from(time);
+        assert!(robj.inherits("POSIXct"));
+        assert_eq!(robj.get_attrib("tzone"), Some(r!("UTC")));
+        assert_eq!(R!("format({{&robj}}, '%Y-%m-%d %H:%M:%OS1')")?, r!("2023-11-14 22:13:20.5"));
+        assert_eq!(DateTime::<Utc>::try_from(&robj)?, time);
+
+        // The instant is kept, not the offset.
+        let offset = time.with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        assert_eq!(DateTime::<FixedOffset>::try_from(Robj::from(offset))?, time);
+
+        let times: Vec<Option<DateTime<Utc>>> =
+            R!("as.POSIXlt(c('2000-01-01 12:00:00', NA), tz = 'UTC')")?.try_into()?;
+        assert_eq!(times, vec![DateTime::from_timestamp(946_728_000, 0), None]);
+    }
+}
+
+#[test]
+fn test_time_delta() {
+    test! {
+        let delta = TimeDelta::try_minutes(90).unwrap();
+        let robj = Robj::from(delta);
+        assert!(robj.inherits("difftime"));
+        assert_eq!(TimeDelta::try_from(robj)?, delta);
+
+        let deltas: Vec<TimeDelta> = R!("as.difftime(c(-1.5, 2), units = 'days')")?.try_into()?;
+        assert_eq!(deltas, vec![TimeDelta::try_hours(-36).unwrap(), TimeDelta::try_days(2).unwrap()]);
+    }
+}
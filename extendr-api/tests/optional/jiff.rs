@@ -0,0 +1,27 @@
+use extendr_api::prelude::*;
+use jiff::{civil::date, SignedDuration, Timestamp, Zoned};
+
+#[test]
+fn test_jiff_conversions() {
+    test! {
+        let day = date(2024, 7, 1);
+        let robj = Robj::from(day);
+        assert_eq!(R!("format({{&robj}})")?, r!("2024-07-01"));
+        assert_eq!(jiff::civil::Date::try_from(robj)?, day);
+        assert_eq!(jiff::civil::Date::try_from(R!("as.Date('1900-03-01')")?)?, date(1900, 3, 1));
+
+        let stamp = Timestamp::from_second(1_000_000_000).unwrap();
+        assert_eq!(Timestamp::try_from(Robj::from(stamp))?, stamp);
+
+        // The time zone is kept in both directions.
+        let zoned: Zoned = R!("as.POSIXct('2024-07-01 12:00:00', tz = 'Europe/Paris')")?.try_into()?;
+        assert_eq!(zoned.time_zone().iana_name(), Some("Europe/Paris"));
+        assert_eq!(zoned.hour(), 12);
+        let robj = Robj::from(zoned.clone());
+        assert_eq!(robj.get_attrib("tzone"), Some(r!("Europe/Paris")));
+        assert_eq!(Zoned::try_from(robj)?, zoned);
+
+        let durations: Vec<Option<SignedDuration>> = R!("as.difftime(c(1, NA), units = 'hours')")?.try_into()?;
+        assert_eq!(durations, vec![Some(SignedDuration::from_hours(1)), None]);
+    }
+}
@@ -17,14 +17,23 @@
 //! should run all the tests defined here.
 //!
 
+#[cfg(feature = "chrono")]
+mod chrono;
+
 #[cfg(feature = "either")]
 mod either;
 
+#[cfg(feature = "jiff")]
+mod jiff;
+
 #[cfg(feature = "ndarray")]
 mod ndarray;
 
 #[cfg(feature = "serde")]
 mod serde;
 
+#[cfg(feature = "time")]
+mod time;
+
 #[cfg(feature = "non-api")]
 mod non_api;
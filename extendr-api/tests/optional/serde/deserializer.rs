@@ -1,6 +1,10 @@
-use extendr_api::deserializer::from_robj;
+use extendr_api::deserializer::{
+    borrow_slice, from_dataframe, from_robj, from_robj_with, DeserializerConfig,
+};
 use extendr_api::prelude::*;
-use extendr_api::serializer::to_robj;
+use extendr_api::serializer::{
+    to_dataframe, to_robj, to_robj_with, NoneAs, SerializerConfig, U64As, Variants, WithAttributes,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
@@ -247,3 +251,224 @@ fn test_deserialize_complex_named_list() {
         assert_eq!(lineage, round_trip);
     }
 }
+
+#[test]
+fn test_deserialize_dataframe() {
+    test! {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Species {
+            Setosa,
+            Versicolor,
+            Virginica,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Flower {
+            length: f64,
+            count: Option<i32>,
+            species: Species,
+        }
+
+        let df = R!("data.frame(
+            length = c(5.1, 7.0),
+            count = c(NA, 3L),
+            species = factor(c('Setosa', 'Versicolor'))
+        )")?;
+        let flowers: Vec<Flower> = from_dataframe(&df)?;
+        assert_eq!(
+            flowers,
+            vec![
+                Flower { length: 5.1, count: None, species: Species::Setosa },
+                Flower { length: 7.0, count: Some(3), species: Species::Versicolor },
+            ]
+        );
+
+        let round_trip: Vec<Flower> = from_dataframe(&to_dataframe(&flowers)?)?;
+        assert_eq!(round_trip, flowers);
+
+        let empty: Vec<Flower> = from_dataframe(&to_dataframe(Vec::<Flower>::new())?)?;
+        assert!(empty.is_empty());
+
+        assert!(from_dataframe::<Flower>(&r!(list!(length = 1.0))).is_err());
+    }
+}
+
+#[test]
+fn test_deserialize_with_config() {
+    test! {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Shape {
+            Point,
+            Circle(f64),
+            Rect { w: f64, h: f64 },
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Test {
+            shapes: Vec<Shape>,
+            names: Vec<Option<String>>,
+            opt: Option<i32>,
+            id: u64,
+        }
+
+        let test = Test {
+            shapes: vec![Shape::Point, Shape::Circle(1.0), Shape::Rect { w: 1.0, h: 2.0 }],
+            names: vec![Some("a".into()), None],
+            opt: None,
+            id: u64::MAX,
+        };
+
+        let variants = Variants::Adjacent { tag: "type", content: "value" };
+        let ser = SerializerConfig::new()
+            .none(NoneAs::Na)
+            .atomic_vectors(true)
+            .variants(variants)
+            .u64_as(U64As::String);
+        let de = DeserializerConfig::new().variants(variants);
+
+        let robj = to_robj_with(&test, ser)?;
+        assert_eq!(robj.dollar("names")?, r!([Some("a"), None]));
+        assert_eq!(from_robj_with::<Test>(&robj, de)?, test);
+        assert!(from_robj::<Test>(&robj).is_err());
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Wrapper {
+            value: Robj,
+        }
+        let robj: Robj = list!(value = R!("factor('a')")?).into();
+        let wrapper: Wrapper = from_robj_with(&robj, DeserializerConfig::new().keep_attributes(true))?;
+        assert!(wrapper.value.is_factor());
+        let wrapper: Wrapper = from_robj(&robj)?;
+        assert!(!wrapper.value.is_factor());
+    }
+}
+
+#[test]
+fn test_deserialize_with_attributes() {
+    test! {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Model {
+            coefficients: Vec<f64>,
+            method: String,
+        }
+
+        // An S3 object.
+        let robj = R!("structure(list(coefficients = c(1.5, 2), method = 'qr'), class = 'lm_fit')")?;
+        let model: WithAttributes<Model> = from_robj(&robj)?;
+        assert_eq!(model.value, Model { coefficients: vec![1.5, 2.0], method: "qr".into() });
+        assert_eq!(model.class(), Some(vec!["lm_fit"]));
+        assert_eq!(model.attribute("names"), Some(&r!(["coefficients", "method"])));
+
+        let config = SerializerConfig::new().atomic_vectors(true);
+        let round_trip = to_robj_with(&model, config)?;
+        assert_eq!(round_trip, robj);
+
+        // Attributes of nested values.
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Measurements {
+            when: WithAttributes<Vec<f64>>,
+            level: WithAttributes<Vec<i32>>,
+        }
+
+        let robj = R!("list(when = as.Date(c('2024-01-01', '2024-01-02')), level = factor(c('b', 'a')))")?;
+        let measurements: Measurements = from_robj(&robj)?;
+        assert_eq!(measurements.when.value, vec![19723.0, 19724.0]);
+        assert_eq!(measurements.when.class(), Some(vec!["Date"]));
+        assert_eq!(measurements.level.value, vec![2, 1]);
+        assert_eq!(measurements.level.attribute("levels"), Some(&r!(["a", "b"])));
+        assert_eq!(to_robj_with(&measurements, config)?, robj);
+
+        // Without attributes, the value is unchanged.
+        let plain = WithAttributes::new(vec![1, 2]);
+        assert_eq!(to_robj_with(&plain, config)?, r!([1, 2]));
+        let with_dim = plain.with_attribute("dim", [2, 1]);
+        assert_eq!(to_robj_with(&with_dim, config)?, R!("matrix(1:2, 2, 1)")?);
+    }
+}
+
+#[test]
+fn test_deserialize_borrowed() {
+    test! {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Borrowed<'a> {
+            name: &'a str,
+            #[serde(borrow)]
+            label: std::borrow::Cow<'a, str>,
+            tags: Vec<&'a str>,
+            bytes: &'a [u8],
+            #[serde(borrow, deserialize_with = "borrow_slice")]
+            doubles: &'a [f64],
+            #[serde(borrow, deserialize_with = "borrow_slice")]
+            ints: &'a [i32],
+            #[serde(borrow, deserialize_with = "borrow_slice")]
+            empty: &'a [f64],
+        }
+
+        let robj = R!("list(
+            name = 'x',
+            label = 'y',
+            tags = c('a', 'b'),
+            bytes = as.raw(1:3),
+            doubles = c(1.5, NA),
+            ints = c(1L, NA),
+            empty = double()
+        )")?;
+        let borrowed: Borrowed = from_robj(&robj)?;
+        assert_eq!(borrowed.name, "x");
+        assert!(matches!(borrowed.label, std::borrow::Cow::Borrowed("y")));
+        assert_eq!(borrowed.tags, ["a", "b"]);
+        assert_eq!(borrowed.bytes, [1, 2, 3]);
+        assert_eq!(borrowed.doubles[0], 1.5);
+        assert!(borrowed.doubles[1].is_na());
+        assert_eq!(borrowed.ints, [1, i32::MIN]);
+        assert!(borrowed.empty.is_empty());
+
+        // The slices are views of the vectors.
+        let doubles = robj.dollar("doubles")?;
+        assert_eq!(borrowed.doubles.as_ptr(), doubles.as_real_slice().unwrap().as_ptr());
+        let bytes = robj.dollar("bytes")?;
+        assert_eq!(borrowed.bytes.as_ptr(), bytes.as_raw_slice().unwrap().as_ptr());
+
+        let robj = R!("list(name = 'x', label = 'y', tags = 'a', bytes = raw(), doubles = 1L, ints = 1L, empty = double())")?;
+        assert!(from_robj::<Borrowed>(&robj).is_err());
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[extendr]
+fn scale_points(#[extendr(serde)] points: Vec<Point>, factor: Serde<f64>) -> Serde<Vec<Point>> {
+    Serde(
+        points
+            .into_iter()
+            .map(|p| Point {
+                x: p.x * *factor,
+                y: p.y * *factor,
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn test_deserialize_arguments() {
+    test! {
+        let points = r!(list!(list!(x = 1.0, y = 2.0), list!(x = 3.0, y = 4.0)));
+        let scaled = unsafe { Robj::from_sexp(wrap__scale_points(points.get(), r!(2).get())) };
+        assert_eq!(scaled, r!(list!(list!(x = 2.0, y = 4.0), list!(x = 6.0, y = 8.0))));
+
+        // Errors name the argument and the path of the element.
+        let points = r!(list!(list!(x = 1.0, y = 2.0), list!(x = 3.0, y = "a")));
+        let err = Serde::<Vec<Point>>::from_arg(&points, "points").unwrap_err();
+        assert_eq!(err.to_string(), "points[[2]]$y: Expected Numeric, got Strings");
+
+        let err = from_robj::<Vec<Point>>(&r!(list!(list!(x = 1.0)))).unwrap_err();
+        assert_eq!(err, Error::AtPath("[[1]]".into(), Box::new(Error::Other("missing field `y`".into()))));
+
+        let err = from_robj::<HashMap<String, Vec<i32>>>(&r!(list!(a = [1, 2], b = list!(1, "x")))).unwrap_err();
+        assert_eq!(err.to_string(), "$b[[2]]: Expected Numeric, got Strings");
+    }
+}
@@ -1,5 +1,8 @@
 use extendr_api::prelude::*;
-use extendr_api::serializer::to_robj;
+use extendr_api::serializer::{
+    to_dataframe, to_dataframe_with, to_robj, to_robj_with, NoneAs, SerializerConfig, U64As,
+    Variants,
+};
 use serde::Serialize;
 
 #[test]
@@ -152,3 +155,141 @@ fn test_serialize_robj() {
         assert_eq!(to_robj(&s).unwrap(), expected);
     }
 }
+
+#[test]
+fn test_serialize_dataframe() {
+    test! {
+        #[derive(Serialize)]
+        enum Level {
+            Low,
+            High,
+        }
+
+        #[derive(Serialize)]
+        struct Row {
+            id: i32,
+            score: Option<f64>,
+            label: String,
+            level: Level,
+            flag: Option<bool>,
+            tags: Vec<&'static str>,
+        }
+
+        let rows = vec![
+            Row { id: 1, score: Some(0.5), label: "a".into(), level: Level::Low, flag: Some(true), tags: vec!["x"] },
+            Row { id: 2, score: None, label: "b".into(), level: Level::High, flag: None, tags: vec![] },
+        ];
+
+        let df = to_dataframe(&rows)?;
+        let expected = R!("data.frame(
+            id = 1:2,
+            score = c(0.5, NA),
+            label = c('a', 'b'),
+            level = factor(c('Low', 'High'), levels = c('Low', 'High')),
+            flag = c(TRUE, NA)
+        )")?;
+        for column in ["id", "score", "label", "level", "flag"] {
+            assert_eq!(df.dollar(column)?, expected.dollar(column)?);
+        }
+        assert_eq!(df.dollar("tags")?, Robj::from(list!(list!("x"), list!())));
+        assert_eq!(R!("nrow")?.call(pairlist!(df))?, r!(2));
+
+        // Missing fields of maps are NA, and integers and doubles are doubles.
+        let rows = vec![
+            [("a", 1.0)].into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+            [("b", 2.0)].into_iter().collect(),
+        ];
+        let df = to_dataframe(&rows)?;
+        assert_eq!(df.dollar("a")?, r!([Some(1.0), None]));
+        assert_eq!(df.dollar("b")?, r!([None, Some(2.0)]));
+
+        assert!(to_dataframe([1, 2]).is_err());
+
+        // The options apply to the cells.
+        #[derive(Serialize)]
+        struct Big {
+            n: u64,
+        }
+        let config = SerializerConfig::new().u64_as(U64As::String);
+        let df = to_dataframe_with([Big { n: 1 << 60 }], config)?;
+        assert_eq!(df.dollar("n")?, r!(["1152921504606846976"]));
+    }
+}
+
+#[test]
+fn test_serialize_with_config() {
+    test! {
+        #[derive(Serialize)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        #[derive(Serialize)]
+        struct Test {
+            opt: Option<i32>,
+            ints: Vec<Option<i32>>,
+            strs: Vec<&'static str>,
+            mixed: Vec<f64>,
+            colors: Vec<Color>,
+            nested: Vec<Vec<i32>>,
+            empty: Vec<i32>,
+        }
+
+        let test = Test {
+            opt: None,
+            ints: vec![Some(1), None],
+            strs: vec!["a", "b"],
+            mixed: vec![1.5, 2.0],
+            colors: vec![Color::Green, Color::Red],
+            nested: vec![vec![1], vec![2, 3]],
+            empty: vec![],
+        };
+
+        let config = SerializerConfig::new().none(NoneAs::Na).atomic_vectors(true);
+        let robj = to_robj_with(&test, config)?;
+        assert_eq!(robj.dollar("opt")?, r!(NA_LOGICAL));
+        assert_eq!(robj.dollar("ints")?, r!([Some(1), None]));
+        assert_eq!(robj.dollar("strs")?, r!(["a", "b"]));
+        assert_eq!(robj.dollar("mixed")?, r!([1.5, 2.0]));
+        assert_eq!(robj.dollar("colors")?, R!("factor(c('Green', 'Red'), levels = c('Red', 'Green'))")?);
+        assert_eq!(robj.dollar("nested")?, Robj::from(list!(r!([1]), r!([2, 3]))));
+        assert_eq!(robj.dollar("empty")?, Robj::from(list!()));
+
+        // The default is unchanged.
+        let robj = to_robj(&test)?;
+        assert_eq!(robj.dollar("opt")?, r!(NULL));
+        assert_eq!(robj.dollar("ints")?, Robj::from(list!(1, NULL)));
+
+        let config = SerializerConfig::new().variants(Variants::Adjacent { tag: "t", content: "c" });
+        assert_eq!(to_robj_with(&Color::Red, config)?, Robj::from(list!(t = "Red")));
+        assert_eq!(to_robj_with(&Some(Color::Red), config)?, Robj::from(list!(t = "Red")));
+
+        let big = u64::MAX - 1;
+        assert_eq!(to_robj_with(&big, SerializerConfig::new())?, r!(big as f64));
+        assert_eq!(to_robj_with(&1_u64, SerializerConfig::new().u64_as(U64As::Error))?, r!(1.0));
+        assert!(to_robj_with(&big, SerializerConfig::new().u64_as(U64As::Error)).is_err());
+        assert_eq!(to_robj_with(&big, SerializerConfig::new().u64_as(U64As::String))?, r!(big.to_string()));
+        let robj = to_robj_with(&(1_u64 << 60), SerializerConfig::new().u64_as(U64As::Integer64))?;
+        assert_eq!(Integers64::try_from(&robj)?.elt(0), Some(1 << 60));
+        assert!(to_robj_with(&big, SerializerConfig::new().u64_as(U64As::Integer64)).is_err());
+
+        #[derive(Serialize)]
+        struct Wrapper {
+            value: Robj,
+        }
+        let factor = R!("factor('a')")?;
+        let wrapper = Wrapper { value: factor.clone() };
+        assert_eq!(to_robj(&wrapper)?.dollar("value")?, r!(1));
+        let robj = to_robj_with(&wrapper, SerializerConfig::new().keep_attributes(true))?;
+        assert_eq!(robj.dollar("value")?, factor);
+
+        // Only a real `Robj` is stored unchanged, whatever the name of a newtype.
+        #[derive(Serialize)]
+        #[serde(rename = "Robj")]
+        struct Address(u64);
+        let address = Address(unsafe { factor.get() } as usize as u64);
+        let robj = to_robj_with(&address, SerializerConfig::new().keep_attributes(true))?;
+        assert!(robj.is_real());
+    }
+}
@@ -0,0 +1,21 @@
+use extendr_api::prelude::*;
+use time::{Date, Duration, Month, OffsetDateTime};
+
+#[test]
+fn test_time_conversions() {
+    test! {
+        let date = Date::from_calendar_date(1999, Month::December, 31).unwrap();
+        let robj = Robj::from(vec![Some(date), None]);
+        assert_eq!(R!("format({{&robj}})")?, r!([Some("1999-12-31"), None]));
+        assert_eq!(Vec::<Option<Date>>::try_from(robj)?, vec![Some(date), None]);
+
+        let time = OffsetDateTime::from_unix_timestamp(-1).unwrap();
+        let robj = Robj::from(time);
+        assert_eq!(R!("format({{&robj}})")?, r!("1969-12-31 23:59:59"));
+        assert_eq!(OffsetDateTime::try_from(robj)?, time);
+
+        let duration = Duration::milliseconds(1500);
+        assert_eq!(Duration::try_from(Robj::from(duration))?, duration);
+        assert_eq!(Duration::try_from(R!("as.difftime(2, units = 'mins')")?)?, Duration::minutes(2));
+    }
+}
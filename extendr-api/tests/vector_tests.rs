@@ -362,3 +362,46 @@ mod num_complex {
         }
     }
 }
+
+#[test]
+fn test_integers64() {
+    test! {
+        let big = 9_007_199_254_740_993_i64; // 2^53 + 1
+        let mut vec = Integers64::from_values([Some(big), None, Some(-1)]);
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.is_integer64(), true);
+        assert_eq!(vec.iter().collect::<Vec<_>>(), vec![Some(big), None, Some(-1)]);
+        assert_eq!(vec.elt(3), None);
+
+        vec.set_elt(1, Some(7));
+        vec[2] = 8;
+        assert_eq!(&*vec, &[big, 7, 8]);
+
+        // Conversions from R.
+        let robj = Robj::from(vec);
+        assert_eq!(Vec::<i64>::try_from(&robj)?, vec![big, 7, 8]);
+        assert_eq!(i64::try_from(&Robj::from(Integers64::from_values([big])))?, big);
+        assert_eq!(f64::try_from(&Robj::from(Integers64::from_values([8])))?, 8.0);
+        assert!(i32::try_from(&Robj::from(Integers64::from_values([big]))).is_err());
+        assert!(Integers64::try_from(r!(1.0)).is_err());
+
+        // Other numeric vectors convert too.
+        assert_eq!(Vec::<Option<i64>>::try_from(&r!([Some(1), None]))?, vec![Some(1), None]);
+        assert_eq!(Vec::<Option<i64>>::try_from(&r!([Some(1.0), None]))?, vec![Some(1), None]);
+        assert!(Vec::<i64>::try_from(&r!([1.5])).is_err());
+    }
+}
+
+#[cfg(feature = "integer64")]
+#[test]
+fn test_i64_returns_integer64() {
+    test! {
+        let big = i64::MAX;
+        let robj = Robj::from(big);
+        assert_eq!(robj.inherits("integer64"), true);
+        assert_eq!(i64::try_from(&robj)?, big);
+
+        let robj = Robj::from(vec![Some(big), None]);
+        assert_eq!(Vec::<Option<i64>>::try_from(&robj)?, vec![Some(big), None]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Rust implementations of R's console callbacks.
+//!
+//! When an [`REngine`](crate::REngine) is started with a [`Console`], R's
+//! `ReadConsole`, `WriteConsoleEx` and `FlushConsole` hooks are pointed at
+//! the functions in this module, which forward to the installed console.
+
+use std::os::raw::{c_char, c_int, c_uchar};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Which of R's console streams a piece of text was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// Regular output, e.g. `print()` and `cat()`.
+    Output,
+    /// Errors, warnings and `message()`.
+    Error,
+}
+
+/// A console that R reads from and writes to.
+///
+/// Implementations must not call back into R.
+pub trait Console: Send {
+    /// Called for every piece of text R writes to the console.
+    fn write(&mut self, text: &str, kind: OutputKind);
+
+    /// Called when R flushes the console.
+    fn flush(&mut self) {}
+
+    /// Called when R needs a line of input, e.g. by `readline()` or the REPL.
+    ///
+    /// Return `None` to signal the end of input.
+    fn read(&mut self, _prompt: &str) -> Option<String> {
+        None
+    }
+}
+
+/// A [`Console`] that collects R's output in memory.
+///
+/// The buffer is cheap to clone; all clones share the same text.
+///
+/// ```no_run
+/// use extendr_engine::{OutputBuffer, REngine};
+///
+/// let buffer = OutputBuffer::new();
+/// let engine = REngine::builder().console(buffer.clone()).start().unwrap();
+/// // ... evaluate R code ...
+/// println!("R printed: {}", buffer.output());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer {
+    inner: Arc<Mutex<Captured>>,
+}
+
+#[derive(Debug, Default)]
+struct Captured {
+    output: String,
+    errors: String,
+}
+
+impl OutputBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything written to the output stream so far.
+    pub fn output(&self) -> String {
+        self.lock().output.clone()
+    }
+
+    /// Everything written to the error stream so far.
+    pub fn errors(&self) -> String {
+        self.lock().errors.clone()
+    }
+
+    /// Return the captured `(output, errors)` and clear the buffer.
+    pub fn take(&self) -> (String, String) {
+        let mut captured = self.lock();
+        (
+            std::mem::take(&mut captured.output),
+            std::mem::take(&mut captured.errors),
+        )
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Captured> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Console for OutputBuffer {
+    fn write(&mut self, text: &str, kind: OutputKind) {
+        let mut captured = self.lock();
+        match kind {
+            OutputKind::Output => captured.output.push_str(text),
+            OutputKind::Error => captured.errors.push_str(text),
+        }
+    }
+}
+
+static CONSOLE: Mutex<Option<Box<dyn Console>>> = Mutex::new(None);
+
+/// The part of the last line read that did not fit in R's buffer.
+static PENDING_INPUT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn with_console<T>(f: impl FnOnce(&mut dyn Console) -> T) -> Option<T> {
+    let mut console = CONSOLE.lock().unwrap_or_else(|e| e.into_inner());
+    console.as_mut().map(|console| f(console.as_mut()))
+}
+
+/// Install `console` and point R's console hooks at it.
+///
+/// # Safety
+///
+/// Must be called after `Rf_initialize_R` and from the thread running R.
+#[cfg(unix)]
+pub(crate) unsafe fn install(console: Box<dyn Console>) {
+    use extendr_ffi::embedding::*;
+
+    *CONSOLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(console);
+    PENDING_INPUT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+
+    // R only calls the hooks when these are NULL.
+    R_Outputfile = std::ptr::null_mut();
+    R_Consolefile = std::ptr::null_mut();
+
+    ptr_R_WriteConsole = None;
+    ptr_R_WriteConsoleEx = Some(write_console_ex);
+    ptr_R_ReadConsole = Some(read_console);
+    ptr_R_FlushConsole = Some(flush_console);
+}
+
+unsafe extern "C" fn write_console_ex(buf: *const c_char, len: c_int, otype: c_int) {
+    let bytes = std::slice::from_raw_parts(buf as *const u8, len.max(0) as usize);
+    let text = String::from_utf8_lossy(bytes);
+    let kind = if otype == 0 {
+        OutputKind::Output
+    } else {
+        OutputKind::Error
+    };
+    // Unwinding into R is undefined behaviour.
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        with_console(|console| console.write(&text, kind))
+    }));
+}
+
+unsafe extern "C" fn flush_console() {
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        with_console(|console| console.flush())
+    }));
+}
+
+unsafe extern "C" fn read_console(
+    prompt: *const c_char,
+    buf: *mut c_uchar,
+    len: c_int,
+    _add_history: c_int,
+) -> c_int {
+    let mut pending = PENDING_INPUT.lock().unwrap_or_else(|e| e.into_inner());
+    if pending.is_empty() {
+        let prompt = if prompt.is_null() {
+            String::new()
+        } else {
+            std::ffi::CStr::from_ptr(prompt)
+                .to_string_lossy()
+                .into_owned()
+        };
+        let line = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            with_console(|console| console.read(&prompt)).flatten()
+        }));
+        let Ok(Some(mut line)) = line else {
+            return 0;
+        };
+        if !line.ends_with('\n') {
+            line.push('\n');
+        }
+        *pending = line.into_bytes();
+    }
+
+    // R expects a NUL terminated line that fits in `len` bytes. Longer lines
+    // are passed on over several calls.
+    let capacity = (len.max(1) - 1) as usize;
+    let chunk = take_chunk(&mut pending, capacity);
+    std::ptr::copy_nonoverlapping(chunk.as_ptr(), buf, chunk.len());
+    *buf.add(chunk.len()) = 0;
+    1
+}
+
+/// Remove and return the longest prefix of `pending` that fits in `capacity`
+/// bytes without splitting a character. A buffer too small for the next
+/// character gets its first bytes, as R joins the bytes of a line again.
+fn take_chunk(pending: &mut Vec<u8>, capacity: usize) -> Vec<u8> {
+    if pending.len() <= capacity {
+        return std::mem::take(pending);
+    }
+    // UTF-8 continuation bytes are `0b10xx_xxxx`.
+    let end = (1..=capacity)
+        .rev()
+        .find(|&end| pending[end] & 0xc0 != 0x80)
+        .unwrap_or(capacity);
+    let rest = pending.split_off(end);
+    std::mem::replace(pending, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_chunk;
+
+    #[test]
+    fn long_lines_are_split_on_char_boundaries() {
+        let mut pending = "aé€b\n".as_bytes().to_vec();
+        assert_eq!(take_chunk(&mut pending, 4), "aé".as_bytes());
+        assert_eq!(take_chunk(&mut pending, 4), "€b".as_bytes());
+        assert_eq!(take_chunk(&mut pending, 4), b"\n");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn characters_are_split_for_tiny_buffers() {
+        let mut pending = "é\n".as_bytes().to_vec();
+        let mut chunks = Vec::new();
+        while !pending.is_empty() {
+            let chunk = take_chunk(&mut pending, 1);
+            assert_eq!(chunk.len(), 1);
+            chunks.extend(chunk);
+        }
+        assert_eq!(chunks, "é\n".as_bytes());
+    }
+}
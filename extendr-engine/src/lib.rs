@@ -45,8 +45,44 @@
 //!
 //! In a binary program, one may use [`start_r`] directly in the `main`-function.
 //!
-//! There is no `end_r`, as we terminate the R process setup, when the parent
-//! process terminates.
+//! To control how R is started, use [`REngine::builder`] instead. This sets
+//! the command line arguments, environment variables, library paths and
+//! the C stack limit, and can redirect R's console to a Rust [`Console`].
+//!
+//! ```no_run
+//! use extendr_engine::{OutputBuffer, REngine, StackLimit};
+//!
+//! let output = OutputBuffer::new();
+//! let engine = REngine::builder()
+//!     .arg("--vanilla")
+//!     .library_path("/opt/R/library")
+//!     .stack_limit(StackLimit::RDefault)
+//!     .console(output.clone())
+//!     .start()
+//!     .expect("failed to start R");
+//!
+//! // ... use R ...
+//!
+//! engine.shutdown();
+//! ```
+//!
+//! R is shut down when the parent process terminates, or explicitly with
+//! [`end_r`]. R cannot be restarted within the same process.
+//!
+//! An [`REngine`] can also drive R like the `R` and `Rscript` executables:
+//! [`REngine::repl`] runs the read-eval-print loop on the console input,
+//! [`REngine::run_main_loop`] hands the process over to R, and
+//! [`REngine::source_file`] runs a script and returns its status and last value.
+//!
+//! ```no_run
+//! use extendr_engine::REngine;
+//!
+//! fn main() {
+//!     let engine = REngine::builder().start().expect("failed to start R");
+//!     let outcome = engine.source_file("pipeline.R").expect("R is not running");
+//!     std::process::exit(outcome.status);
+//! }
+//! ```
 //!
 //! [`test!`]: https://docs.rs/extendr-api/latest/extendr_api/macro.test.html
 //!
@@ -64,58 +100,286 @@
 use extendr_ffi::{
     setup_Rmainloop, R_CStackLimit, R_CleanTempDir, R_RunExitFinalizers, Rf_initialize_R,
 };
+use std::ffi::{CString, OsString};
 use std::os::raw;
-use std::sync::Once;
-
-// Generate mutable static strings.
-// Much more efficient than `CString`.
-// Generates asciiz.
-macro_rules! cstr_mut {
-    ($s: expr) => {
-        concat!($s, "\0").as_ptr() as *mut raw::c_char
-    };
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub mod console;
+mod repl;
+pub use console::{Console, OutputBuffer, OutputKind};
+pub use repl::{RValue, ScriptOutcome};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EngineState {
+    NotStarted,
+    Running,
+    ShutDown,
 }
 
-static START_R: Once = Once::new();
+static STATE: Mutex<EngineState> = Mutex::new(EngineState::NotStarted);
 
-pub fn start_r() {
-    START_R.call_once(|| {
-        unsafe {
-            if std::env::var("R_HOME").is_err() {
-                // env! gets the build-time R_HOME stored by extendr-ffi
-                std::env::set_var("R_HOME", env!("R_HOME"));
+fn engine_state() -> std::sync::MutexGuard<'static, EngineState> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Errors from starting the embedded R process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    /// R is already running in this process.
+    AlreadyRunning,
+    /// R has been shut down and cannot be restarted.
+    ShutDown,
+    /// An argument or environment variable contains a nul byte.
+    InvalidArgument(String),
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::AlreadyRunning => write!(f, "R is already running in this process"),
+            EngineError::ShutDown => write!(f, "R has been shut down and cannot be restarted"),
+            EngineError::InvalidArgument(arg) => {
+                write!(f, "invalid argument for R: {arg:?}")
             }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// The C stack limit R checks against to detect infinite recursion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StackLimit {
+    /// Disable the check. R cannot know the stack size of threads created by
+    /// Rust, so this is the default.
+    #[default]
+    Unlimited,
+    /// Keep the limit R detects for the main thread.
+    RDefault,
+    /// Use a limit of this many bytes.
+    Bytes(usize),
+}
+
+/// Handle to the embedded R process, created by [`REngineBuilder::start`].
+///
+/// Dropping the handle does not stop R.
+#[derive(Debug)]
+pub struct REngine {
+    _private: (),
+}
+
+impl REngine {
+    /// Configure how R is started.
+    pub fn builder() -> REngineBuilder {
+        REngineBuilder::new()
+    }
+
+    /// True if R has been started and not shut down.
+    pub fn is_running() -> bool {
+        *engine_state() == EngineState::Running
+    }
 
+    /// Shut down R, see [`end_r`].
+    pub fn shutdown(self) {
+        end_r();
+    }
+}
+
+/// Builder for the embedded R process.
+///
+/// By default R is started as `R --slave --no-save`, with R's console
+/// connected to stdout/stderr and the C stack check disabled.
+pub struct REngineBuilder {
+    args: Vec<String>,
+    envs: Vec<(OsString, OsString)>,
+    library_paths: Vec<PathBuf>,
+    stack_limit: StackLimit,
+    #[cfg(unix)]
+    console: Option<Box<dyn Console>>,
+    #[cfg(unix)]
+    interactive: Option<bool>,
+}
+
+impl Default for REngineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl REngineBuilder {
+    pub fn new() -> Self {
+        Self {
+            args: vec!["--slave".to_string(), "--no-save".to_string()],
+            envs: Vec::new(),
+            library_paths: Vec::new(),
+            stack_limit: StackLimit::default(),
+            #[cfg(unix)]
+            console: None,
+            #[cfg(unix)]
+            interactive: None,
+        }
+    }
+
+    /// Add a command line argument, e.g. `--vanilla` or `--max-connections=256`.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add several command line arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Remove all arguments, including the default `--slave --no-save`.
+    pub fn clear_args(mut self) -> Self {
+        self.args.clear();
+        self
+    }
+
+    /// Set an environment variable before R starts, e.g. `R_DEFAULT_PACKAGES`.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a library path. These are prepended to `R_LIBS` and so come first in `.libPaths()`.
+    pub fn library_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.library_paths.push(path.into());
+        self
+    }
+
+    /// Set the C stack limit.
+    pub fn stack_limit(mut self, stack_limit: StackLimit) -> Self {
+        self.stack_limit = stack_limit;
+        self
+    }
+
+    /// Redirect R's console input and output to `console`.
+    ///
+    /// Only available on Unix-alikes, where R exposes its console hooks.
+    #[cfg(unix)]
+    pub fn console(mut self, console: impl Console + 'static) -> Self {
+        self.console = Some(Box::new(console));
+        self
+    }
+
+    /// Run R in interactive mode, as if attached to a terminal.
+    ///
+    /// In non-interactive mode an error in the [`REngine::repl`] loop ends the process.
+    /// Only available on Unix-alikes; by default R decides based on stdin.
+    #[cfg(unix)]
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = Some(interactive);
+        self
+    }
+
+    /// Start R. This can only succeed once per process.
+    pub fn start(self) -> Result<REngine, EngineError> {
+        let mut state = engine_state();
+        match *state {
+            EngineState::Running => return Err(EngineError::AlreadyRunning),
+            EngineState::ShutDown => return Err(EngineError::ShutDown),
+            EngineState::NotStarted => {}
+        }
+
+        let mut argv = Vec::with_capacity(self.args.len() + 1);
+        for arg in std::iter::once("R").chain(self.args.iter().map(String::as_str)) {
+            let arg = CString::new(arg).map_err(|_| EngineError::InvalidArgument(arg.into()))?;
+            argv.push(arg);
+        }
+
+        if std::env::var_os("R_HOME").is_none() {
+            // env! gets the build-time R_HOME stored by extendr-ffi
+            std::env::set_var("R_HOME", env!("R_HOME"));
+        }
+        for (key, value) in &self.envs {
+            std::env::set_var(key, value);
+        }
+        if !self.library_paths.is_empty() {
+            let existing = std::env::var_os("R_LIBS").unwrap_or_default();
+            let paths = self
+                .library_paths
+                .iter()
+                .cloned()
+                .chain(std::env::split_paths(&existing));
+            let r_libs = std::env::join_paths(paths)
+                .map_err(|e| EngineError::InvalidArgument(e.to_string()))?;
+            std::env::set_var("R_LIBS", r_libs);
+        }
+
+        unsafe {
             // Due to Rf_initEmbeddedR using __libc_stack_end
             // We can't call Rf_initEmbeddedR.
             // Instead we must follow rustr's example and call the parts.
 
-            //let res = unsafe { Rf_initEmbeddedR(1, args.as_mut_ptr()) };
-            // NOTE: R will crash if this is called twice in the same process.
-            Rf_initialize_R(
-                3,
-                [cstr_mut!("R"), cstr_mut!("--slave"), cstr_mut!("--no-save")].as_mut_ptr(),
+            // R keeps pointers into argv, so it must outlive R.
+            let argv: &'static mut [*mut raw::c_char] = Vec::leak(
+                argv.into_iter()
+                    .map(|arg| arg.into_raw())
+                    .collect::<Vec<_>>(),
             );
+            // NOTE: R will crash if this is called twice in the same process.
+            Rf_initialize_R(argv.len() as raw::c_int, argv.as_mut_ptr());
 
-            // In case you are curious.
-            // Maybe 8MB is a bit small.
-            // eprintln!("R_CStackLimit={:016x}", R_CStackLimit);
-            R_CStackLimit = usize::MAX;
+            #[cfg(unix)]
+            if let Some(console) = self.console {
+                console::install(console);
+            }
+            #[cfg(unix)]
+            if let Some(interactive) = self.interactive {
+                extendr_ffi::embedding::R_Interactive = interactive.into();
+            }
+
+            match self.stack_limit {
+                // In case you are curious.
+                // Maybe 8MB is a bit small.
+                // eprintln!("R_CStackLimit={:016x}", R_CStackLimit);
+                StackLimit::Unlimited => R_CStackLimit = usize::MAX,
+                StackLimit::RDefault => {}
+                StackLimit::Bytes(bytes) => R_CStackLimit = bytes,
+            }
 
             setup_Rmainloop();
         }
-    });
+
+        *state = EngineState::Running;
+        Ok(REngine { _private: () })
+    }
+}
+
+/// Start R with the default settings, if it is not already running.
+///
+/// See [`REngine::builder`] to configure the R process.
+pub fn start_r() {
+    match REngine::builder().start() {
+        Ok(_) | Err(EngineError::AlreadyRunning) => {}
+        Err(e) => panic!("{e}"),
+    }
 }
 
 /// Close down the R interpreter. Note you won't be able to
 /// Restart it, so use with care or not at all.
-fn end_r() {
+///
+/// Calling this more than once, or before R is started, does nothing.
+pub fn end_r() {
+    let mut state = engine_state();
+    if *state != EngineState::Running {
+        return;
+    }
     unsafe {
         //Rf_endEmbeddedR(0);
         R_RunExitFinalizers();
         //CleanEd();
         R_CleanTempDir();
     }
+    *state = EngineState::ShutDown;
 }
 
 /// Ensures that an embedded R instance is present when evaluating
@@ -129,9 +393,7 @@ pub fn with_r<T, E>(f: impl FnOnce() -> std::result::Result<T, E>) -> std::resul
 
 #[ctor::dtor]
 fn shutdown_r() {
-    if START_R.is_completed() {
-        end_r();
-    }
+    end_r();
 }
 
 #[cfg(test)]
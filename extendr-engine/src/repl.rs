@@ -0,0 +1,163 @@
+//! Running R code the way `R` and `Rscript` do.
+
+use crate::{EngineError, REngine};
+use extendr_ffi::embedding::{run_Rmainloop, R_ReplDLLdo1, R_ReplDLLinit};
+use extendr_ffi::{
+    cetype_t, R_GlobalEnv, R_NilValue, R_PreserveObject, R_ReleaseObject, R_tryEval,
+    Rf_allocVector, Rf_cons, Rf_install, Rf_lang1, Rf_lcons, Rf_mkCharLenCE, Rf_protect,
+    Rf_unprotect, Rf_xlength, LOGICAL, R_CHAR, SET_STRING_ELT, SET_TAG, SEXP, SEXPTYPE, STRING_ELT,
+    TYPEOF, VECTOR_ELT,
+};
+use std::ffi::CString;
+use std::os::raw;
+use std::path::Path;
+
+/// An R object kept alive on behalf of Rust, such as the value of a script.
+///
+/// Use `extendr_api::Robj::from_sexp(value.get())` to work with it.
+pub struct RValue {
+    sexp: SEXP,
+}
+
+impl RValue {
+    // Safety: `sexp` must be a valid R object.
+    unsafe fn new(sexp: SEXP) -> Self {
+        R_PreserveObject(sexp);
+        Self { sexp }
+    }
+
+    /// The underlying `SEXP`. It stays valid as long as `self` is alive.
+    pub fn get(&self) -> SEXP {
+        self.sexp
+    }
+}
+
+impl Drop for RValue {
+    fn drop(&mut self) {
+        if REngine::is_running() {
+            unsafe { R_ReleaseObject(self.sexp) };
+        }
+    }
+}
+
+impl std::fmt::Debug for RValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sexptype = unsafe { TYPEOF(self.sexp) };
+        f.debug_struct("RValue").field("type", &sexptype).finish()
+    }
+}
+
+/// The result of [`REngine::source_file`].
+#[derive(Debug)]
+pub struct ScriptOutcome {
+    /// The exit status `Rscript` would have returned: 0 on success, 1 on error.
+    pub status: i32,
+    /// The value of the last expression of the script, if it succeeded.
+    pub value: Option<RValue>,
+    /// R's error message, if the script failed.
+    pub error: Option<String>,
+}
+
+impl REngine {
+    /// Run R's read-eval-print loop until the console has no more input.
+    ///
+    /// Input is read through the [`Console`](crate::Console) given to the builder,
+    /// or from stdin. Calling `q()` from R ends the process.
+    pub fn repl(&self) -> Result<(), EngineError> {
+        if !REngine::is_running() {
+            return Err(EngineError::ShutDown);
+        }
+        unsafe {
+            R_ReplDLLinit();
+            while R_ReplDLLdo1() > 0 {}
+        }
+        Ok(())
+    }
+
+    /// Hand control to R's main loop, as the `R` executable does.
+    ///
+    /// This never returns: R exits the process when its input ends or `q()` is called.
+    pub fn run_main_loop(self) -> ! {
+        unsafe { run_Rmainloop() };
+        std::process::exit(0)
+    }
+
+    /// Run the script at `path` in the global environment, like `Rscript path`.
+    ///
+    /// Visible values are printed, and evaluation stops at the first error.
+    /// Unlike `Rscript`, an error does not end the process; it is reported in
+    /// the [`ScriptOutcome`].
+    pub fn source_file(&self, path: impl AsRef<Path>) -> Result<ScriptOutcome, EngineError> {
+        if !REngine::is_running() {
+            return Err(EngineError::ShutDown);
+        }
+        let path = path.as_ref().to_string_lossy().into_owned();
+        if path.contains('\0') {
+            return Err(EngineError::InvalidArgument(path));
+        }
+
+        unsafe {
+            // source(file = path, print.eval = TRUE)
+            let file = Rf_protect(Rf_allocVector(SEXPTYPE::STRSXP, 1));
+            SET_STRING_ELT(
+                file,
+                0,
+                Rf_mkCharLenCE(
+                    path.as_ptr() as *const raw::c_char,
+                    path.len() as raw::c_int,
+                    cetype_t::CE_UTF8,
+                ),
+            );
+            let print_eval = Rf_protect(Rf_allocVector(SEXPTYPE::LGLSXP, 1));
+            *LOGICAL(print_eval) = 1;
+            let args = Rf_protect(Rf_cons(print_eval, R_NilValue));
+            SET_TAG(args, install("print.eval"));
+            let args = Rf_protect(Rf_cons(file, args));
+            let call = Rf_protect(Rf_lcons(install("source"), args));
+
+            let mut error: raw::c_int = 0;
+            let result = R_tryEval(call, R_GlobalEnv, &mut error as *mut raw::c_int);
+            let outcome = if error != 0 {
+                ScriptOutcome {
+                    status: 1,
+                    value: None,
+                    error: Some(last_error_message()),
+                }
+            } else {
+                // source() returns list(value = , visible = )
+                Rf_protect(result);
+                let value = RValue::new(VECTOR_ELT(result, 0));
+                Rf_unprotect(1);
+                ScriptOutcome {
+                    status: 0,
+                    value: Some(value),
+                    error: None,
+                }
+            };
+            Rf_unprotect(5);
+            Ok(outcome)
+        }
+    }
+}
+
+unsafe fn install(name: &str) -> SEXP {
+    let name = CString::new(name).unwrap();
+    Rf_install(name.as_ptr())
+}
+
+// The message of the last error, as `geterrmessage()` returns it.
+unsafe fn last_error_message() -> String {
+    let call = Rf_protect(Rf_lang1(install("geterrmessage")));
+    let mut error: raw::c_int = 0;
+    let message = R_tryEval(call, R_GlobalEnv, &mut error as *mut raw::c_int);
+    let text = if error == 0 && TYPEOF(message) == SEXPTYPE::STRSXP && Rf_xlength(message) > 0 {
+        std::ffi::CStr::from_ptr(R_CHAR(STRING_ELT(message, 0)))
+            .to_string_lossy()
+            .trim_end()
+            .to_string()
+    } else {
+        String::new()
+    };
+    Rf_unprotect(1);
+    text
+}
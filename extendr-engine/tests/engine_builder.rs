@@ -0,0 +1,62 @@
+// R can only be started once per process, so this file holds a single test.
+#![cfg(unix)]
+
+use extendr_engine::{end_r, EngineError, OutputBuffer, REngine, StackLimit};
+use extendr_ffi::{R_GlobalEnv, Rf_protect, Rf_unprotect, LOGICAL, SEXP};
+use std::ffi::CString;
+
+#[allow(improper_ctypes)]
+extern "C" {
+    fn R_ParseEvalString(code: *const std::os::raw::c_char, env: SEXP) -> SEXP;
+}
+
+fn eval_is_true(code: &str) -> bool {
+    let code = CString::new(code).unwrap();
+    unsafe {
+        let value = Rf_protect(R_ParseEvalString(code.as_ptr(), R_GlobalEnv));
+        let result = *LOGICAL(value) == 1;
+        Rf_unprotect(1);
+        result
+    }
+}
+
+#[test]
+fn builder_configures_and_captures_r() {
+    let library = std::env::temp_dir();
+    let output = OutputBuffer::new();
+    let engine = REngine::builder()
+        .arg("--vanilla")
+        .env("EXTENDR_ENGINE_TEST", "yes")
+        .library_path(&library)
+        .stack_limit(StackLimit::RDefault)
+        .console(output.clone())
+        .start()
+        .unwrap();
+    assert!(REngine::is_running());
+    assert_eq!(
+        REngine::builder().start().unwrap_err(),
+        EngineError::AlreadyRunning
+    );
+
+    assert!(eval_is_true(
+        r#"Sys.getenv("EXTENDR_ENGINE_TEST") == "yes""#
+    ));
+    assert!(eval_is_true(r#"commandArgs()[2] == "--slave""#));
+    assert!(eval_is_true(&format!(
+        r#"normalizePath({:?}) %in% normalizePath(.libPaths())"#,
+        library.display().to_string()
+    )));
+
+    assert!(eval_is_true(r#"{ cat("hello\n"); message("oops"); TRUE }"#));
+    assert_eq!(output.take(), ("hello\n".to_string(), "oops\n".to_string()));
+    assert_eq!(output.output(), "");
+
+    engine.shutdown();
+    assert!(!REngine::is_running());
+    // Shutting down twice does nothing.
+    end_r();
+    assert_eq!(
+        REngine::builder().start().unwrap_err(),
+        EngineError::ShutDown
+    );
+}
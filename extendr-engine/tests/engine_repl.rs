@@ -0,0 +1,65 @@
+// R can only be started once per process, so this file holds a single test.
+#![cfg(unix)]
+
+use extendr_engine::{Console, OutputBuffer, OutputKind, REngine};
+use extendr_ffi::{Rf_xlength, REAL};
+use std::collections::VecDeque;
+
+// Feeds R a fixed list of lines and collects its output.
+struct ScriptedConsole {
+    input: VecDeque<String>,
+    output: OutputBuffer,
+}
+
+impl Console for ScriptedConsole {
+    fn write(&mut self, text: &str, kind: OutputKind) {
+        self.output.write(text, kind);
+    }
+
+    fn read(&mut self, _prompt: &str) -> Option<String> {
+        self.input.pop_front()
+    }
+}
+
+#[test]
+fn repl_and_scripts() {
+    let output = OutputBuffer::new();
+    let console = ScriptedConsole {
+        input: ["x <- 20", "x + 1", "stop('boom')", "x * 2"]
+            .map(String::from)
+            .into(),
+        output: output.clone(),
+    };
+    let engine = REngine::builder()
+        .interactive(true)
+        .console(console)
+        .start()
+        .unwrap();
+
+    // Errors do not end the loop in interactive mode.
+    engine.repl().unwrap();
+    let (printed, errors) = output.take();
+    assert!(printed.contains("[1] 21\n"));
+    assert!(printed.contains("[1] 40\n"));
+    assert!(errors.contains("boom"));
+
+    let dir = std::env::temp_dir();
+    let script = dir.join("extendr_engine_script.R");
+    std::fs::write(&script, "y <- x + 22\nprint('hello')\ny\n").unwrap();
+    let outcome = engine.source_file(&script).unwrap();
+    assert_eq!(outcome.status, 0);
+    assert_eq!(outcome.error, None);
+    let value = outcome.value.unwrap();
+    unsafe {
+        assert_eq!(Rf_xlength(value.get()), 1);
+        assert_eq!(*REAL(value.get()), 42.);
+    }
+    assert_eq!(output.take().0, "[1] \"hello\"\n[1] 42\n");
+
+    std::fs::write(&script, "z <- 1\nstop('script failed')\nz <- 2\n").unwrap();
+    let outcome = engine.source_file(&script).unwrap();
+    assert_eq!(outcome.status, 1);
+    assert!(outcome.value.is_none());
+    assert!(outcome.error.unwrap().contains("script failed"));
+    std::fs::remove_file(&script).unwrap();
+}
@@ -0,0 +1,42 @@
+//! Bindings to R's embedding interface (`Rembedded.h` and `Rinterface.h`)
+//!
+//! These are used by `extendr-engine` to run R inside a Rust process.
+//! They are not part of the API available to R packages.
+
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+
+/// Writes `len` bytes of `buf` to the console, `otype` is 0 for output and 1 for errors/warnings.
+pub type WriteConsoleEx = unsafe extern "C" fn(buf: *const c_char, len: c_int, otype: c_int);
+
+/// Reads at most `len` bytes of a line into `buf` after showing `prompt`.
+/// Returns 0 on end of input.
+pub type ReadConsole = unsafe extern "C" fn(
+    prompt: *const c_char,
+    buf: *mut c_uchar,
+    len: c_int,
+    add_history: c_int,
+) -> c_int;
+
+extern "C" {
+    /// Run R's read-eval-print loop. Only returns by exiting the process.
+    pub fn run_Rmainloop();
+    pub fn R_ReplDLLinit();
+    /// Run one iteration of the read-eval-print loop. Returns -1 at the end of input.
+    pub fn R_ReplDLLdo1() -> c_int;
+}
+
+#[cfg(unix)]
+extern "C" {
+    pub static mut R_Interactive: crate::Rboolean;
+
+    /// `FILE *` used for console output when `ptr_R_WriteConsole*` are not set.
+    pub static mut R_Outputfile: *mut c_void;
+    /// `FILE *` used for console errors when `ptr_R_WriteConsole*` are not set.
+    pub static mut R_Consolefile: *mut c_void;
+
+    pub static mut ptr_R_WriteConsole: Option<unsafe extern "C" fn(buf: *const c_char, len: c_int)>;
+    pub static mut ptr_R_WriteConsoleEx: Option<WriteConsoleEx>;
+    pub static mut ptr_R_ReadConsole: Option<ReadConsole>;
+    pub static mut ptr_R_FlushConsole: Option<unsafe extern "C" fn()>;
+    pub static mut ptr_R_ResetConsole: Option<unsafe extern "C" fn()>;
+}
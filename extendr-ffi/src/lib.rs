@@ -14,6 +14,7 @@ mod symbols;
 pub use symbols::*;
 pub mod backports;
 pub use backports::*;
+pub mod embedding;
 
 #[cfg(feature = "non-api")]
 mod non_api;
@@ -134,6 +135,7 @@ pub enum cetype_t {
 #[doc = "Finalization interface"]
 pub type R_CFinalizer_t = ::std::option::Option<unsafe extern "C" fn(arg1: SEXP)>;
 pub type Int32 = ::std::os::raw::c_uint;
+pub type PROTECT_INDEX = ::std::os::raw::c_int;
 #[doc = "R 4.3 redefined `Rcomplex` to a union for compatibility with Fortran.\n But the old definition is compatible both the union version\n and the struct version.\n See: <https://github.com/extendr/extendr/issues/524>\n <div rustbindgen replaces=\"Rcomplex\"></div>"]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -301,6 +303,12 @@ extern "C" {
     pub fn R_SetExternalPtrAddr(s: SEXP, p: *mut ::std::os::raw::c_void);
     pub fn R_SetExternalPtrTag(s: SEXP, tag: SEXP);
     pub fn R_SetExternalPtrProtected(s: SEXP, p: SEXP);
+    #[doc = "Weak reference interface"]
+    pub fn R_MakeWeakRef(key: SEXP, val: SEXP, fin: SEXP, onexit: Rboolean) -> SEXP;
+    pub fn R_MakeWeakRefC(key: SEXP, val: SEXP, fin: R_CFinalizer_t, onexit: Rboolean) -> SEXP;
+    pub fn R_WeakRefKey(w: SEXP) -> SEXP;
+    pub fn R_WeakRefValue(w: SEXP) -> SEXP;
+    pub fn R_RunWeakRefFinalizer(w: SEXP);
     pub fn R_compute_identical(arg1: SEXP, arg2: SEXP, arg3: ::std::os::raw::c_int) -> Rboolean;
     #[doc = "C stack limit"]
     pub static mut R_CStackLimit: usize;
@@ -333,6 +341,7 @@ extern "C" {
     ) -> SEXP;
     #[doc = "preserve objects across GCs"]
     pub fn R_PreserveObject(arg1: SEXP);
+    pub fn R_ProtectWithIndex(arg1: SEXP, arg2: *mut PROTECT_INDEX);
     pub fn R_RegisterCFinalizerEx(s: SEXP, fun: R_CFinalizer_t, onexit: Rboolean);
     pub fn R_registerRoutines(
         info: *mut DllInfo,
@@ -342,6 +351,7 @@ extern "C" {
         externalRoutines: *const R_ExternalMethodDef,
     ) -> ::std::os::raw::c_int;
     pub fn R_ReleaseObject(arg1: SEXP);
+    pub fn R_Reprotect(arg1: SEXP, arg2: PROTECT_INDEX);
     pub fn R_RunExitFinalizers();
     pub fn R_Serialize(s: SEXP, ops: R_outpstream_t);
     #[doc = "Current srcref, for debuggers"]
@@ -376,6 +386,7 @@ extern "C" {
     pub fn Rf_coerceVector(arg1: SEXP, arg2: SEXPTYPE) -> SEXP;
     pub fn Rf_conformable(arg1: SEXP, arg2: SEXP) -> Rboolean;
     pub fn Rf_cons(arg1: SEXP, arg2: SEXP) -> SEXP;
+    pub fn Rf_copyMostAttrib(arg1: SEXP, arg2: SEXP);
     pub fn Rf_defineVar(arg1: SEXP, arg2: SEXP, arg3: SEXP);
     pub fn Rf_dimgets(arg1: SEXP, arg2: SEXP) -> SEXP;
     pub fn Rf_dimnamesgets(arg1: SEXP, arg2: SEXP) -> SEXP;
@@ -386,6 +397,7 @@ extern "C" {
     pub fn Rf_findFun(arg1: SEXP, arg2: SEXP) -> SEXP;
     pub fn Rf_GetArrayDimnames(arg1: SEXP) -> SEXP;
     pub fn Rf_getAttrib(arg1: SEXP, arg2: SEXP) -> SEXP;
+    pub fn Rf_getCharCE(arg1: SEXP) -> cetype_t;
     pub fn Rf_GetColNames(arg1: SEXP) -> SEXP;
     pub fn Rf_GetRowNames(arg1: SEXP) -> SEXP;
     pub fn Rf_initialize_R(
@@ -436,10 +448,12 @@ extern "C" {
     pub fn Rf_runif(arg1: f64, arg2: f64) -> f64;
     pub fn Rf_ScalarInteger(arg1: ::std::os::raw::c_int) -> SEXP;
     pub fn Rf_setAttrib(arg1: SEXP, arg2: SEXP, arg3: SEXP) -> SEXP;
+    pub fn Rf_translateCharUTF8(arg1: SEXP) -> *const ::std::os::raw::c_char;
     pub fn Rf_unprotect(arg1: ::std::os::raw::c_int);
     pub fn Rf_VectorToPairList(x: SEXP) -> SEXP;
     pub fn Rf_xlength(arg1: SEXP) -> R_xlen_t;
     pub fn Rf_xlengthgets(arg1: SEXP, arg2: R_xlen_t) -> SEXP;
+    pub fn ATTRIB(x: SEXP) -> SEXP;
     pub fn SET_ATTRIB(x: SEXP, v: SEXP);
     pub fn SET_INTEGER_ELT(x: SEXP, i: R_xlen_t, v: ::std::os::raw::c_int);
     pub fn SET_OBJECT(x: SEXP, v: ::std::os::raw::c_int);
@@ -449,6 +463,9 @@ extern "C" {
     pub fn SET_VECTOR_ELT(x: SEXP, i: R_xlen_t, v: SEXP) -> SEXP;
     pub fn SETCDR(x: SEXP, y: SEXP) -> SEXP;
     pub fn setup_Rmainloop();
+    #[doc = "stack-based memory allocation, freed by `vmaxset`"]
+    pub fn vmaxget() -> *mut ::std::os::raw::c_void;
+    pub fn vmaxset(arg1: *const ::std::os::raw::c_void);
     pub fn REAL_ELT(x: SEXP, i: R_xlen_t) -> f64;
     pub fn COMPLEX_ELT(x: SEXP, i: R_xlen_t) -> Rcomplex;
     pub fn INTEGER_ELT(x: SEXP, i: R_xlen_t) -> ::std::os::raw::c_int;
@@ -41,7 +41,7 @@ pub(crate) fn extendr_type_conversion(item: Item, opts: &ExtendrOptions) -> Toke
     }
 }
 
-fn do_extendr_type_conversion(item: Item, _opts: &ExtendrOptions) -> syn::Result<TokenStream> {
+fn do_extendr_type_conversion(item: Item, opts: &ExtendrOptions) -> syn::Result<TokenStream> {
     let TypeFields {
         ident: self_ty,
         generics,
@@ -64,26 +64,65 @@ fn do_extendr_type_conversion(item: Item, _opts: &ExtendrOptions) -> syn::Result
     // block makes it to R.
     let _doc_string = wrappers::get_doc_string(&attrs);
 
+    // Shared types are only reachable through `Shared<T>`, which locks them.
+    if opts.shared {
+        return Ok(TokenStream::from(quote! {
+            #item
+
+            // Output conversion function for this type.
+            impl From<#self_ty> for Robj {
+                fn from(value: #self_ty) -> Self {
+                    let mut res: Robj = extendr_api::Shared::new(value).into();
+                    res.set_attrib(class_symbol(), #self_ty_name).unwrap();
+                    res
+                }
+            }
+        }));
+    }
+
+    // Types with a stable type id use tagged external pointers,
+    // which can be shared with other packages.
+    let (type_tag_impl, try_from_ref, try_from_mut, new_ptr) = match &opts.type_id {
+        Some(type_id) => (
+            quote! {
+                impl extendr_api::TypeTag for #self_ty {
+                    const TYPE_ID: &'static str = #type_id;
+                }
+            },
+            quote!(ExternalPtr::<#self_ty>::try_from_tagged(robj)?),
+            quote!(ExternalPtr::<#self_ty>::try_from_tagged_mut(robj)?),
+            quote!(ExternalPtr::new_tagged(value)),
+        ),
+        None => (
+            quote!(),
+            quote!(robj.try_into()?),
+            quote!(robj.try_into()?),
+            quote!(ExternalPtr::new(value)),
+        ),
+    };
+
     let conversion_impls = quote! {
+        #type_tag_impl
+
         // Output conversion function for this type.
-        impl TryFrom<&Robj> for &#self_ty {
+        impl<'a> TryFrom<&'a Robj> for &'a #self_ty {
             type Error = extendr_api::Error;
-            fn try_from(robj: &Robj) -> extendr_api::Result<Self> {
+            fn try_from(robj: &'a Robj) -> extendr_api::Result<Self> {
                 use extendr_api::ExternalPtr;
                 unsafe {
-                    let external_ptr: &ExternalPtr<#self_ty> = robj.try_into()?;
+                    let external_ptr: &ExternalPtr<#self_ty> = #try_from_ref;
                     external_ptr.try_addr()
                 }
             }
         }
 
         // Input conversion function for a mutable reference to this type.
-        impl TryFrom<&mut Robj> for &mut #self_ty {
+        impl<'a> TryFrom<&'a mut Robj> for &'a mut #self_ty {
             type Error = extendr_api::Error;
-            fn try_from(robj: &mut Robj) -> extendr_api::Result<Self> {
+            fn try_from(robj: &'a mut Robj) -> extendr_api::Result<Self> {
                 use extendr_api::ExternalPtr;
                 unsafe {
-                    let external_ptr: &mut ExternalPtr<#self_ty> = robj.try_into()?;
+                    let external_ptr: &mut ExternalPtr<#self_ty> = #try_from_mut;
                     external_ptr.try_addr_mut()
                 }
             }
@@ -100,7 +139,7 @@ fn do_extendr_type_conversion(item: Item, _opts: &ExtendrOptions) -> syn::Result
             fn from(value: #self_ty) -> Self {
                 use extendr_api::ExternalPtr;
                 unsafe {
-                    let mut res: ExternalPtr<#self_ty> = ExternalPtr::new(value);
+                    let mut res: ExternalPtr<#self_ty> = #new_ptr;
                     res.set_attrib(class_symbol(), #self_ty_name).unwrap();
                     res.into()
                 }
@@ -11,6 +11,12 @@ pub(crate) struct ExtendrOptions {
     /// R. See [WRE, Section 6.3 number generation](https://cran.r-project.org/doc/manuals/R-exts.html#Random-numbers)
     pub use_rng: bool,
     pub invisible: Option<bool>,
+    /// Stable type id of a `struct` or `enum` stored in external pointers,
+    /// see `extendr_api::TypeTag`.
+    pub type_id: Option<String>,
+    /// Store a `struct` or `enum` as `extendr_api::Shared<T>`, and lock it in
+    /// the methods of its `impl` block.
+    pub shared: bool,
 }
 
 impl ExtendrOptions {
@@ -20,6 +26,8 @@ impl ExtendrOptions {
     ///
     /// - `r_name = "name"` which specifies the name of the wrapper on the R-side.
     /// - `use_rng = bool` ensures the RNG-state is pulled and pushed
+    /// - `type_id = "mypkg::Type/v1"` gives a `struct` or `enum` a stable type id
+    /// - `shared` stores a `struct` or `enum` in an `Arc<Mutex<T>>`; use it on its `impl` too
     ///
     pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::parse::Result<()> {
         let path = meta
@@ -32,6 +40,10 @@ impl ExtendrOptions {
                 self.invisible = Some(true);
                 Ok(())
             }
+            "shared" => {
+                self.shared = true;
+                Ok(())
+            }
             _ => {
                 let value = meta.value()?;
                 match path.to_string().as_str() {
@@ -51,6 +63,14 @@ impl ExtendrOptions {
                             Err(value.error("`mod_name` must be a string literal"))
                         }
                     }
+                    "type_id" => {
+                        if let Ok(Lit::Str(litstr)) = value.parse() {
+                            self.type_id = Some(litstr.value());
+                            Ok(())
+                        } else {
+                            Err(value.error("`type_id` must be a string literal"))
+                        }
+                    }
                     "use_rng" => {
                         if let Ok(LitBool { value, .. }) = value.parse() {
                             self.use_rng = value;
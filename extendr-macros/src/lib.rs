@@ -91,7 +91,28 @@ pub fn extendr(attr: TokenStream, item: TokenStream) -> TokenStream {
     let extendr_opts_parser = syn::meta::parser(|meta| opts.parse(meta));
     parse_macro_input!(attr with extendr_opts_parser);
 
-    match parse_macro_input!(item as Item) {
+    let item = parse_macro_input!(item as Item);
+    if opts.type_id.is_some() && !matches!(item, Item::Struct(_) | Item::Enum(_)) {
+        return syn::Error::new_spanned(item, "`type_id` is only allowed on `struct` and `enum`")
+            .into_compile_error()
+            .into();
+    }
+
+    if opts.shared && !matches!(item, Item::Struct(_) | Item::Enum(_) | Item::Impl(_)) {
+        return syn::Error::new_spanned(
+            item,
+            "`shared` is only allowed on `struct`, `enum` and `impl`",
+        )
+        .into_compile_error()
+        .into();
+    }
+    if opts.shared && opts.type_id.is_some() {
+        return syn::Error::new_spanned(item, "`shared` types cannot have a `type_id`")
+            .into_compile_error()
+            .into();
+    }
+
+    match item {
         Item::Struct(str) => {
             let struct_name = str.ident.to_string();
             let struct_doc = crate::wrappers::get_doc_string(&str.attrs);
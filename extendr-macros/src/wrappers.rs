@@ -101,7 +101,10 @@ pub(crate) fn make_function_wrappers(
             Some(FnArg::Receiver(ref receiver)) => receiver.mutability.is_some(),
             _ => false,
         };
-        if is_mut {
+        if opts.shared {
+            // eg. Counter::increment(&mut self), locking the `Shared<Counter>`
+            quote! { extendr_api::Shared::<#self_ty>::try_from(&_self_robj)?.lock().#rust_name }
+        } else if is_mut {
             // eg. Person::name(&mut self)
             quote! { extendr_api::unwrap_or_throw_error(
                 <&mut #self_ty>::try_from(&mut _self_robj)
@@ -149,6 +152,12 @@ pub(crate) fn make_function_wrappers(
     let actual_args: Punctuated<Expr, Token![,]> =
         inputs.iter().filter_map(translate_actual).collect();
 
+    // references to `#[extendr]` objects, and `ExternalPtr`s, are borrowed for the duration of the call
+    let borrow_args: Vec<syn::Stmt> = inputs
+        .iter()
+        .filter_map(|input| translate_borrow(input, opts.shared))
+        .collect();
+
     let meta_args: Vec<Expr> = inputs
         .iter_mut()
         .map(|input| translate_meta_arg(input, self_ty))
@@ -213,6 +222,19 @@ pub(crate) fn make_function_wrappers(
         }
     };
 
+    // `Serde<T>` return values are converted fallibly, raising serializer errors in R.
+    let return_is_serde = match sig.output {
+        syn::ReturnType::Type(_, ref return_type) => is_serde_type(return_type),
+        syn::ReturnType::Default => false,
+    };
+
+    if return_is_ref_self && opts.shared {
+        return Err(syn::Error::new_spanned(
+            &sig.output,
+            "methods of `shared` types cannot return references to `self`",
+        ));
+    }
+
     let return_type_conversion = if return_is_ref_self {
         // instead of converting &Self / &mut Self, pass on the passed
         // ExternalPtr<Self>
@@ -220,24 +242,34 @@ pub(crate) fn make_function_wrappers(
             let return_ref_to_self = #call_name(#actual_args);
 
             #(
-            let arg_ref = extendr_api::R_ExternalPtrAddr(#sexp_args)
-                .cast::<Box<dyn std::any::Any>>()
-                .as_ref()
-                .unwrap()
-                .downcast_ref::<#self_ty>()
-                .unwrap();
-            if std::ptr::addr_eq(
+            let arg_ref = <&#self_ty>::try_from(&extendr_api::Robj::from_sexp(#sexp_args))
+                .map(std::ptr::from_ref);
+            if arg_ref.is_ok_and(|arg_ref| std::ptr::addr_eq(
                 arg_ref,
-                std::ptr::from_ref(return_ref_to_self)) {
+                std::ptr::from_ref(return_ref_to_self))) {
                     return Ok(extendr_api::Robj::from_sexp(#sexp_args))
                 }
             )*
             Err(Error::ExpectedExternalPtrReference.into())
         )
+    } else if return_is_serde {
+        quote!(Ok(extendr_api::Robj::try_from(#call_name(#actual_args))?))
     } else {
         quote!(Ok(extendr_api::Robj::from(#call_name(#actual_args))))
     };
 
+    // Borrows are released if R unwinds out of the call, e.g. on an R error.
+    let call_with_borrows = if borrow_args.is_empty() {
+        quote!(#return_type_conversion)
+    } else {
+        quote!(
+            extendr_api::BorrowGuard::scope(|| -> std::result::Result<extendr_api::Robj, Box<dyn std::error::Error>> {
+                #(#borrow_args)*
+                #return_type_conversion
+            })
+        )
+    };
+
     // TODO: the unsafe in here is unnecessary
     wrappers.push(parse_quote!(
         #[no_mangle]
@@ -254,7 +286,7 @@ pub(crate) fn make_function_wrappers(
             > = unsafe {
                     std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || -> std::result::Result<extendr_api::Robj, Box<dyn std::error::Error>> {
                         #(#convert_args)*
-                        #return_type_conversion
+                        #call_with_borrows
                     }))
                 };
 
@@ -486,6 +518,7 @@ fn translate_meta_arg(input: &mut FnArg, self_ty: Option<&syn::Type>) -> syn::Re
 }
 
 // Get defaults from #[extendr(default = "value")] attribute.
+// #[extendr(serde)] is removed too, see `has_serde_attr`.
 fn get_defaults(attrs: &mut Vec<syn::Attribute>) -> Option<String> {
     use syn::Lit;
 
@@ -497,6 +530,7 @@ fn get_defaults(attrs: &mut Vec<syn::Attribute>) -> Option<String> {
             if meta_list.path.is_ident("extendr") {
                 let mut default_value = None;
                 let mut theres_default = false;
+                let mut theres_serde = false;
 
                 let parse_result = meta_list.parse_nested_meta(|meta| {
                     if meta.path.is_ident("default") {
@@ -505,6 +539,8 @@ fn get_defaults(attrs: &mut Vec<syn::Attribute>) -> Option<String> {
                         if let Ok(Lit::Str(litstr)) = value.parse() {
                             default_value = Some(litstr.value());
                         }
+                    } else if meta.path.is_ident("serde") {
+                        theres_serde = true;
                     }
                     Ok(())
                 });
@@ -513,6 +549,9 @@ fn get_defaults(attrs: &mut Vec<syn::Attribute>) -> Option<String> {
                     res = default_value;
                     continue;
                 }
+                if parse_result.is_ok() && theres_serde {
+                    continue;
+                }
             }
         }
 
@@ -576,6 +615,38 @@ fn translate_to_robj(input: &FnArg) -> syn::Result<syn::Stmt> {
     }
 }
 
+// Arguments of type `Serde<T>` are converted with serde, naming the argument in errors.
+fn is_serde_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "Serde"),
+        _ => false,
+    }
+}
+
+// Arguments marked with #[extendr(serde)] are converted as `Serde<T>`.
+fn has_serde_attr(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let mut theres_serde = false;
+        if let syn::Meta::List(ref meta_list) = attr.meta {
+            if meta_list.path.is_ident("extendr") {
+                let _ = meta_list.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("serde") {
+                        theres_serde = true;
+                    } else if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<Expr>()?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        theres_serde
+    })
+}
+
 // Generate actual argument list for the call (ie. a list of conversions).
 fn translate_actual(input: &FnArg) -> Option<Expr> {
     match input {
@@ -583,7 +654,12 @@ fn translate_actual(input: &FnArg) -> Option<Expr> {
             let pat = &pattype.pat.as_ref();
             if let syn::Pat::Ident(ref ident) = pat {
                 let varname = format_ident!("_{}_robj", ident.ident);
-                if type_needs_mut_robj(&pattype.ty) {
+                let name = sanitize_identifier(ident.ident.clone()).to_string();
+                if is_serde_type(&pattype.ty) {
+                    Some(parse_quote! { extendr_api::Serde::from_arg(&#varname, #name)? })
+                } else if has_serde_attr(&pattype.attrs) {
+                    Some(parse_quote! { extendr_api::Serde::from_arg(&#varname, #name)?.0 })
+                } else if type_needs_mut_robj(&pattype.ty) {
                     Some(parse_quote! { (&mut #varname).try_into()? })
                 } else {
                     Some(parse_quote! { (&#varname).try_into()? })
@@ -599,6 +675,51 @@ fn translate_actual(input: &FnArg) -> Option<Expr> {
     }
 }
 
+// Arguments of type `ExternalPtr<T>` give mutable access to the object.
+fn is_external_ptr_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "ExternalPtr"),
+        _ => false,
+    }
+}
+
+// Track the borrow of reference and `ExternalPtr<T>` arguments, so that an object
+// cannot be borrowed mutably twice, e.g. by a callback to R that calls another method.
+// The mutex of a `shared` object is exclusive, so `self` is always borrowed mutably.
+fn translate_borrow(input: &FnArg, shared: bool) -> Option<syn::Stmt> {
+    let (robj, guard, name, mutable) = match input {
+        FnArg::Typed(ref pattype) => {
+            let mutable = match pattype.ty.as_ref() {
+                Type::Reference(ref reference) => reference.mutability.is_some(),
+                ty if is_external_ptr_type(ty) => true,
+                _ => return None,
+            };
+            let syn::Pat::Ident(ref ident) = pattype.pat.as_ref() else {
+                return None;
+            };
+            (
+                format_ident!("_{}_robj", ident.ident),
+                format_ident!("_{}_borrow", ident.ident),
+                ident.ident.to_string(),
+                mutable,
+            )
+        }
+        FnArg::Receiver(ref receiver) => (
+            format_ident!("_self_robj"),
+            format_ident!("_self_borrow"),
+            "self".to_string(),
+            shared || receiver.mutability.is_some(),
+        ),
+    };
+    Some(parse_quote! {
+        let #guard = extendr_api::BorrowGuard::new(&#robj, #mutable, #name)?;
+    })
+}
+
 // Get a single named literal from a list of attributes.
 // eg. #[default="xyz"]
 // Remove the attribute from the list.
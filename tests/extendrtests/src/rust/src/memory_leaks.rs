@@ -31,6 +31,12 @@ fn leak_negative_control(x: Robj) {
     drop(x)
 }
 
+// The number of objects protected by extendr.
+#[extendr]
+fn protected_objects() -> i32 {
+    extendr_api::ownership::protection_stats().protected as i32
+}
+
 // Macro to generate exports.
 // This ensures exported functions are registered with R.
 // See corresponding C code in `entrypoint.c`.
@@ -43,5 +49,6 @@ extendr_module! {
     fn leak_unwrap_doubles;
     fn leak_positive_control;
     fn leak_negative_control;
+    fn protected_objects;
 
 }